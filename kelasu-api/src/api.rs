@@ -0,0 +1,448 @@
+//! The routes of the service, apart from HTTP itself. `openapi.yaml` documents them.
+
+use kelasu_game::{
+    board::{GameState, Move, Position},
+    eval::Weights,
+    json::Json,
+    piece::Team,
+    record::Record,
+    render::{describe, Renderer, Style},
+    search::{search, Limits},
+    setup::Setup,
+    Game,
+};
+use std::{
+    collections::BTreeMap,
+    sync::{atomic::AtomicBool, Mutex},
+    time::Duration,
+};
+
+#[cfg(test)]
+mod tests;
+
+pub const OPENAPI: &str = include_str!("../openapi.yaml");
+
+/// how many games the service keeps before it asks for some to be deleted.
+const MAX_GAMES: usize = 10_000;
+const DEFAULT_DEPTH: u32 = 4;
+/// evaluations are cut off here, so that one request cannot keep a thread forever.
+const MAX_DEPTH: u32 = 8;
+const MAX_TIME: Duration = Duration::from_secs(10);
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Response {
+    pub status: u16,
+    pub content_type: &'static str,
+    pub body: String,
+}
+
+impl Response {
+    fn json(status: u16, json: Json) -> Self {
+        Self {
+            status,
+            content_type: "application/json",
+            body: json.to_string(),
+        }
+    }
+
+    fn text(body: String) -> Self {
+        Self {
+            status: 200,
+            content_type: "text/plain; charset=utf-8",
+            body,
+        }
+    }
+
+    fn error(status: u16, message: impl ToString) -> Self {
+        Self::json(
+            status,
+            Json::object([("error", message.to_string().into())]),
+        )
+    }
+}
+
+/// a game, with everything needed to replay it.
+#[derive(Debug, Clone)]
+struct Entry {
+    start: Position,
+    actions: Vec<Move>,
+    game: Game,
+}
+
+#[derive(Debug, Default)]
+struct Games {
+    games: BTreeMap<u64, Entry>,
+    next_id: u64,
+}
+
+/// the games, shared by every request.
+#[derive(Debug)]
+pub struct Api {
+    games: Mutex<Games>,
+    weights: Weights,
+}
+
+fn state_json(id: u64, entry: &Entry) -> Json {
+    let game = &entry.game;
+    let (draw_offer, result) = match game.state {
+        GameState::Ongoing { draw_offer } => (draw_offer.map(Team::name).into(), Json::Null),
+        GameState::Finished(winner) => (Json::Null, winner.name().into()),
+    };
+    let position = game.position().to_string();
+    // the board is the first field of the position, with its rows separated by `/`.
+    let board = position
+        .split(' ')
+        .next()
+        .unwrap_or_default()
+        .split('/')
+        .map(Json::from)
+        .collect::<Vec<_>>();
+    let mut locked = game.locked_tiles.iter().map(|p| p.0).collect::<Vec<_>>();
+    locked.sort();
+    Json::object([
+        ("id", id.into()),
+        (
+            "status",
+            if game.is_ongoing() {
+                "ongoing"
+            } else {
+                "finished"
+            }
+            .into(),
+        ),
+        ("result", result),
+        ("turn", game.turn.name().into()),
+        ("energy", u32::from(game.power).into()),
+        ("draw_offer", draw_offer),
+        (
+            "locked",
+            locked
+                .into_iter()
+                .map(|p| format!("{p:02}").into())
+                .collect::<Vec<_>>()
+                .into(),
+        ),
+        (
+            "turns_until_draw",
+            u32::from(game.turns_until_draw()).into(),
+        ),
+        ("board", board.into()),
+        ("position", position.into()),
+        ("start", entry.start.to_string().into()),
+        (
+            "actions",
+            entry
+                .actions
+                .iter()
+                .map(|action| action.to_string().into())
+                .collect::<Vec<_>>()
+                .into(),
+        ),
+    ])
+}
+
+/// the JSON body, which may be empty.
+fn body_json(body: &str) -> Result<Json, Response> {
+    if body.trim().is_empty() {
+        return Ok(Json::Object(vec![]));
+    }
+    match body.parse::<Json>() {
+        Ok(json @ Json::Object(_)) => Ok(json),
+        Ok(_) => Err(Response::error(400, "The body must be a JSON object.")),
+        Err(e) => Err(Response::error(400, e)),
+    }
+}
+
+/// the string field of the body, if it is there.
+fn body_string<'a>(json: &'a Json, key: &str) -> Result<Option<&'a str>, Response> {
+    match json.get(key) {
+        None | Some(Json::Null) => Ok(None),
+        Some(Json::String(s)) => Ok(Some(s)),
+        Some(_) => Err(Response::error(400, format!("`{key}` must be a string."))),
+    }
+}
+
+fn body_number(json: &Json, key: &str) -> Result<Option<u64>, Response> {
+    match json.get(key) {
+        None | Some(Json::Null) => Ok(None),
+        Some(n) => n
+            .as_u64()
+            .map(Some)
+            .ok_or_else(|| Response::error(400, format!("`{key}` must be a whole number."))),
+    }
+}
+
+impl Api {
+    pub fn new(weights: Weights) -> Self {
+        Self {
+            games: Mutex::default(),
+            weights,
+        }
+    }
+
+    /// answers one request. `url` is the path with its query, as `/games/1/text?format=box`.
+    pub fn handle(&self, method: &str, url: &str, body: &str) -> Response {
+        let (path, query) = url.split_once('?').unwrap_or((url, ""));
+        let query = query
+            .split('&')
+            .filter_map(|pair| pair.split_once('='))
+            .collect::<Vec<_>>();
+        let segments = path.trim_matches('/').split('/').collect::<Vec<_>>();
+
+        let id = match segments.get(1).map(|id| id.parse::<u64>()) {
+            Some(Ok(id)) => Some(id),
+            Some(Err(_)) if segments[0] == "games" => {
+                return Response::error(404, "Game ids are numbers.")
+            }
+            _ => None,
+        };
+        let result = match (method, segments.as_slice(), id) {
+            ("GET", ["openapi.yaml"], _) => Ok(Response {
+                status: 200,
+                content_type: "application/yaml",
+                body: OPENAPI.to_owned(),
+            }),
+            ("GET", ["games"], _) => Ok(self.list()),
+            ("POST", ["games"], _) => self.create(body),
+            ("GET", ["games", _], Some(id)) => {
+                self.with(id, |entry| Ok(Response::json(200, state_json(id, entry))))
+            }
+            ("DELETE", ["games", _], Some(id)) => self.delete(id),
+            ("GET", ["games", _, "text"], Some(id)) => {
+                let format = query
+                    .iter()
+                    .find(|(key, _)| *key == "format")
+                    .map_or("plain", |(_, value)| value);
+                self.with(id, |entry| text(entry, format))
+            }
+            ("GET", ["games", _, "actions"], Some(id)) => self.with(id, |entry| {
+                let actions = entry
+                    .game
+                    .legal_actions()
+                    .iter()
+                    .map(|action| action.action().to_string().into())
+                    .collect::<Vec<_>>();
+                Ok(Response::json(200, actions.into()))
+            }),
+            ("POST", ["games", _, "actions"], Some(id)) => self.play(id, body),
+            ("POST", ["games", _, "evaluation"], Some(id)) => self.evaluate(id, body),
+            (
+                _,
+                ["openapi.yaml"]
+                | ["games"]
+                | ["games", _]
+                | ["games", _, "text" | "actions" | "evaluation"],
+                _,
+            ) => Err(Response::error(405, "That method is not allowed here.")),
+            _ => Err(Response::error(404, "There is nothing here.")),
+        };
+        result.unwrap_or_else(|response| response)
+    }
+
+    /// runs `f` on a copy of the game, so that the games are not locked for long.
+    fn with(
+        &self,
+        id: u64,
+        f: impl FnOnce(&Entry) -> Result<Response, Response>,
+    ) -> Result<Response, Response> {
+        let entry = self.games.lock().unwrap().games.get(&id).cloned();
+        match entry {
+            Some(entry) => f(&entry),
+            None => Err(Response::error(404, format!("There is no game {id}."))),
+        }
+    }
+
+    fn list(&self) -> Response {
+        let games = self.games.lock().unwrap();
+        let list = games
+            .games
+            .iter()
+            .map(|(&id, entry)| {
+                let state = state_json(id, entry);
+                Json::object([
+                    ("id", id.into()),
+                    ("status", state.get("status").cloned().unwrap_or(Json::Null)),
+                    ("result", state.get("result").cloned().unwrap_or(Json::Null)),
+                    ("actions", entry.actions.len().into()),
+                ])
+            })
+            .collect::<Vec<_>>();
+        Response::json(200, list.into())
+    }
+
+    fn create(&self, body: &str) -> Result<Response, Response> {
+        let json = body_json(body)?;
+        let game = match (
+            body_string(&json, "setup")?,
+            body_string(&json, "position")?,
+        ) {
+            (Some(_), Some(_)) => {
+                return Err(Response::error(
+                    400,
+                    "Give a setup or a position, not both.",
+                ))
+            }
+            (Some(setup), None) => setup
+                .parse::<Setup>()
+                .map_err(|e| Response::error(400, e))?
+                .game(),
+            (None, Some(position)) => position
+                .parse::<Position>()
+                .map_err(|e| Response::error(400, e))?
+                .into(),
+            (None, None) => Game::new(),
+        };
+        let entry = Entry {
+            start: game.position(),
+            actions: vec![],
+            game,
+        };
+
+        let mut games = self.games.lock().unwrap();
+        if games.games.len() >= MAX_GAMES {
+            return Err(Response::error(
+                503,
+                "There are too many games. Delete some first.",
+            ));
+        }
+        games.next_id += 1;
+        let id = games.next_id;
+        let response = Response::json(201, state_json(id, &entry));
+        games.games.insert(id, entry);
+        Ok(response)
+    }
+
+    fn delete(&self, id: u64) -> Result<Response, Response> {
+        match self.games.lock().unwrap().games.remove(&id) {
+            Some(_) => Ok(Response {
+                status: 204,
+                content_type: "text/plain; charset=utf-8",
+                body: String::new(),
+            }),
+            None => Err(Response::error(404, format!("There is no game {id}."))),
+        }
+    }
+
+    fn play(&self, id: u64, body: &str) -> Result<Response, Response> {
+        let json = body_json(body)?;
+        let Some(action) = body_string(&json, "action")? else {
+            return Err(Response::error(400, "`action` is missing."));
+        };
+        let action = action
+            .parse::<Move>()
+            .map_err(|e| Response::error(400, e))?;
+
+        let mut games = self.games.lock().unwrap();
+        let Some(entry) = games.games.get_mut(&id) else {
+            return Err(Response::error(404, format!("There is no game {id}.")));
+        };
+        let verified = entry
+            .game
+            .verify_action(action.clone())
+            .map_err(|e| Response::error(422, e))?;
+        entry.game.make_move(verified);
+        entry.actions.push(action);
+        Ok(Response::json(200, state_json(id, entry)))
+    }
+
+    fn evaluate(&self, id: u64, body: &str) -> Result<Response, Response> {
+        let json = body_json(body)?;
+        let depth = body_number(&json, "depth")?.map(|d| d.clamp(1, MAX_DEPTH.into()) as u32);
+        let time = body_number(&json, "time")?.map(|ms| Duration::from_millis(ms).min(MAX_TIME));
+        let limits = Limits {
+            depth: depth.or(time.is_none().then_some(DEFAULT_DEPTH)),
+            time: Some(time.unwrap_or(MAX_TIME)),
+        };
+
+        self.with(id, |entry| {
+            let game = &entry.game;
+            if !game.is_ongoing() {
+                return Err(Response::error(409, "The game is over."));
+            }
+            let evaluation = self.weights.evaluate(&game.board, game.turn);
+            let terms = evaluation
+                .scores
+                .iter()
+                .filter(|s| s.score() != 0)
+                .map(|s| (s.term.to_string(), s.score().into()))
+                .collect();
+            let mut last = None;
+            let best = search(
+                game,
+                &self.weights,
+                &limits,
+                &AtomicBool::new(false),
+                |info| {
+                    last = Some(info.clone());
+                },
+            );
+            let (score, depth, nodes, pv) = match last {
+                Some(info) => (
+                    info.score.into(),
+                    info.depth.into(),
+                    info.nodes.into(),
+                    info.pv.iter().map(|a| a.to_string().into()).collect(),
+                ),
+                None => (Json::Null, Json::Null, Json::Null, vec![]),
+            };
+            Ok(Response::json(
+                200,
+                Json::object([
+                    ("turn", game.turn.name().into()),
+                    ("static", evaluation.total().into()),
+                    ("terms", Json::Object(terms)),
+                    ("best", best.map(|action| action.to_string()).into()),
+                    ("score", score),
+                    ("depth", depth),
+                    ("nodes", nodes),
+                    ("pv", pv.into()),
+                ]),
+            ))
+        })
+    }
+}
+
+/// the game as text, in one of the formats of `GET /games/{id}/text`.
+fn text(entry: &Entry, format: &str) -> Result<Response, Response> {
+    let game = &entry.game;
+    let style = match format {
+        "words" => return Ok(Response::text(describe(game))),
+        "record" => {
+            let record = Record {
+                start: (entry.start != Game::new().position()).then(|| entry.start.clone()),
+                result: match game.state {
+                    GameState::Finished(winner) => Some(winner),
+                    GameState::Ongoing { .. } => None,
+                },
+                actions: entry.actions.clone(),
+                ..Default::default()
+            };
+            return Ok(Response::text(record.to_string()));
+        }
+        "plain" => Style::Plain,
+        "box" => Style::Box,
+        "compact" => Style::Compact,
+        _ => {
+            return Err(Response::error(
+                400,
+                "The format is plain, box, compact, words or record.",
+            ))
+        }
+    };
+    let renderer = Renderer {
+        style,
+        last_move: entry.actions.last().cloned(),
+        energy: game.is_ongoing(),
+        ..Default::default()
+    };
+    let board = renderer.render(game);
+    if style == Style::Compact {
+        return Ok(Response::text(board));
+    }
+    Ok(Response::text(format!(
+        "{}\n{}'s turn.\nTurns until a draw: {}.\n\n{board}",
+        game.state,
+        game.turn,
+        game.turns_until_draw()
+    )))
+}
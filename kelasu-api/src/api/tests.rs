@@ -0,0 +1,154 @@
+use super::*;
+use kelasu_game::board::Winner;
+
+fn json(response: &Response) -> Json {
+    assert_eq!(
+        response.content_type, "application/json",
+        "{}",
+        response.body
+    );
+    response.body.parse().unwrap()
+}
+
+#[test]
+pub(crate) fn test_play() {
+    let api = Api::new(Weights::default());
+    let created = api.handle("POST", "/games", "");
+    assert_eq!(created.status, 201);
+    let state = json(&created);
+    assert_eq!(state.get("id"), Some(&Json::from(1)));
+    assert_eq!(state.get("turn").and_then(Json::as_str), Some("blue"));
+    assert_eq!(
+        state.get("board").and_then(Json::as_array).map(<[_]>::len),
+        Some(10)
+    );
+
+    let actions = json(&api.handle("GET", "/games/1/actions", ""));
+    let actions = actions.as_array().unwrap();
+    assert!(actions.contains(&"move 11 to 21".into()));
+
+    let played = api.handle("POST", "/games/1/actions", r#"{"action":"move 11 to 21"}"#);
+    assert_eq!(played.status, 200);
+    let state = json(&played);
+    assert_eq!(state.get("energy"), Some(&Json::from(3)));
+    assert_eq!(state.get("locked"), Some(&Json::from(vec!["21".into()])));
+    assert_eq!(state, json(&api.handle("GET", "/games/1", "")));
+
+    // illegal actions are turned away, and change nothing.
+    let illegal = api.handle("POST", "/games/1/actions", r#"{"action":"move 21 to 31"}"#);
+    assert_eq!(illegal.status, 422);
+    assert!(json(&illegal).get("error").is_some());
+    assert_eq!(api.handle("POST", "/games/1/actions", "{}").status, 400);
+    assert_eq!(api.handle("POST", "/games/1/actions", "move").status, 400);
+    assert_eq!(state, json(&api.handle("GET", "/games/1", "")));
+
+    let resigned = json(&api.handle("POST", "/games/1/actions", r#"{"action":"resign"}"#));
+    assert_eq!(
+        resigned.get("status").and_then(Json::as_str),
+        Some("finished")
+    );
+    assert_eq!(resigned.get("result").and_then(Json::as_str), Some("red"));
+    assert_eq!(api.handle("POST", "/games/1/evaluation", "").status, 409);
+
+    let record = api.handle("GET", "/games/1/text?format=record", "");
+    assert!(record.body.contains("move 11 to 21"), "{}", record.body);
+    // the record of a finished game keeps its result, and replays to it.
+    let record = record.body.parse::<Record>().unwrap();
+    assert_eq!(record.result, Some(Winner(Some(Team::Red))));
+    assert_eq!(
+        record.replay().unwrap().state,
+        GameState::Finished(Winner(Some(Team::Red)))
+    );
+    assert_eq!(api.handle("DELETE", "/games/1", "").status, 204);
+    assert_eq!(api.handle("GET", "/games/1", "").status, 404);
+}
+
+#[test]
+pub(crate) fn test_create() {
+    let api = Api::new(Weights::default());
+    let wings = json(&api.handle("POST", "/games", r#"{"setup":"wings"}"#));
+    let expected = Setup::Kelasu960(9).game().position().to_string();
+    assert_eq!(
+        wings.get("position").and_then(Json::as_str),
+        Some(expected.as_str())
+    );
+
+    let position = wings.get("position").unwrap().clone();
+    let from_position = api.handle(
+        "POST",
+        "/games",
+        &Json::object([("position", position.clone())]).to_string(),
+    );
+    assert_eq!(json(&from_position).get("start"), Some(&position));
+
+    for body in [
+        r#"{"setup":"nowhere"}"#,
+        r#"{"position":"nonsense"}"#,
+        r#"{"setup":"wings","position":"nonsense"}"#,
+        r#"{"setup":3}"#,
+        "[]",
+    ] {
+        assert_eq!(api.handle("POST", "/games", body).status, 400, "{body}");
+    }
+    let list = json(&api.handle("GET", "/games", ""));
+    assert_eq!(list.as_array().map(<[_]>::len), Some(2));
+}
+
+#[test]
+pub(crate) fn test_text_and_evaluation() {
+    let api = Api::new(Weights::default());
+    api.handle("POST", "/games", "");
+
+    let plain = api.handle("GET", "/games/1/text", "");
+    assert!(plain.content_type.starts_with("text/plain"));
+    assert!(
+        plain.body.starts_with("Ongoing match.\nBlue's turn."),
+        "{}",
+        plain.body
+    );
+    for format in ["box", "compact", "words", "record"] {
+        let text = api.handle("GET", &format!("/games/1/text?format={format}"), "");
+        assert_eq!(text.status, 200, "{format}");
+    }
+    assert_eq!(
+        api.handle("GET", "/games/1/text?format=gif", "").status,
+        400
+    );
+
+    let evaluation = json(&api.handle("POST", "/games/1/evaluation", r#"{"depth":1}"#));
+    assert_eq!(evaluation.get("depth"), Some(&Json::from(1)));
+    let best = evaluation.get("best").and_then(Json::as_str).unwrap();
+    assert!(best.parse::<Move>().is_ok());
+    assert!(evaluation
+        .get("score")
+        .and_then(|s| s.get("value"))
+        .is_some());
+    assert_eq!(
+        api.handle("POST", "/games/1/evaluation", r#"{"depth":"deep"}"#)
+            .status,
+        400
+    );
+}
+
+#[test]
+pub(crate) fn test_routes() {
+    let api = Api::new(Weights::default());
+    assert_eq!(api.handle("GET", "/nowhere", "").status, 404);
+    assert_eq!(api.handle("GET", "/games/one", "").status, 404);
+    assert_eq!(api.handle("GET", "/games/7/actions", "").status, 404);
+    assert_eq!(api.handle("PUT", "/games", "").status, 405);
+    assert_eq!(api.handle("GET", "/games/1/evaluation", "").status, 405);
+
+    // every route is documented.
+    let openapi = api.handle("GET", "/openapi.yaml", "");
+    assert_eq!(openapi.status, 200);
+    for path in [
+        "/games:",
+        "/games/{id}:",
+        "/games/{id}/text:",
+        "/games/{id}/actions:",
+        "/games/{id}/evaluation:",
+    ] {
+        assert!(openapi.body.contains(path), "{path}");
+    }
+}
@@ -0,0 +1,97 @@
+use api::Api;
+use kelasu_game::eval::Weights;
+use std::{env, io::Read, process::exit, sync::Arc, thread};
+use tiny_http::{Header, Method, Request, Response, Server};
+
+mod api;
+
+const USAGE: &str = "\
+    Usage: kelasu-api [--bind <address>] [--port <port>] [--weights <file>]\n\
+    \n\
+    Serves Kelasu games and analysis over HTTP, as JSON and text.\n\
+    The routes are documented at /openapi.yaml.\n\
+      \t--bind     the address to listen on (default 127.0.0.1, this computer only)\n\
+      \t--port     the port to listen on (default 8080)\n\
+      \t--weights  the evaluation weights, from a file (default the built-in ones)";
+
+/// request bodies are cut off here.
+const MAX_BODY: u64 = 64 * 1024;
+
+fn fail(message: impl std::fmt::Display) -> ! {
+    eprintln!("{message}\n\n{USAGE}");
+    exit(1);
+}
+
+fn header(name: &str, value: &str) -> Header {
+    // the names and values are all ASCII.
+    Header::from_bytes(name, value).unwrap()
+}
+
+fn respond(api: &Api, mut request: Request) {
+    // other tools' web pages may call the service from anywhere.
+    let cors = header("Access-Control-Allow-Origin", "*");
+    if *request.method() == Method::Options {
+        let response = Response::empty(204)
+            .with_header(cors)
+            .with_header(header(
+                "Access-Control-Allow-Methods",
+                "GET, POST, DELETE, OPTIONS",
+            ))
+            .with_header(header("Access-Control-Allow-Headers", "Content-Type"));
+        let _ = request.respond(response);
+        return;
+    }
+
+    let mut body = String::new();
+    let reply = match request.as_reader().take(MAX_BODY).read_to_string(&mut body) {
+        Ok(_) => api.handle(request.method().as_str(), request.url(), &body),
+        Err(_) => api::Response {
+            status: 400,
+            content_type: "application/json",
+            body: r#"{"error":"The body must be UTF-8."}"#.to_owned(),
+        },
+    };
+    let response = Response::from_string(reply.body)
+        .with_status_code(reply.status)
+        .with_header(header("Content-Type", reply.content_type))
+        .with_header(cors);
+    // a client that hung up does not need an answer.
+    let _ = request.respond(response);
+}
+
+fn main() {
+    let mut bind = "127.0.0.1".to_owned();
+    let mut port = 8080u16;
+    let mut weights = Weights::default();
+
+    let mut args = env::args().skip(1);
+    while let Some(arg) = args.next() {
+        let Some(value) = args.next() else {
+            fail(format!("{arg} needs a value."));
+        };
+        match arg.as_str() {
+            "--bind" => bind = value,
+            "--port" => {
+                port = value
+                    .parse()
+                    .unwrap_or_else(|_| fail("--port needs a number."))
+            }
+            "--weights" => {
+                weights = Weights::load(&value).unwrap_or_else(|e| fail(format!("{value}: {e}")))
+            }
+            _ => fail(format!("Unexpected argument: {arg}")),
+        }
+    }
+
+    let server = Server::http((bind.as_str(), port)).unwrap_or_else(|e| {
+        eprintln!("Could not listen on {bind}:{port}: {e}");
+        exit(1);
+    });
+    eprintln!("Listening on http://{bind}:{port}/");
+    let api = Arc::new(Api::new(weights));
+    for request in server.incoming_requests() {
+        // evaluations take a while, so every request gets a thread.
+        let api = api.clone();
+        thread::spawn(move || respond(&api, request));
+    }
+}
@@ -0,0 +1,90 @@
+use crate::{game::board_repr, Context, Error};
+
+use kelasu_game::{
+    board::Position,
+    protocol::{Engine, EngineError},
+    search::{Info, Limits},
+    setup::Setup,
+    Game,
+};
+use std::time::Duration;
+use tracing::info;
+
+/// how long the engine may think about a position.
+const THINKING_TIME: Duration = Duration::from_secs(5);
+
+/// the engine command line in `ENGINE`, e.g. `kelasu-engine`, if there is one.
+fn engine_command() -> Option<Vec<String>> {
+    let command = dotenv::var("ENGINE").ok()?;
+    let words = command
+        .split_whitespace()
+        .map(str::to_owned)
+        .collect::<Vec<_>>();
+    (!words.is_empty()).then_some(words)
+}
+
+/// asks the engine for its last info line and best action.
+fn ask(command: &[String], position: &Position) -> Result<(Option<Info>, String), EngineError> {
+    let mut engine = Engine::spawn(&command[0], &command[1..])?;
+    engine.new_game()?;
+    engine.set_position(Some(position), &[])?;
+    let limits = Limits {
+        depth: None,
+        time: Some(THINKING_TIME),
+    };
+    let mut last = None;
+    let best = engine.go(&limits, |info| last = Some(info.clone()))?;
+    let best = best.map_or("none".to_owned(), |action| action.to_string());
+    Ok((last, format!("{} ({})", best, engine.name)))
+}
+
+/// Asks the engine about a position.
+#[poise::command(slash_command, prefix_command)]
+pub async fn evaluate(
+    ctx: Context<'_>,
+    #[description = "A setup, e.g. `wings`, or a position string. The standard setup if left out."]
+    position: Option<String>,
+) -> Result<(), Error> {
+    info!("{} invoked /evaluate {position:?}", ctx.author().name);
+    let Some(command) = engine_command() else {
+        ctx.say("There is no engine to ask.").await?;
+        return Ok(());
+    };
+    let game = match position.as_deref() {
+        None => Game::new(),
+        Some(text) => match (text.parse::<Setup>(), text.parse::<Position>()) {
+            (Ok(setup), _) => setup.game(),
+            (_, Ok(position)) => Game::from(position),
+            (Err(_), Err(e)) => {
+                ctx.say(format!("That is neither a setup nor a position: {e}"))
+                    .await?;
+                return Ok(());
+            }
+        },
+    };
+
+    ctx.defer().await?;
+    let position = game.position();
+    let answer = tokio::task::spawn_blocking(move || ask(&command, &position)).await?;
+    let response = match answer {
+        Ok((info, best)) => {
+            let mut response = format!("{}\nBest action: {best}", board_repr(&game, &[], None));
+            if let Some(info) = info {
+                let pv = info
+                    .pv
+                    .iter()
+                    .map(ToString::to_string)
+                    .collect::<Vec<_>>()
+                    .join("; ");
+                response.push_str(&format!(
+                    "\nScore: {} at depth {}\nLine: {pv}",
+                    info.score, info.depth
+                ));
+            }
+            response
+        }
+        Err(e) => format!("The engine failed: {e}"),
+    };
+    ctx.say(response).await?;
+    Ok(())
+}
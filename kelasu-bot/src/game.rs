@@ -1,6 +1,8 @@
 use kelasu_game::{
     board::{GameState, Move, Pos, VerifiedMove, Winner},
-    piece::{Icon, PieceKind, Team},
+    piece::{PieceKind, Team},
+    render::{Renderer, Style},
+    setup::Setup,
     Game as BoardGame,
 };
 use poise::{
@@ -25,133 +27,37 @@ pub struct Game {
     pub lobby: LobbyId,
     pub blue: UserId,
     pub red: UserId,
+    pub setup: Setup,
     pub game: BoardGame,
+    // every action so far, for the analysis after the game.
+    pub actions: Vec<Move>,
+}
+
+/// the board in a code block, with the selected tiles fenced in.
+pub fn board_repr(game: &BoardGame, positions: &[Pos], held_digit: Option<i8>) -> String {
+    let renderer = Renderer {
+        style: Style::Box,
+        selected: positions.to_vec(),
+        cursor: positions.last().copied(),
+        cursor_row: held_digit,
+        energy: true,
+        ..Default::default()
+    };
+    format!("```hs\n{}```", renderer.render(game))
 }
 
 impl Game {
-    pub fn new(lobby: LobbyId, blue: UserId, red: UserId) -> Self {
+    pub fn new(lobby: LobbyId, blue: UserId, red: UserId, setup: Setup) -> Self {
         Self {
             lobby,
             blue,
             red,
-            game: BoardGame::new(),
+            setup,
+            game: setup.game(),
+            actions: vec![],
         }
     }
 
-    fn board_repr(&self, positions: &[Pos], held_digit: Option<i8>) -> String {
-        /*
-        000: None
-        001: Left
-        010: Right
-        011: Both
-        100: Selected Left
-        101: Selected Right
-        110: Cursor Left
-        111: Cursor Right
-        */
-
-        fn xy(&Pos(p): &Pos) -> [usize; 2] {
-            [p as usize % 10, p as usize / 10]
-        }
-
-        let fences = {
-            let mut fences = [[0b_000_u8; 11]; 10];
-
-            for [x, y] in positions.iter().map(xy) {
-                fences[y][x + 0] |= 0b_001;
-                fences[y][x + 1] |= 0b_010;
-            }
-
-            if let Some([x, y]) = positions.last().map(xy) {
-                fences[y][x + 0] = 0b_100;
-                fences[y][x + 1] = 0b_101;
-            }
-
-            if let Some(y) = held_digit {
-                for x in 0..11 {
-                    if fences[y as usize][x] == 0 {
-                        fences[y as usize][x] = 0b110;
-                    }
-                }
-            }
-
-            fences
-        };
-
-        fn fence_icon(fence: u8) -> char {
-            b" []|()-"[fence as usize] as char
-        }
-
-        // this string is only for reference, it will not actually be displayed :P
-        let board_repr_len = "\
-            ```hs\n\
-            Energy:# # # # # # # #\n\
-            \n\
-               ╔[0-1-2-3-4-5-6-7-8-9]╗\n\
-             A ║[B|B(B)B B B B B B[B]║ A\n\
-             B ║ B B B B B B B B B B ║ B\n\
-             C ║ S   S  [ | | ]S   S ║ C\n\
-           \"D=║-.-.-.-.-.-.-.-.-.-.-║=D\"\n\
-             E ║         : :         ║ E\n\
-             F ║         : :         ║ F\n\
-             G ║                     ║ G\n\
-             H ║ s   s         s   s ║ H\n\
-             I ║ b b b b b b b b b b ║ I\n\
-             J ║ b b b b b b b b b b ║ J\n\
-               ╚[0-1-2-3-4-5-6-7-8-9]╝\n\
-            ```"
-        .len();
-
-        fn push_energy(out: &mut String, power: u8) {
-            out.push_str("\nEnergy:");
-            for _ in 0..power {
-                out.push_str(" #");
-            }
-            out.push('\n');
-        }
-
-        let mut out = String::with_capacity(board_repr_len);
-        out.push_str("```hs");
-        if self.game.turn == Team::Blue {
-            push_energy(&mut out, self.game.power);
-        }
-        out.push_str("\n   ╔[0-1-2-3-4-5-6-7-8-9]╗\n");
-        for (y, (row, rank)) in self.game.board.tiles.chunks(10).zip('A'..='J').enumerate() {
-            let row_selected = held_digit.map_or(false, |d| d == y as i8);
-            let line = if row_selected { b"\"=.=\"" } else { b"     " }.map(|b| b as char);
-
-            out.push(line[0]);
-            out.push(rank);
-            out.push(line[1]);
-            out.push('║');
-            for (x, tile) in row.iter().enumerate() {
-                out.push(fence_icon(fences[y][x]));
-                out.push(if tile.0.is_none() {
-                    if [[4, 4], [4, 5], [5, 4], [5, 5]].contains(&[y, x]) {
-                        ':'
-                    } else {
-                        line[2]
-                    }
-                } else {
-                    tile.icon()
-                })
-            }
-            out.push(fence_icon(fences[y][10]));
-            out.push('║');
-            out.push(line[3]);
-            out.push(rank);
-            out.push(line[4]);
-            out.push('\n');
-        }
-        out.push_str("   ╚[0-1-2-3-4-5-6-7-8-9]╝\n");
-        if self.game.turn == Team::Red {
-            push_energy(&mut out, self.game.power);
-        }
-        out.push_str("```");
-
-        out
-    }
-
     async fn select_merge(
         &self,
         ctx: Context<'_>,
@@ -331,82 +237,6 @@ impl Game {
         Ok(resigned)
     }
 
-    async fn offer_draw(
-        &self,
-        ctx: Context<'_>,
-        player: UserId,
-        opponent: UserId,
-    ) -> Result<VerifiedMove, serenity::Error> {
-        let reply = ctx
-            .send(|b| {
-                b.content(format!("<@{player}> Your opponent is offering a draw."))
-                    .components(|c| {
-                        c.create_action_row(|r| {
-                            r.create_button(|b| {
-                                b.custom_id("accept")
-                                    .label("Accept")
-                                    .emoji('🤝')
-                                    .style(ButtonStyle::Secondary)
-                            })
-                            .create_button(|b| {
-                                b.custom_id("decline")
-                                    .label("Decline")
-                                    .emoji('⛔')
-                                    .style(ButtonStyle::Primary)
-                            })
-                        })
-                    })
-            })
-            .await?;
-        let mut message = reply.message().await?.into_owned();
-
-        let mut interactions = message
-            .await_component_interactions(ctx.discord())
-            .timeout(Duration::from_secs(60 * 5))
-            .build();
-        let mut interaction;
-        let (response, p_move) = loop {
-            interaction = interactions.next().await;
-            if let Some(interaction) = &interaction {
-                info!("interaction received: {}", interaction.data.custom_id);
-                interaction.defer(&ctx.discord().http).await?;
-            }
-            break match &interaction {
-                Some(interaction) => {
-                    let from_player = interaction.user.id == player;
-                    let from_opponent = interaction.user.id == opponent;
-                    info!(from_player, from_opponent);
-                    match interaction.data.custom_id.as_str() {
-                        "accept" if from_player => ("Accepted.", Move::Draw),
-                        "accept" if from_opponent => {
-                            respond_ephemeral(ctx, interaction, "It's not your turn.").await?;
-                            continue;
-                        }
-                        "decline" if from_player => ("Declined.", Move::DeclineDraw),
-                        "decline" if from_opponent => ("Cancelled.", Move::DeclineDraw),
-                        _ => {
-                            respond_ephemeral(
-                                ctx,
-                                interaction,
-                                "You are not a player in this game.",
-                            )
-                            .await?;
-                            continue;
-                        }
-                    }
-                }
-                None => ("Timed out. Cancelling.", Move::DeclineDraw),
-            };
-        };
-        message
-            .edit(&ctx.discord().http, |m| {
-                m.content(response).components(|c| c)
-            })
-            .await?;
-
-        Ok(self.game.verify_action(p_move).unwrap())
-    }
-
     async fn make_move(
         &self,
         ctx: Context<'_>,
@@ -423,11 +253,27 @@ impl Game {
             }
         ))
         .await?;
+        // the offer stays open while the player thinks, and lapses at the end of their turn.
+        let draw_offered = matches!(
+            self.game.state,
+            GameState::Ongoing { draw_offer: Some(team) } if team != self.game.turn
+        );
+        if draw_offered && prev_turn != self.game.turn {
+            ctx.say(format!(
+                "<@{player}> Your opponent is offering a draw. \
+                Accept it, decline it, or play on and it lapses at the end of your turn."
+            ))
+            .await?;
+        }
 
         let mut held_digit = None;
         let mut positions: Vec<Pos> = Vec::with_capacity(10);
 
-        fn add_components(c: &mut CreateComponents, alphabet: bool) -> &mut CreateComponents {
+        fn add_components(
+            c: &mut CreateComponents,
+            alphabet: bool,
+            draw_offered: bool,
+        ) -> &mut CreateComponents {
             let labels = if alphabet {
                 b"ABCDEFGHIJ"
             } else {
@@ -472,7 +318,7 @@ impl Game {
                 })
                 .create_button(|b| {
                     b.custom_id("draw")
-                        .label("Draw")
+                        .label(if draw_offered { "Accept draw" } else { "Draw" })
                         .emoji('🤝')
                         .style(ButtonStyle::Primary)
                 })
@@ -481,14 +327,23 @@ impl Game {
                         .label("Resign")
                         .emoji('⚠')
                         .style(ButtonStyle::Danger)
-                })
+                });
+                if draw_offered {
+                    r.create_button(|b| {
+                        b.custom_id("decline")
+                            .label("Decline draw")
+                            .emoji('⛔')
+                            .style(ButtonStyle::Secondary)
+                    });
+                }
+                r
             })
         }
 
         let reply = ctx
             .send(|b| {
-                b.content(self.board_repr(&positions, held_digit))
-                    .components(|c| add_components(c, held_digit.is_none()))
+                b.content(board_repr(&self.game, &positions, held_digit))
+                    .components(|c| add_components(c, held_digit.is_none(), draw_offered))
             })
             .await?;
 
@@ -556,6 +411,7 @@ impl Game {
                     }
                 }
                 "draw" => MakeMove(Move::Draw),
+                "decline" => MakeMove(Move::DeclineDraw),
                 "reset" => Reset,
                 "move" => match positions.as_slice() {
                     [] => Say("Select a piece."),
@@ -638,8 +494,8 @@ impl Game {
 
             message
                 .edit(&ctx.discord().http, |m| {
-                    m.content(self.board_repr(&positions, held_digit))
-                        .components(|c| add_components(c, held_digit.is_none()))
+                    m.content(board_repr(&self.game, &positions, held_digit))
+                        .components(|c| add_components(c, held_digit.is_none(), draw_offered))
                 })
                 .await?;
         };
@@ -667,23 +523,25 @@ impl Game {
 
         let mut prev_turn = !self.game.turn;
         loop {
-            let draw_offered = match self.game.state {
-                GameState::Ongoing { draw_offered } => draw_offered,
-                GameState::Finished(winner) => return Ok(winner),
-            };
+            // keep /describe up to date.
+            ctx.data().playing.write().await.extend([
+                (self.blue, self.game.clone()),
+                (self.red, self.game.clone()),
+            ]);
+
+            if let GameState::Finished(winner) = self.game.state {
+                return Ok(winner);
+            }
 
             let [player, opponent] = match self.game.turn {
                 Team::Blue => [self.blue, self.red],
                 Team::Red => [self.red, self.blue],
             };
 
-            let p_move = if draw_offered {
-                self.offer_draw(ctx, player, opponent).await?
-            } else {
-                self.make_move(ctx, player, opponent, prev_turn).await?
-            };
+            let p_move = self.make_move(ctx, player, opponent, prev_turn).await?;
 
             prev_turn = self.game.turn;
+            self.actions.push(p_move.action().clone());
             self.game.make_move(p_move);
         }
     }
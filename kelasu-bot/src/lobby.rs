@@ -5,6 +5,7 @@ use crate::{
 };
 use std::{fmt::Display, sync::Arc};
 
+use kelasu_game::setup::Setup;
 use poise::{
     futures_util::StreamExt,
     serenity_prelude::{self as serenity, User, UserId},
@@ -65,14 +66,16 @@ pub struct Lobby {
     /// the first player is the host.
     pub players: Vec<UserInfo>,
     pub status: LobbyStatus,
+    pub setup: Setup,
 }
 
 impl Lobby {
-    pub fn new(id: LobbyId, host: UserInfo) -> Self {
+    pub fn new(id: LobbyId, host: UserInfo, setup: Setup) -> Self {
         Self {
             id,
             players: vec![host],
             status: LobbyStatus::new(),
+            setup,
         }
     }
 
@@ -195,7 +198,7 @@ impl Lobby {
             pair.swap(0, 1);
         }
 
-        let game = Game::new(self.id.clone(), pair[0], pair[1]);
+        let game = Game::new(self.id.clone(), pair[0], pair[1], self.setup);
         self.status = LobbyStatus::Ongoing;
         Ok(game)
     }
@@ -1,13 +1,23 @@
 use crate::lobby::{Lobby, LobbyId};
 use std::{collections::HashMap, sync::Arc};
 
-use kelasu_game::piece::Team;
-use poise::serenity_prelude::{self as serenity, RwLock};
+use kelasu_game::{
+    analysis::Analyser,
+    piece::Team,
+    puzzle::{Puzzle, PuzzleRun},
+    record::Record,
+    render::{self, Diagram},
+    replay::Replay,
+    setup::Setup,
+};
+use poise::serenity_prelude::{self as serenity, RwLock, UserId};
 use tracing::info;
 use tracing_subscriber;
 
+mod engine;
 mod game;
 mod lobby;
+mod puzzle;
 mod util;
 
 pub type Error = Box<dyn std::error::Error + Send + Sync>;
@@ -18,12 +28,20 @@ pub struct Lobbies {
     // NOTE: this will be slower the more users there will be.
     // not much of a concern if it's not popular, though :P
     lobbies: RwLock<HashMap<LobbyId, Lobby>>,
+    puzzles: Vec<Puzzle>,
+    // the puzzle each user is solving, with its number.
+    solving: RwLock<HashMap<UserId, (usize, PuzzleRun)>>,
+    // the game each player is in, as of the last action.
+    playing: RwLock<HashMap<UserId, kelasu_game::Game>>,
 }
 
 impl Lobbies {
     fn new() -> Self {
         Self {
             lobbies: RwLock::new(HashMap::new()),
+            puzzles: puzzle::load_puzzles(),
+            solving: RwLock::new(HashMap::new()),
+            playing: RwLock::new(HashMap::new()),
         }
     }
 }
@@ -56,6 +74,10 @@ async fn lobbies(ctx: Context<'_>) -> Result<(), Error> {
             response.push_str(s);
         }
 
+        if v.setup != Setup::Standard {
+            response.push_str(&format!("\n- Setup: {}", v.setup));
+        }
+
         // status
         response.push_str(&format!("\n- Status: {}", v.status));
     }
@@ -68,15 +90,28 @@ async fn lobbies(ctx: Context<'_>) -> Result<(), Error> {
 async fn host(
     ctx: Context<'_>,
     #[description = "The name of the new lobby."] name: String,
+    #[description = "The starting setup, e.g. `wings` or `kelasu960 42`. Standard if left out."]
+    setup: Option<String>,
 ) -> Result<(), Error> {
-    info!("{} invoked /host {name}", ctx.author().name);
+    info!("{} invoked /host {name} {setup:?}", ctx.author().name);
+    let setup = match setup.as_deref().map(str::parse::<Setup>) {
+        None => Setup::Standard,
+        Some(Ok(setup)) => setup,
+        Some(Err(e)) => {
+            ctx.say(e.to_string()).await?;
+            return Ok(());
+        }
+    };
     let mut lobbies = ctx.data().lobbies.write().await;
     let response = if lobbies.contains_key(&name) {
         "That lobby already exists.".to_owned()
     } else {
         let id = Arc::new(name);
-        lobbies.insert(id.clone(), Lobby::new(id.clone(), ctx.author().into()));
-        format!("Created lobby: {id}")
+        lobbies.insert(
+            id.clone(),
+            Lobby::new(id.clone(), ctx.author().into(), setup),
+        );
+        format!("Created lobby: {id} ({setup})")
     };
     info!(response);
     ctx.say(response).await?;
@@ -122,13 +157,59 @@ async fn try_join(ctx: Context<'_>, name: &String) -> Result<bool, Error> {
         lobby.start(ctx, teams).await?
     };
 
-    let result = game.start(ctx).await?;
-    let result = match result.0 {
+    let winner = game.start(ctx).await;
+    // the game is over, or was broken off.
+    {
+        let mut playing = ctx.data().playing.write().await;
+        playing.remove(&game.blue);
+        playing.remove(&game.red);
+    }
+    let winner = winner?;
+    let result = match winner.0 {
         Some(Team::Blue) => format!("<@{}> won against <@{}>!", game.blue, game.red),
         Some(Team::Red) => format!("<@{}> won against <@{}>!", game.red, game.blue),
         None => format!("Draw between <@{}> and <@{}>!", game.blue, game.red),
     };
-    ctx.say(format!("Game over!\nResult: {result}")).await?;
+
+    // analysing and drawing every position take a while, so keep them off the async runtime.
+    let record = Record {
+        start: (game.setup != Setup::Standard).then(|| game.setup.game().position()),
+        result: Some(winner),
+        actions: std::mem::take(&mut game.actions),
+        ..Default::default()
+    };
+    let (report, replay) = tokio::task::spawn_blocking(move || {
+        (
+            Analyser::default().analyse(&record),
+            // small frames keep long games under the attachment size limit.
+            Replay {
+                diagram: Diagram {
+                    tile_size: 32,
+                    ..Default::default()
+                },
+                ..Default::default()
+            }
+            .gif(&record),
+        )
+    })
+    .await?;
+    ctx.send(|m| {
+        m.content(format!("Game over!\nResult: {result}"));
+        if let Ok(report) = &report {
+            m.attachment(serenity::AttachmentType::Bytes {
+                data: report.to_string().into_bytes().into(),
+                filename: "analysis.txt".to_owned(),
+            });
+        }
+        if let Ok(replay) = replay {
+            m.attachment(serenity::AttachmentType::Bytes {
+                data: replay.into(),
+                filename: "replay.gif".to_owned(),
+            });
+        }
+        m
+    })
+    .await?;
 
     Ok(true)
 }
@@ -162,6 +243,38 @@ async fn join(
     Ok(())
 }
 
+/// Describes your game or puzzle in words, or what is on one tile.
+#[poise::command(slash_command, prefix_command)]
+async fn describe(
+    ctx: Context<'_>,
+    #[description = "The tile to describe, e.g. `D2` or `32`. The whole position if left out."]
+    tile: Option<String>,
+) -> Result<(), Error> {
+    info!("{} invoked /describe {tile:?}", ctx.author().name);
+    let id = ctx.author().id;
+    let playing = ctx.data().playing.read().await.get(&id).cloned();
+    let game = match playing {
+        Some(game) => game,
+        None => match ctx.data().solving.read().await.get(&id) {
+            Some((_, run)) => run.game.clone(),
+            None => {
+                ctx.say("You are not playing a game or solving a puzzle.")
+                    .await?;
+                return Ok(());
+            }
+        },
+    };
+    let response = match tile {
+        None => render::describe(&game),
+        Some(tile) => match render::parse_tile_name(&tile) {
+            Some(pos) => render::describe_tile(&game, pos),
+            None => "Tiles are named by their row letter and column digit, e.g. `D2`.".to_owned(),
+        },
+    };
+    ctx.say(response).await?;
+    Ok(())
+}
+
 #[poise::command(prefix_command)]
 async fn register(ctx: Context<'_>) -> Result<(), Error> {
     poise::builtins::register_application_commands_buttons(ctx).await?;
@@ -173,7 +286,17 @@ async fn main() {
     tracing_subscriber::fmt::init();
     let framework = poise::Framework::builder()
         .options(poise::FrameworkOptions {
-            commands: vec![host(), join(), lobbies(), register()],
+            commands: vec![
+                host(),
+                join(),
+                lobbies(),
+                describe(),
+                engine::evaluate(),
+                puzzle::puzzle(),
+                puzzle::attempt(),
+                puzzle::hint(),
+                register(),
+            ],
             ..Default::default()
         })
         .token(dotenv::var("BOT_TOKEN").expect("missing BOT_TOKEN"))
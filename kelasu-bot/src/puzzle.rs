@@ -0,0 +1,106 @@
+use crate::{game::board_repr, Context, Error};
+
+use kelasu_game::{
+    board::Move,
+    puzzle::{Puzzle, PuzzleRun, Verdict},
+};
+use rand::Rng;
+use tracing::info;
+
+/// the puzzles from the file in `PUZZLES`, or the built-in ones.
+pub fn load_puzzles() -> Vec<Puzzle> {
+    match dotenv::var("PUZZLES") {
+        Ok(path) => Puzzle::load(&path).expect("invalid PUZZLES file"),
+        Err(_) => Puzzle::builtin(),
+    }
+}
+
+fn describe(run: &PuzzleRun, number: usize) -> String {
+    format!(
+        "**Puzzle {number}: {}**\n{}\n{}",
+        run.puzzle.title,
+        run.puzzle.goal.describe(run.player()),
+        board_repr(&run.game, &[], None)
+    )
+}
+
+/// Starts a puzzle. Solve it with /attempt.
+#[poise::command(slash_command, prefix_command)]
+pub async fn puzzle(
+    ctx: Context<'_>,
+    #[description = "Which puzzle to play. A random one if left out."] number: Option<usize>,
+) -> Result<(), Error> {
+    info!("{} invoked /puzzle {number:?}", ctx.author().name);
+    let puzzles = &ctx.data().puzzles;
+    if puzzles.is_empty() {
+        ctx.say("There are no puzzles.").await?;
+        return Ok(());
+    }
+    let number = number.unwrap_or_else(|| rand::thread_rng().gen_range(1..=puzzles.len()));
+    let Some(puzzle) = number.checked_sub(1).and_then(|i| puzzles.get(i)) else {
+        ctx.say(format!("There are only {} puzzles.", puzzles.len()))
+            .await?;
+        return Ok(());
+    };
+    let run = PuzzleRun::new(puzzle.clone());
+    let response = describe(&run, number);
+    ctx.data()
+        .solving
+        .write()
+        .await
+        .insert(ctx.author().id, (number, run));
+    ctx.say(response).await?;
+    Ok(())
+}
+
+/// Attempts an action in your puzzle, e.g. `move 33 to 44`.
+#[poise::command(slash_command, prefix_command)]
+pub async fn attempt(
+    ctx: Context<'_>,
+    #[description = "The action, e.g. `move 33 to 44` or `merge warrior at 44 with 43`."]
+    #[rest]
+    action: String,
+) -> Result<(), Error> {
+    info!("{} invoked /attempt {action}", ctx.author().name);
+    let action = match action.parse::<Move>() {
+        Ok(action) => action,
+        Err(e) => {
+            ctx.say(format!("Invalid action: {e}")).await?;
+            return Ok(());
+        }
+    };
+    let response = {
+        let mut solving = ctx.data().solving.write().await;
+        let Some((number, run)) = solving.get_mut(&ctx.author().id) else {
+            ctx.say("You are not solving a puzzle. Start one with /puzzle.")
+                .await?;
+            return Ok(());
+        };
+        let verdict = run.attempt(action);
+        let response = match verdict {
+            Verdict::Correct { .. } => format!("{verdict}\n{}", describe(run, *number)),
+            _ => verdict.to_string(),
+        };
+        if run.is_solved() {
+            solving.remove(&ctx.author().id);
+        }
+        response
+    };
+    ctx.say(response).await?;
+    Ok(())
+}
+
+/// Shows the next action of a solution to your puzzle.
+#[poise::command(slash_command, prefix_command)]
+pub async fn hint(ctx: Context<'_>) -> Result<(), Error> {
+    info!("{} invoked /hint", ctx.author().name);
+    let response = match ctx.data().solving.read().await.get(&ctx.author().id) {
+        Some((_, run)) => match run.hint() {
+            Some(action) => format!("Try `{action}`."),
+            None => "There is nothing left to play.".to_owned(),
+        },
+        None => "You are not solving a puzzle. Start one with /puzzle.".to_owned(),
+    };
+    ctx.say(response).await?;
+    Ok(())
+}
@@ -7,7 +7,7 @@ fn main() {
     input("Press Enter to begin the game.");
 
     while game.is_ongoing() {
-        println!("\n{game}");
+        println!("\n{}", game.coloured());
 
         let command = input("Input a move.");
         if command == "help" {
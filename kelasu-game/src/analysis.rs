@@ -0,0 +1,310 @@
+//! Looks back over a finished game to find where it was decided.
+//!
+//! Every position of a record is searched, and every action is compared with the best one
+//! the search found. Actions that throw away a forced win, or too much of the evaluation,
+//! are marked. Evaluations in the report are from Blue's point of view.
+//!
+//! An action is judged by searching the position after it one action less deep,
+//! so that it is scored exactly as the search scored it when looking for the best action.
+
+use crate::{
+    board::{GameState, Move, Winner},
+    eval::Weights,
+    json::Json,
+    piece::Team,
+    record::{IllegalAction, Record},
+    search::{search, Limits, Score},
+    Game,
+};
+use std::{fmt::Display, sync::atomic::AtomicBool};
+
+#[cfg(test)]
+mod tests;
+
+/// how a forced win compares with evaluations, which stay far below it.
+const WIN: i32 = 100_000;
+/// the evaluation that fills one side of the graph.
+const GRAPH_SCALE: i32 = 1000;
+const GRAPH_WIDTH: i32 = 10;
+
+/// how an action went wrong.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Mark {
+    /// the team could force a win, and no longer can.
+    MissedWin,
+    /// the team lost at least [`Analyser::blunder`] of its evaluation.
+    Blunder,
+    /// the team lost at least [`Analyser::mistake`] of its evaluation.
+    Mistake,
+}
+
+impl Mark {
+    pub const ALL: [Self; 3] = [Self::MissedWin, Self::Blunder, Self::Mistake];
+
+    fn name(self) -> &'static str {
+        match self {
+            Mark::MissedWin => "missed win",
+            Mark::Blunder => "blunder",
+            Mark::Mistake => "mistake",
+        }
+    }
+}
+
+impl Display for Mark {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ActionReport {
+    /// counted from 1, for both teams.
+    pub turn: u32,
+    pub team: Team,
+    pub action: Move,
+    /// the evaluation after the action.
+    pub eval: Score,
+    /// what the search would have played instead, if the action was marked.
+    pub better: Option<Move>,
+    pub mark: Option<Mark>,
+}
+
+/// written as text, with a graph of the evaluation. see [`Report::to_json`] for JSON.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Report {
+    pub blue: Option<String>,
+    pub red: Option<String>,
+    pub result: Option<Winner>,
+    /// the evaluation before the first action.
+    pub start: Score,
+    pub actions: Vec<ActionReport>,
+}
+
+/// how deep to search and how much worse an action has to be to get marked.
+#[derive(Debug, Clone)]
+pub struct Analyser {
+    pub weights: Weights,
+    /// in actions, as in [`Limits::depth`].
+    pub depth: u32,
+    /// in hundredths of a Blank, as the evaluation.
+    pub mistake: i32,
+    pub blunder: i32,
+}
+
+impl Default for Analyser {
+    fn default() -> Self {
+        Self {
+            weights: Weights::default(),
+            depth: 3,
+            mistake: 150,
+            blunder: 400,
+        }
+    }
+}
+
+/// the score for the other team.
+fn flip(score: Score) -> Score {
+    match score {
+        Score::Value(value) => Score::Value(-value),
+        Score::Win(actions) => Score::Loss(actions),
+        Score::Loss(actions) => Score::Win(actions),
+    }
+}
+
+/// the score as a number, so that scores can be compared and subtracted.
+fn value(score: Score) -> i32 {
+    match score {
+        Score::Value(value) => value.clamp(-WIN / 2, WIN / 2),
+        Score::Win(actions) => WIN - actions as i32,
+        Score::Loss(actions) => actions as i32 - WIN,
+    }
+}
+
+impl Analyser {
+    /// the score for Blue and the best action of the team to move, or how the game ended.
+    fn evaluate(&self, game: &Game, depth: u32) -> (Score, Option<Move>) {
+        match game.state {
+            GameState::Finished(Winner(Some(Team::Blue))) => (Score::Win(0), None),
+            GameState::Finished(Winner(Some(Team::Red))) => (Score::Loss(0), None),
+            GameState::Finished(Winner(None)) => (Score::Value(0), None),
+            GameState::Ongoing { .. } => {
+                let limits = Limits {
+                    depth: Some(depth),
+                    time: None,
+                };
+                let mut score = Score::Value(0);
+                let best = search(
+                    game,
+                    &self.weights,
+                    &limits,
+                    &AtomicBool::new(false),
+                    |info| score = info.score,
+                );
+                let score = if game.turn == Team::Blue {
+                    score
+                } else {
+                    flip(score)
+                };
+                (score, best)
+            }
+        }
+    }
+
+    /// searches every position of the record, and marks its actions.
+    pub fn analyse(&self, record: &Record) -> Result<Report, IllegalAction> {
+        let mut game = record.start_game();
+        let (start, mut best) = self.evaluate(&game, self.depth);
+        let mut before = start;
+        let mut turn = 1;
+        let mut actions = vec![];
+        for (i, action) in record.actions.iter().enumerate() {
+            let verified = game
+                .verify_action(action.clone())
+                .map_err(|error| IllegalAction {
+                    index: i + 1,
+                    action: action.clone(),
+                    error,
+                })?;
+            let team = game.turn;
+            game.make_move(verified);
+            let (after, next_best) = self.evaluate(&game, self.depth);
+            let (played, _) = self.evaluate(&game, self.depth.saturating_sub(1));
+
+            // resigning and draws are choices the search never makes, so they are left alone.
+            let on_board = matches!(action, Move::Move { .. } | Move::Merge { .. });
+            let [own_before, own_played] = match team {
+                Team::Blue => [before, played],
+                Team::Red => [flip(before), flip(played)],
+            };
+            let lost = value(own_before) - value(own_played);
+            let mark = match own_before {
+                _ if !on_board => None,
+                Score::Win(_) if !matches!(own_played, Score::Win(_)) => Some(Mark::MissedWin),
+                _ if lost >= self.blunder => Some(Mark::Blunder),
+                _ if lost >= self.mistake => Some(Mark::Mistake),
+                _ => None,
+            };
+            let better = mark.and(best.take()).filter(|better| better != action);
+            actions.push(ActionReport {
+                turn,
+                team,
+                action: action.clone(),
+                eval: after,
+                better,
+                mark,
+            });
+
+            if game.turn != team {
+                turn += 1;
+            }
+            before = after;
+            best = next_best;
+        }
+
+        Ok(Report {
+            blue: record.tag("Blue").map(str::to_owned),
+            red: record.tag("Red").map(str::to_owned),
+            result: record.result,
+            start,
+            actions,
+        })
+    }
+}
+
+/// `+1.25`, `win 3` or `loss 3`.
+fn eval_text(score: Score) -> String {
+    match score {
+        Score::Value(value) => format!("{:+.2}", value as f64 / 100.0),
+        score => score.to_string(),
+    }
+}
+
+/// a bar from the middle of the graph, to the right when Blue is ahead.
+fn graph(score: Score) -> String {
+    let width = GRAPH_WIDTH as usize;
+    let bar = (value(score).clamp(-GRAPH_SCALE, GRAPH_SCALE) * GRAPH_WIDTH / GRAPH_SCALE) as isize;
+    let left = "#".repeat((-bar).max(0) as usize);
+    let right = "#".repeat(bar.max(0) as usize);
+    format!("{left:>width$}|{right:<width$}")
+}
+
+impl Report {
+    /// how many actions of the team got the mark.
+    pub fn count(&self, team: Team, mark: Mark) -> usize {
+        self.actions
+            .iter()
+            .filter(|a| a.team == team && a.mark == Some(mark))
+            .count()
+    }
+
+    /// the report as one JSON object.
+    ///
+    /// scores are objects with one of `value`, `win` or `loss`, as in [`Score`].
+    pub fn to_json(&self) -> Json {
+        let actions = self
+            .actions
+            .iter()
+            .map(|a| {
+                Json::object([
+                    ("turn", a.turn.into()),
+                    ("team", a.team.name().into()),
+                    ("action", a.action.to_string().into()),
+                    ("eval", a.eval.into()),
+                    ("better", a.better.as_ref().map(ToString::to_string).into()),
+                    ("mark", a.mark.map(Mark::name).into()),
+                ])
+            })
+            .collect::<Vec<_>>();
+        Json::object([
+            ("blue", self.blue.clone().into()),
+            ("red", self.red.clone().into()),
+            ("result", self.result.map_or("*", Winner::name).into()),
+            ("start", self.start.into()),
+            ("actions", actions.into()),
+        ])
+    }
+}
+
+impl Display for Report {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for (team, name) in [(Team::Blue, &self.blue), (Team::Red, &self.red)] {
+            write!(f, "{:<5}", format!("{team}:"))?;
+            if let Some(name) = name {
+                write!(f, " {name}")?;
+            }
+            let marks = Mark::ALL.map(|mark| match self.count(team, mark) {
+                1 => format!("1 {mark}"),
+                n => format!("{n} {mark}s"),
+            });
+            writeln!(f, " ({})", marks.join(", "))?;
+        }
+        writeln!(f, "Result: {}", self.result.map_or("*", Winner::name))?;
+        writeln!(f)?;
+
+        let width = GRAPH_WIDTH as usize;
+        writeln!(
+            f,
+            "turn team {:<29} {:>8}  {:<width$}|{:>width$}",
+            "action", "eval", "red", "blue"
+        )?;
+        let row = |turn: u32, team: &str, action: String, eval: Score| {
+            format!(
+                "{turn:>4} {team:<4} {action:<29} {:>8}  {}",
+                eval_text(eval),
+                graph(eval)
+            )
+        };
+        writeln!(f, "{}", row(0, "", String::new(), self.start).trim_end())?;
+        for a in &self.actions {
+            let mut line = row(a.turn, &a.team.to_string(), a.action.to_string(), a.eval);
+            if let Some(mark) = a.mark {
+                line += &format!("  {mark}");
+                if let Some(better) = &a.better {
+                    line += &format!(", better: {better}");
+                }
+            }
+            writeln!(f, "{}", line.trim_end())?;
+        }
+        Ok(())
+    }
+}
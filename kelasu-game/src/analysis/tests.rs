@@ -0,0 +1,63 @@
+use super::*;
+
+// moving the warrior onto 45 takes every victory tile. otherwise, the general takes 55.
+const OCCUPATION: &str = "S........./........../........../.....W..../....W...../....WW..../........../........../........../s....g...s blue 1 - ongoing 0";
+
+fn record(actions: &str) -> Record {
+    format!("[Blue \"alpha\"]\n[Start \"{OCCUPATION}\"]\n{actions}")
+        .parse()
+        .unwrap()
+}
+
+#[test]
+pub(crate) fn test_missed_win() {
+    let report = Analyser::default()
+        .analyse(&record("move 35 to 36"))
+        .unwrap();
+    assert_eq!(report.start, Score::Win(1));
+    let action = &report.actions[0];
+    assert_eq!(action.turn, 1);
+    assert_eq!(action.team, Team::Blue);
+    assert_eq!(action.mark, Some(Mark::MissedWin));
+    assert_eq!(action.better, Some("move 35 to 45".parse().unwrap()));
+    assert_eq!(report.count(Team::Blue, Mark::MissedWin), 1);
+    assert!(report
+        .to_string()
+        .contains("missed win, better: move 35 to 45"));
+}
+
+#[test]
+pub(crate) fn test_report() {
+    let mut record = record("move 35 to 45");
+    record.result = Some(Winner(Some(Team::Blue)));
+    let report = Analyser::default().analyse(&record).unwrap();
+    assert_eq!(report.actions[0].mark, None);
+    assert_eq!(report.actions[0].eval, Score::Win(0));
+    assert_eq!(
+        report.to_json().to_string(),
+        "{\"blue\":\"alpha\",\"red\":null,\"result\":\"blue\",\"start\":{\"win\":1},\"actions\":[\
+        {\"turn\":1,\"team\":\"blue\",\"action\":\"move 35 to 45\",\"eval\":{\"win\":0},\"better\":null,\"mark\":null}]}"
+    );
+
+    let record = Record {
+        actions: vec!["move 35 to 55".parse().unwrap()],
+        ..record
+    };
+    assert!(Analyser::default().analyse(&record).is_err());
+}
+
+#[test]
+pub(crate) fn test_stalemate() {
+    // red moves its only piece and has power left, but nothing else to act with.
+    let record = Record {
+        start: Some(
+            "B........S/........../........../........../........../........../........../........../s........./s........b blue 1 - ongoing 0"
+                .parse()
+                .unwrap(),
+        ),
+        actions: vec!["move 00 to 10".parse().unwrap(), "move 99 to 89".parse().unwrap()],
+        ..Default::default()
+    };
+    let report = Analyser::default().analyse(&record).unwrap();
+    assert_eq!(report.actions[1].eval, Score::Value(0));
+}
@@ -0,0 +1,7 @@
+use kelasu_game::protocol::serve;
+use std::io::{stdin, stdout};
+
+/// speaks the Kelasu Engine Protocol over stdin and stdout.
+fn main() {
+    serve(stdin().lock(), stdout());
+}
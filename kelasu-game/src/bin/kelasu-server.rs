@@ -0,0 +1,37 @@
+use kelasu_game::lan::serve;
+use std::{env, net::TcpListener, process::exit};
+
+const USAGE: &str = "\
+    Usage: kelasu-server [--bind <address>] [--port <port>]\n\
+    \n\
+    Hosts lobbies for Kelasu games over the local network.\n\
+    Connect with `kelasu-tui --connect <address>:<port>`.\n\
+      \t--bind  the address to listen on (default 0.0.0.0, every network)\n\
+      \t--port  the port to listen on (default 7878)";
+
+fn main() {
+    let mut bind = "0.0.0.0".to_owned();
+    let mut port = 7878u16;
+
+    let mut args = env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match (arg.as_str(), args.next()) {
+            ("--bind", Some(address)) => bind = address,
+            ("--port", Some(p)) if p.parse::<u16>().is_ok() => port = p.parse().unwrap(),
+            _ => {
+                eprintln!("{USAGE}");
+                exit(1);
+            }
+        }
+    }
+
+    let listener = match TcpListener::bind((bind.as_str(), port)) {
+        Ok(listener) => listener,
+        Err(e) => {
+            eprintln!("Could not listen on {bind}:{port}: {e}");
+            exit(1);
+        }
+    };
+    eprintln!("Listening on {bind}:{port}.");
+    serve(listener);
+}
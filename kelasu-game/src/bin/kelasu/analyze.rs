@@ -0,0 +1,92 @@
+use crate::{Args, Input};
+use kelasu_game::{
+    analysis::Analyser,
+    eval::Weights,
+    piece::Team,
+    search::{search, Limits},
+};
+use std::{sync::atomic::AtomicBool, time::Duration};
+
+pub const USAGE: &str = "\
+    Usage: kelasu analyze <position|records> [--depth <actions>] [--time <ms>] [--weights <file>]\n\
+    \x20                     [--turn <team>] [--game <n>] [--json]\n\
+    \n\
+    Searches a position for its best action, printing every depth as it finishes,\n\
+    or searches every position of a game record and marks its missed wins, blunders and mistakes.\n\
+      \t--depth    the depth of the search (default 4, or 3 for every position of a record)\n\
+      \t--time     how long to search a position, instead of a fixed depth\n\
+      \t--weights  the evaluation weights, from a file (default the built-in ones)\n\
+      \t--turn     the team to move on a board (default blue)\n\
+      \t--game     which game of the records to analyse, counted from 1 (default 1)\n\
+      \t--json     write the report of a record as JSON instead of text";
+
+pub fn run(mut args: Args) {
+    let mut input = None;
+    let mut depth = None;
+    let mut time = None;
+    let mut weights = Weights::default();
+    let mut turn = Team::Blue;
+    let mut game = 1;
+    let mut json = false;
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--depth" => depth = Some(args.parse(&arg)),
+            "--time" => time = Some(Duration::from_millis(args.parse(&arg))),
+            "--weights" => {
+                let path = args.value(&arg);
+                weights =
+                    Weights::load(&path).unwrap_or_else(|e| args.fail(format!("{path}: {e}")));
+            }
+            "--turn" => turn = args.parse(&arg),
+            "--game" => game = args.parse(&arg),
+            "--json" => json = true,
+            _ if input.is_none() => input = Some(Input::read(&args, &arg)),
+            _ => args.fail(format!("Unexpected argument: {arg}")),
+        }
+    }
+    let Some(input) = input else {
+        args.fail("No position or records given.");
+    };
+
+    if let Input::Records(records) = &input {
+        let analyser = Analyser {
+            weights,
+            depth: depth.unwrap_or(Analyser::default().depth),
+            ..Default::default()
+        };
+        match analyser.analyse(Input::record(&args, records, game)) {
+            Ok(report) if json => println!("{}", report.to_json()),
+            Ok(report) => print!("{report}"),
+            Err(e) => args.fail(e),
+        }
+        return;
+    }
+
+    let game = input.game(&args, turn, game, None);
+    if !game.is_ongoing() {
+        args.fail(format!("The game is over. {}", game.state));
+    }
+    println!("{}", weights.evaluate(&game.board, game.turn));
+    let limits = Limits {
+        depth: depth.or(time.is_none().then_some(4)),
+        time,
+    };
+    let best = search(&game, &weights, &limits, &AtomicBool::new(false), |info| {
+        let pv = info
+            .pv
+            .iter()
+            .map(|action| action.to_string())
+            .collect::<Vec<_>>();
+        println!(
+            "Depth {}: score {}, {} nodes in {:.2?}: {}",
+            info.depth,
+            info.score,
+            info.nodes,
+            info.time,
+            pv.join(", ")
+        );
+    });
+    if let Some(action) = best {
+        println!("Best action: {action}");
+    }
+}
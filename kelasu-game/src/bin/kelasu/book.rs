@@ -0,0 +1,100 @@
+use crate::{Args, Input};
+use kelasu_game::{
+    book::{Book, BookBuilder, Granularity},
+    piece::Team,
+    Game,
+};
+
+pub const USAGE: &str = "\
+    Usage: kelasu book build <records>... --output <book> [--turns <n>] [--whole-turns]\n\
+    \x20      kelasu book probe <book> [--position <position>] [--turn <team>]\n\
+    \x20                        [<action>; <action>; ...]\n\
+    \n\
+    Builds opening books from game records, and looks up positions in them.\n\
+      \t--output       where to write the book\n\
+      \t--turns        how many turns of each game go into the book (default 8)\n\
+      \t--whole-turns  store the rest of the turn instead of single actions\n\
+      \t--position     start from a position, a board or the end of a record instead of the start\n\
+      \t--turn         the team to move on a board (default blue)";
+
+pub fn run(mut args: Args) {
+    match args.next().as_deref() {
+        Some("build") => build(args),
+        Some("probe") => probe(args),
+        _ => args.fail("Build or probe?"),
+    }
+}
+
+fn build(mut args: Args) {
+    let mut records = vec![];
+    let mut output = None;
+    let mut turns = 8;
+    let mut granularity = Granularity::Action;
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--output" => output = Some(args.value(&arg)),
+            "--turns" => turns = args.parse(&arg),
+            "--whole-turns" => granularity = Granularity::Turn,
+            _ => records.extend(Input::records(&args, &arg)),
+        }
+    }
+    let Some(output) = output else {
+        args.fail("The book needs an --output file.");
+    };
+
+    let mut builder = BookBuilder::new(granularity, turns);
+    for record in &records {
+        builder.add(record);
+    }
+    let book = builder.build();
+    book.save(&output)
+        .unwrap_or_else(|e| args.fail(format!("{output}: {e}")));
+    println!("{} positions from {} games.", book.len(), records.len());
+}
+
+fn probe(mut args: Args) {
+    let Some(path) = args.next() else {
+        args.fail("Which book?");
+    };
+    let book = Book::load(&path).unwrap_or_else(|e| args.fail(format!("{path}: {e}")));
+    let mut input = None;
+    let mut turn = Team::Blue;
+    let mut actions = vec![];
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--position" => {
+                let position = args.value(&arg);
+                input = Some(Input::read(&args, &position));
+            }
+            "--turn" => turn = args.parse(&arg),
+            _ => actions.push(arg),
+        }
+    }
+    let mut game = input.map_or_else(Game::new, |input| input.game(&args, turn, 1, None));
+    let actions = actions.join(" ");
+    for action in actions.split(';').map(str::trim).filter(|a| !a.is_empty()) {
+        match game.verify_move_str(action) {
+            Ok(action) => game.make_move(action),
+            Err(e) => args.fail(format!("`{action}`: {e}")),
+        }
+    }
+
+    let choices = book.probe(&game);
+    if choices.is_empty() {
+        println!("The position is not in the book.");
+    }
+    let total = choices.iter().map(|c| c.weight as f64).sum::<f64>();
+    for choice in choices {
+        let actions = choice
+            .actions
+            .iter()
+            .map(ToString::to_string)
+            .collect::<Vec<_>>();
+        println!(
+            "{:5.1}% ({}): {}",
+            100.0 * choice.weight as f64 / total,
+            choice.weight,
+            actions.join("; ")
+        );
+    }
+}
@@ -0,0 +1,64 @@
+use crate::{Args, Input};
+use kelasu_game::{piece::Team, record::Record, Game};
+
+pub const USAGE: &str = "\
+    Usage: kelasu convert <board|position|records> --to <board|position|record>\n\
+    \x20                     [--turn <team>] [--game <n>] [--action <n>]\n\
+    \n\
+    Converts between boards, position strings and game records.\n\
+    A record becomes the position after its last action, and a board or a position\n\
+    becomes a record that starts from it.\n\
+      \t--to      what to convert to\n\
+      \t--turn    the team to move on a board (default blue)\n\
+      \t--game    which game of the records to convert, counted from 1 (default 1)\n\
+      \t--action  how many actions of the record to play first (default all of them)";
+
+pub fn run(mut args: Args) {
+    let mut input = None;
+    let mut to = None;
+    let mut turn = Team::Blue;
+    let mut game = 1;
+    let mut actions = None;
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--to" => to = Some(args.value(&arg)),
+            "--turn" => turn = args.parse(&arg),
+            "--game" => game = args.parse(&arg),
+            "--action" => actions = Some(args.parse(&arg)),
+            _ if input.is_none() => input = Some(Input::read(&args, &arg)),
+            _ => args.fail(format!("Unexpected argument: {arg}")),
+        }
+    }
+    let Some(input) = input else {
+        args.fail("Nothing to convert given.");
+    };
+    let Some(to) = to else {
+        args.fail("--to is missing.");
+    };
+
+    match (to.as_str(), &input) {
+        // records stay records, tidied up.
+        ("record", Input::Records(records)) if actions.is_none() => {
+            for record in records {
+                println!("{record}");
+            }
+        }
+        ("record", _) => {
+            let position = input.game(&args, turn, game, actions).position();
+            let record = Record {
+                start: (position != Game::new().position()).then_some(position),
+                ..Default::default()
+            };
+            print!("{record}");
+        }
+        ("position", _) => println!("{}", input.game(&args, turn, game, actions).position()),
+        ("board", _) => {
+            let position = input.game(&args, turn, game, actions).position();
+            // the board is the first field, with its rows separated by `/`.
+            let board = position.to_string();
+            let board = board.split(' ').next().unwrap_or_default();
+            println!("{}", board.replace('/', "\n"));
+        }
+        _ => args.fail(format!("Cannot convert to {to}.")),
+    }
+}
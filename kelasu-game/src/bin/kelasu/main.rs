@@ -0,0 +1,194 @@
+use kelasu_game::{
+    board::{Board, Position},
+    piece::Team,
+    record::Record,
+    Game,
+};
+use std::{
+    env, fs,
+    io::{self, Read},
+    path::Path,
+    process::exit,
+    str::FromStr,
+};
+
+mod analyze;
+mod book;
+mod convert;
+mod perft;
+mod play;
+mod puzzles;
+mod render;
+mod selfplay;
+mod solve;
+mod tournament;
+mod validate;
+
+const USAGE: &str = "\
+    Usage: kelasu <command> [options]\n\
+    \n\
+    Commands:\n\
+      \tplay      plays a game on the command line, against another player or the computer\n\
+      \tanalyze   searches a position for its best action, or marks the mistakes of a game record\n\
+      \trender    draws a position as text, SVG or PNG, or animates a game record as a GIF\n\
+      \tconvert   converts between boards, position strings and game records\n\
+      \tvalidate  checks that game records are legal and their results right\n\
+      \tperft     counts the action sequences and positions reachable from a position\n\
+      \tselfplay  plays the computer against itself from random openings\n\
+      \tmatch     plays engines against each other, with a tally and an optional SPRT\n\
+      \tsolve     proves or refutes a forced win within a number of turns\n\
+      \tpuzzles   finds puzzles in game records\n\
+      \tbook      builds opening books from game records, and looks up positions in them\n\
+    \n\
+    Boards, positions and records are read from a file, from stdin if given as `-`,\n\
+    or from the argument itself. Run `kelasu <command> --help` for the options of a command.";
+
+/// the arguments of a command, and how to complain about them.
+pub struct Args {
+    args: std::vec::IntoIter<String>,
+    usage: &'static str,
+}
+
+impl Iterator for Args {
+    type Item = String;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.args.next()
+    }
+}
+
+impl Args {
+    pub fn fail(&self, message: impl std::fmt::Display) -> ! {
+        eprintln!("{message}\n\n{}", self.usage);
+        exit(1);
+    }
+
+    /// the value after `flag`.
+    pub fn value(&mut self, flag: &str) -> String {
+        self.next()
+            .unwrap_or_else(|| self.fail(format!("{flag} needs a value.")))
+    }
+
+    /// the value after `flag`, parsed.
+    pub fn parse<T: FromStr>(&mut self, flag: &str) -> T
+    where
+        T::Err: std::fmt::Display,
+    {
+        let value = self.value(flag);
+        value
+            .parse()
+            .unwrap_or_else(|e| self.fail(format!("{flag} {value}: {e}")))
+    }
+}
+
+/// a board, a position or game records, whichever the text turned out to be.
+pub enum Input {
+    Board(Board),
+    Position(Position),
+    Records(Vec<Record>),
+}
+
+impl Input {
+    /// reads `arg` as a file, as stdin if it is `-`, or as the text itself.
+    pub fn read(args: &Args, arg: &str) -> Self {
+        let is_file = Path::new(arg).is_file();
+        let text = if arg == "-" {
+            let mut text = String::new();
+            io::stdin()
+                .read_to_string(&mut text)
+                .unwrap_or_else(|e| args.fail(format!("stdin: {e}")));
+            text
+        } else if is_file {
+            fs::read_to_string(arg).unwrap_or_else(|e| args.fail(format!("{arg}: {e}")))
+        } else {
+            arg.to_owned()
+        };
+
+        let position = match text.trim().parse::<Position>() {
+            Ok(position) => return Self::Position(position),
+            Err(e) => e,
+        };
+        let board = match text.parse::<Board>() {
+            Ok(board) => return Self::Board(board),
+            Err(e) => e,
+        };
+        let records = match Record::parse_all(&text) {
+            Ok(records) if !records.is_empty() => return Self::Records(records),
+            Ok(_) => "it has no tags or actions".to_owned(),
+            // the first line, without the syntax of every action.
+            Err(e) => e.to_string().lines().next().unwrap_or_default().to_owned(),
+        };
+        let what = if is_file || arg == "-" {
+            "That is not"
+        } else {
+            "There is no such file, and that is not"
+        };
+        args.fail(format!(
+            "{what} a position ({position}), a board ({board}) or a game record ({records})."
+        ))
+    }
+
+    /// the game it shows. a board has `turn` to move, and a record is played up to
+    /// `actions` actions of its `game`th game, or to its end.
+    pub fn game(&self, args: &Args, turn: Team, game: usize, actions: Option<usize>) -> Game {
+        match self {
+            Self::Board(board) => Game::from_position(turn, board.clone()),
+            Self::Position(position) => position.clone().into(),
+            Self::Records(records) => {
+                let mut record = Self::record(args, records, game).clone();
+                record.actions.truncate(actions.unwrap_or(usize::MAX));
+                record.replay().unwrap_or_else(|e| args.fail(e))
+            }
+        }
+    }
+
+    /// reads `arg` as with [`Input::read`], failing unless it holds game records.
+    pub fn records(args: &Args, arg: &str) -> Vec<Record> {
+        match Self::read(args, arg) {
+            Self::Records(records) => records,
+            _ => args.fail(format!("{arg} is not a game record.")),
+        }
+    }
+
+    /// the `game`th record, counted from 1.
+    pub fn record<'a>(args: &Args, records: &'a [Record], game: usize) -> &'a Record {
+        usize::checked_sub(game, 1)
+            .and_then(|i| records.get(i))
+            .unwrap_or_else(|| args.fail(format!("There are {} games.", records.len())))
+    }
+}
+
+fn main() {
+    let mut args = env::args().skip(1);
+    let command = args.next().unwrap_or_default();
+    let (usage, run): (&'static str, fn(Args)) = match command.as_str() {
+        "play" => (play::USAGE, play::run),
+        "analyze" | "analyse" => (analyze::USAGE, analyze::run),
+        "render" => (render::USAGE, render::run),
+        "convert" => (convert::USAGE, convert::run),
+        "validate" => (validate::USAGE, validate::run),
+        "perft" => (perft::USAGE, perft::run),
+        "selfplay" => (selfplay::USAGE, selfplay::run),
+        "match" => (tournament::USAGE, tournament::run),
+        "solve" => (solve::USAGE, solve::run),
+        "puzzles" => (puzzles::USAGE, puzzles::run),
+        "book" => (book::USAGE, book::run),
+        "" | "help" | "--help" | "-h" => {
+            println!("{USAGE}");
+            return;
+        }
+        _ => {
+            eprintln!("Unknown command: {command}\n\n{USAGE}");
+            exit(1);
+        }
+    };
+    let args = args.collect::<Vec<_>>();
+    if args.iter().any(|arg| arg == "--help" || arg == "-h") {
+        println!("{usage}");
+        return;
+    }
+    run(Args {
+        args: args.into_iter(),
+        usage,
+    });
+}
@@ -0,0 +1,58 @@
+use crate::{Args, Input};
+use kelasu_game::{
+    perft::{divide, perft, Unit},
+    piece::Team,
+    Game,
+};
+use std::time::Instant;
+
+pub const USAGE: &str = "\
+    Usage: kelasu perft <depth> [--turns] [--divide] [--position <position>] [--turn <team>]\n\
+    \n\
+    Counts the action sequences and positions reachable from a position.\n\
+      \t--turns     measure the depth in whole turns instead of single actions\n\
+      \t--divide    break the counts down by the first action\n\
+      \t--position  start from a position, a board or the end of a record instead of the start\n\
+      \t--turn      the team to move on a board (default blue)";
+
+pub fn run(mut args: Args) {
+    let mut depth = None;
+    let mut unit = Unit::Action;
+    let mut show_divide = false;
+    let mut input = None;
+    let mut turn = Team::Blue;
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--turns" => unit = Unit::Turn,
+            "--divide" => show_divide = true,
+            "--position" => {
+                let position = args.value(&arg);
+                input = Some(Input::read(&args, &position));
+            }
+            "--turn" => turn = args.parse(&arg),
+            _ => match arg.parse() {
+                Ok(d) if depth.is_none() => depth = Some(d),
+                _ => args.fail(format!("Unexpected argument: {arg}")),
+            },
+        }
+    }
+    let Some(depth) = depth else {
+        args.fail("No depth given.");
+    };
+    let game = input.map_or_else(Game::new, |input| input.game(&args, turn, 1, None));
+
+    let start = Instant::now();
+    if show_divide {
+        for (action, count) in divide(&game, depth, unit) {
+            println!(
+                "{action}: {} sequences, {} positions",
+                count.sequences, count.positions
+            );
+        }
+        println!();
+    }
+    let count = perft(&game, depth, unit);
+    println!("Sequences: {}", count.sequences);
+    println!("Positions: {}", count.positions);
+    println!("Time: {:.2?}", start.elapsed());
+}
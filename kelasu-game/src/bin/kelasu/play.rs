@@ -0,0 +1,112 @@
+use crate::Args;
+use kelasu_game::{
+    board::{GameState, Move},
+    eval::Weights,
+    piece::Team,
+    record::Record,
+    render::{describe, describe_tile, parse_tile_name},
+    search::{search, Limits},
+    setup::Setup,
+};
+use std::{fs, io, sync::atomic::AtomicBool, time::Duration};
+
+pub const USAGE: &str = "\
+    Usage: kelasu play [--ai <blue|red>] [--time <ms>] [--setup <setup>] [--record <file>]\n\
+    \n\
+    Plays a game on the command line, reading one action per line from stdin.\n\
+    Besides actions, `help` lists the syntax, `describe` tells the position in words\n\
+    and `describe <tile>` tells what is on one tile, e.g. `describe D2`.\n\
+      \t--ai      the team the computer plays (default none, for two players)\n\
+      \t--time    how long the computer thinks about each action (default 2000)\n\
+      \t--setup   the starting setup, e.g. `wings` or `kelasu960 42` (default standard)\n\
+      \t--record  where to save the game as a record when it ends";
+
+pub fn run(mut args: Args) {
+    let mut ai = None;
+    let mut time = 2000;
+    let mut setup = Setup::Standard;
+    let mut record_file = None;
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--ai" => ai = Some(args.parse::<Team>(&arg)),
+            "--time" => time = args.parse(&arg),
+            "--setup" => setup = args.parse(&arg),
+            "--record" => record_file = Some(args.value(&arg)),
+            _ => args.fail(format!("Unexpected argument: {arg}")),
+        }
+    }
+    let limits = Limits {
+        depth: None,
+        time: Some(Duration::from_millis(time)),
+    };
+
+    let mut game = setup.game();
+    let mut record = Record {
+        start: (setup != Setup::Standard).then(|| game.position()),
+        ..Default::default()
+    };
+    let mut lines = io::stdin().lines();
+    while game.is_ongoing() {
+        let action = if ai == Some(game.turn) {
+            let best = search(
+                &game,
+                &Weights::default(),
+                &limits,
+                &AtomicBool::new(false),
+                |_| {},
+            );
+            let action = best.expect("an ongoing game always has a legal action");
+            println!("{} plays `{action}`.", game.turn);
+            action
+        } else {
+            println!("\n{}", game.coloured());
+            println!("{} to act, or `help`:", game.turn);
+            let Some(Ok(line)) = lines.next() else {
+                break;
+            };
+            let line = line.trim();
+            match line.split_once(' ').unwrap_or((line, "")) {
+                ("help", _) => {
+                    println!("{}", Move::SYNTAX);
+                    continue;
+                }
+                ("describe", "") => {
+                    print!("{}", describe(&game));
+                    continue;
+                }
+                ("describe", tile) => {
+                    match parse_tile_name(tile) {
+                        Some(pos) => println!("{}", describe_tile(&game, pos)),
+                        None => println!("Tiles are named like `D2` or `32`."),
+                    }
+                    continue;
+                }
+                _ => match line.parse::<Move>() {
+                    Ok(action) => action,
+                    Err(e) => {
+                        println!("{e}");
+                        continue;
+                    }
+                },
+            }
+        };
+        match game.verify_action(action.clone()) {
+            Ok(verified) => {
+                game.make_move(verified);
+                record.actions.push(action);
+            }
+            Err(e) => println!("That action is illegal: {e}"),
+        }
+    }
+
+    println!("\n{}", game.coloured());
+    if let GameState::Finished(winner) = game.state {
+        record.result = Some(winner);
+    }
+    if let Some(path) = record_file {
+        if let Some(team) = ai {
+            record.set_tag(&team.to_string(), "kelasu");
+        }
+        fs::write(&path, record.to_string()).unwrap_or_else(|e| args.fail(format!("{path}: {e}")));
+    }
+}
@@ -0,0 +1,43 @@
+use crate::{Args, Input};
+use kelasu_game::puzzle::Generator;
+use std::fs;
+
+pub const USAGE: &str = "\
+    Usage: kelasu puzzles <records>... [--output <file>] [--max-win-actions <n>]\n\
+    \x20                     [--max-defence-power <n>]\n\
+    \n\
+    Finds puzzles in game records, and writes them out as a puzzle collection, easiest first.\n\
+      \t--output             where to write the puzzles (default stdout)\n\
+      \t--max-win-actions    the most actions a winning turn may take (default 3)\n\
+      \t--max-defence-power  the most power a defending turn may have (default 2)";
+
+pub fn run(mut args: Args) {
+    let mut records = vec![];
+    let mut output = None;
+    let mut generator = Generator::default();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--output" => output = Some(args.value(&arg)),
+            "--max-win-actions" => generator.max_win_actions = args.parse(&arg),
+            "--max-defence-power" => generator.max_defence_power = args.parse(&arg),
+            _ => records.extend(Input::records(&args, &arg)),
+        }
+    }
+    if records.is_empty() {
+        args.fail("No records given.");
+    }
+
+    let puzzles = generator.generate(&records);
+    let text = puzzles
+        .iter()
+        .map(|puzzle| puzzle.to_string())
+        .collect::<Vec<_>>()
+        .join("\n");
+    match output {
+        Some(file) => {
+            fs::write(&file, text).unwrap_or_else(|e| args.fail(format!("{file}: {e}")));
+            eprintln!("{} puzzles from {} games.", puzzles.len(), records.len());
+        }
+        None => print!("{text}"),
+    }
+}
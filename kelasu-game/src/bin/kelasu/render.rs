@@ -0,0 +1,119 @@
+use crate::{Args, Input};
+use kelasu_game::{
+    piece::Team,
+    render::{colour_enabled, describe, Diagram, Renderer, Style},
+    replay::Replay,
+};
+use std::fs;
+
+pub const USAGE: &str = "\
+    Usage: kelasu render <position|records> [--format <format>] [--output <file>]\n\
+    \x20                    [--turn <team>] [--game <n>] [--action <n>] [--delay <centiseconds>]\n\
+    \n\
+    Draws a position, or a position of a game record with its last action highlighted.\n\
+    As a GIF, a game record is animated instead, one frame per action.\n\
+      \t--format  plain, box, compact, words, svg, png or gif (default from the output file, or plain)\n\
+      \t--output  where to write the drawing (default stdout)\n\
+      \t--turn    the team to move on a board (default blue)\n\
+      \t--game    which game of the records to draw, counted from 1 (default 1)\n\
+      \t--action  how many actions of the record to play first (default all of them)\n\
+      \t--delay   how long each frame of a GIF shows, in hundredths of a second (default 150)";
+
+pub fn run(mut args: Args) {
+    let mut input = None;
+    let mut format = None;
+    let mut output = None;
+    let mut turn = Team::Blue;
+    let mut game = 1;
+    let mut actions = None;
+    let mut replay = Replay::default();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--format" => format = Some(args.value(&arg)),
+            "--output" => output = Some(args.value(&arg)),
+            "--turn" => turn = args.parse(&arg),
+            "--game" => game = args.parse(&arg),
+            "--action" => actions = Some(args.parse(&arg)),
+            "--delay" => replay.delay = args.parse(&arg),
+            _ if input.is_none() => input = Some(Input::read(&args, &arg)),
+            _ => args.fail(format!("Unexpected argument: {arg}")),
+        }
+    }
+    let Some(input) = input else {
+        args.fail("No position or records given.");
+    };
+    let format = format.unwrap_or_else(|| match output.as_deref() {
+        Some(file) if file.ends_with(".svg") => "svg".to_owned(),
+        Some(file) if file.ends_with(".png") => "png".to_owned(),
+        Some(file) if file.ends_with(".gif") => "gif".to_owned(),
+        _ => "plain".to_owned(),
+    });
+
+    // a GIF animates the whole record instead of drawing one position of it.
+    #[cfg(feature = "gif")]
+    if format == "gif" {
+        let Input::Records(records) = &input else {
+            args.fail("Only game records can be animated.");
+        };
+        let Some(file) = &output else {
+            args.fail("GIF images need an --output file.");
+        };
+        let gif = replay
+            .gif(Input::record(&args, records, game))
+            .unwrap_or_else(|e| args.fail(e));
+        fs::write(file, gif).unwrap_or_else(|e| args.fail(format!("{file}: {e}")));
+        return;
+    }
+
+    // records are drawn as their replay shows them, with the last action narrated.
+    let (diagram, game) = match &input {
+        Input::Records(records) => {
+            let mut frames = replay
+                .frames(Input::record(&args, records, game))
+                .unwrap_or_else(|e| args.fail(e));
+            frames.swap_remove(actions.unwrap_or(usize::MAX).min(frames.len() - 1))
+        }
+        _ => {
+            let game = input.game(&args, turn, game, None);
+            let diagram = Diagram {
+                energy: game.is_ongoing().then_some((game.turn, game.power)),
+                ..Default::default()
+            };
+            (diagram, game)
+        }
+    };
+
+    let style = match format.as_str() {
+        "plain" => Some(Style::Plain),
+        "box" => Some(Style::Box),
+        "compact" => Some(Style::Compact),
+        _ => None,
+    };
+    let image = match (format.as_str(), style) {
+        (_, Some(style)) => {
+            let renderer = Renderer {
+                style,
+                last_move: diagram.last_move,
+                energy: true,
+                colour: output.is_none() && colour_enabled(),
+                ..Default::default()
+            };
+            renderer.render(&game).into_bytes()
+        }
+        ("words", _) => describe(&game).into_bytes(),
+        ("svg", _) => diagram.svg(&game.board).into_bytes(),
+        #[cfg(feature = "png")]
+        ("png", _) if output.is_some() => diagram.png(&game.board),
+        #[cfg(feature = "png")]
+        ("png", _) => args.fail("PNG images need an --output file."),
+        #[cfg(not(feature = "png"))]
+        ("png", _) => args.fail("Built without PNG support."),
+        #[cfg(not(feature = "gif"))]
+        ("gif", _) => args.fail("Built without GIF support."),
+        _ => args.fail(format!("Unknown format: {format}")),
+    };
+    match output {
+        Some(file) => fs::write(&file, image).unwrap_or_else(|e| args.fail(format!("{file}: {e}"))),
+        None => print!("{}", String::from_utf8_lossy(&image)),
+    }
+}
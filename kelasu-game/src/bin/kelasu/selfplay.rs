@@ -0,0 +1,112 @@
+use crate::Args;
+use kelasu_game::{
+    board::{GameState, Winner},
+    eval::Weights,
+    piece::Team,
+    search::{search, Limits},
+    tournament::random_openings,
+};
+use std::{fs::File, io::Write, sync::atomic::AtomicBool, time::Duration};
+
+pub const USAGE: &str = "\
+    Usage: kelasu selfplay [--games <n>] [--depth <actions>] [--time <ms>] [--weights <file>]\n\
+    \x20                      [--opening-actions <n>] [--seed <n>] [--max-actions <n>] [--records <file>]\n\
+    \n\
+    Plays the built-in search against itself, once from each of a number of random openings.\n\
+      \t--games            how many games to play (default 10)\n\
+      \t--depth            the depth of every search (default 2)\n\
+      \t--time             how long every search may take, instead of a fixed depth\n\
+      \t--weights          the evaluation weights, from a file (default the built-in ones)\n\
+      \t--opening-actions  how many random actions each opening has (default 4)\n\
+      \t--seed             the seed of the random openings (default 0)\n\
+      \t--max-actions      draw games that last longer than this (default 500)\n\
+      \t--records          write every game to this file";
+
+pub fn run(mut args: Args) {
+    let mut games = 10;
+    let mut depth = None;
+    let mut time = None;
+    let mut weights = Weights::default();
+    let mut opening_actions = 4;
+    let mut seed = 0;
+    let mut max_actions = 500;
+    let mut records_file = None;
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--games" => games = args.parse(&arg),
+            "--depth" => depth = Some(args.parse(&arg)),
+            "--time" => time = Some(Duration::from_millis(args.parse(&arg))),
+            "--weights" => {
+                let path = args.value(&arg);
+                weights =
+                    Weights::load(&path).unwrap_or_else(|e| args.fail(format!("{path}: {e}")));
+            }
+            "--opening-actions" => opening_actions = args.parse(&arg),
+            "--seed" => seed = args.parse(&arg),
+            "--max-actions" => max_actions = args.parse(&arg),
+            "--records" => records_file = Some(args.value(&arg)),
+            _ => args.fail(format!("Unexpected argument: {arg}")),
+        }
+    }
+    let limits = Limits {
+        depth: depth.or(time.is_none().then_some(2)),
+        time,
+    };
+    let mut records_file = records_file
+        .map(|path| File::create(&path).unwrap_or_else(|e| args.fail(format!("{path}: {e}"))));
+
+    let mut wins = [0; 2];
+    let mut draws = 0;
+    for (i, mut record) in random_openings(games, opening_actions, seed)
+        .unwrap_or_else(|e| args.fail(e))
+        .into_iter()
+        .enumerate()
+    {
+        record.set_tag("Blue", "kelasu");
+        record.set_tag("Red", "kelasu");
+        let mut game = record.replay().unwrap_or_else(|e| args.fail(e));
+        while game.is_ongoing() {
+            if record.actions.len() >= max_actions {
+                record.result = Some(Winner(None));
+                record.set_tag("Termination", "action limit");
+                break;
+            }
+            let best = search(&game, &weights, &limits, &AtomicBool::new(false), |_| {});
+            let action = best.expect("an ongoing game always has a legal action");
+            let verified = game
+                .verify_action(action.clone())
+                .unwrap_or_else(|e| args.fail(format!("The search chose `{action}`: {e}")));
+            game.make_move(verified);
+            record.actions.push(action);
+        }
+        if let GameState::Finished(winner) = game.state {
+            record.result = Some(winner);
+        }
+
+        let outcome = match record.result {
+            Some(Winner(Some(team))) => {
+                wins[team as usize] += 1;
+                format!("{team} wins")
+            }
+            _ => {
+                draws += 1;
+                "draw".to_owned()
+            }
+        };
+        println!(
+            "Game {}: {outcome} after {} actions.",
+            i + 1,
+            record.actions.len()
+        );
+        if let Some(file) = &mut records_file {
+            if let Err(e) = writeln!(file, "{record}") {
+                eprintln!("Could not write the record: {e}");
+            }
+        }
+    }
+    println!(
+        "\nBlue wins: {}, Red wins: {}, draws: {draws}.",
+        wins[Team::Blue as usize],
+        wins[Team::Red as usize]
+    );
+}
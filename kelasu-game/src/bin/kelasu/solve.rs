@@ -0,0 +1,52 @@
+use crate::{Args, Input};
+use kelasu_game::{
+    piece::Team,
+    solver::{solve, Solution},
+    Game,
+};
+use std::{sync::atomic::AtomicBool, time::Instant};
+
+pub const USAGE: &str = "\
+    Usage: kelasu solve <turns> [--position <position>] [--turn <team>]\n\
+    \n\
+    Proves or refutes that the team to move wins within a number of its own turns.\n\
+      \t--position  start from a position, a board or the end of a record instead of the start\n\
+      \t--turn      the team to move on a board (default blue)";
+
+pub fn run(mut args: Args) {
+    let mut turns = None;
+    let mut input = None;
+    let mut turn = Team::Blue;
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--position" => {
+                let position = args.value(&arg);
+                input = Some(Input::read(&args, &position));
+            }
+            "--turn" => turn = args.parse(&arg),
+            _ => match arg.parse() {
+                Ok(n) if turns.is_none() => turns = Some(n),
+                _ => args.fail(format!("Unexpected argument: {arg}")),
+            },
+        }
+    }
+    let Some(turns) = turns else {
+        args.fail("No number of turns given.");
+    };
+    let game = input.map_or_else(Game::new, |input| input.game(&args, turn, 1, None));
+
+    let start = Instant::now();
+    match solve(&game, turns, &AtomicBool::new(false)) {
+        Some(Solution::Win { turns, line }) => {
+            println!("{} wins within {turns} turns:", game.turn);
+            for action in line {
+                println!("{action}");
+            }
+        }
+        Some(Solution::NoWin) => {
+            println!("{} cannot force a win within {turns} turns.", game.turn)
+        }
+        None => println!("The solver was stopped."),
+    }
+    println!("Time: {:.2?}", start.elapsed());
+}
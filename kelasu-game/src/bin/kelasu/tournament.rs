@@ -0,0 +1,204 @@
+use crate::{Args, Input};
+use kelasu_game::{
+    board::Winner,
+    protocol::Engine,
+    search::Limits,
+    tournament::{
+        random_openings, run as play, tally, Player, Settings, Sprt, SprtResult, TimeControl,
+    },
+};
+use std::{fs::File, io::Write, ops::ControlFlow, sync::Arc, thread, time::Duration};
+
+pub const USAGE: &str = "\
+    Usage: kelasu match --engine <name>=<command> --engine <name>=<command> [options]\n\
+    \n\
+    Plays every pair of engines against each other, once with each team on every opening.\n\
+      \t--engine <name>=<command>         an engine that speaks the Kelasu Engine Protocol\n\
+      \t--option <name>.<option>=<value>  sets an option of an engine before every game\n\
+      \t--movetime <ms>                   the time limit of every search; there is no game clock\n\
+      \t--depth <actions>                 the depth limit of every search\n\
+      \t--margin <ms>                     extra time before a search loses on time (default 100)\n\
+      \t--rounds <n>                      how many random openings to play (default 10)\n\
+      \t--opening-actions <n>             how many random actions each opening has (default 4)\n\
+      \t--openings <records>              play these records as openings instead\n\
+      \t--seed <n>                        the seed of the random openings (default 0)\n\
+      \t--max-actions <n>                 draw games that last longer than this (default 2000)\n\
+      \t--concurrency <n>                 how many games to play at once (default: every CPU)\n\
+      \t--records <file>                  write every game to this file\n\
+      \t--sprt <elo0>,<elo1>              stop once the first engine is known to be elo0 or elo1 stronger";
+
+/// an engine given on the command line.
+struct EngineArg {
+    name: String,
+    command: Vec<String>,
+    options: Vec<(String, String)>,
+}
+
+pub fn run(mut args: Args) {
+    let mut engines: Vec<EngineArg> = vec![];
+    let mut limits = Limits::default();
+    let mut margin = Duration::from_millis(100);
+    let mut rounds = 10;
+    let mut opening_actions = 4;
+    let mut openings_file = None;
+    let mut seed = 0;
+    let mut max_actions = 2000;
+    let mut concurrency = thread::available_parallelism().map_or(1, |n| n.get());
+    let mut records_file = None;
+    let mut sprt = None;
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--engine" => {
+                let value = args.value(&arg);
+                let Some((name, command)) = value.split_once('=') else {
+                    args.fail("Engines are given as <name>=<command>.");
+                };
+                let command = command
+                    .split_whitespace()
+                    .map(str::to_owned)
+                    .collect::<Vec<_>>();
+                if command.is_empty() {
+                    args.fail(format!("The engine {name} has no command."));
+                }
+                engines.push(EngineArg {
+                    name: name.to_owned(),
+                    command,
+                    options: vec![],
+                });
+            }
+            "--option" => {
+                let value = args.value(&arg);
+                let parsed = value
+                    .split_once('.')
+                    .and_then(|(name, rest)| Some((name, rest.split_once('=')?)));
+                let Some((name, (option, value))) = parsed else {
+                    args.fail("Options are given as <name>.<option>=<value>.");
+                };
+                let Some(engine) = engines.iter_mut().find(|e| e.name == name) else {
+                    args.fail(format!("There is no engine named {name} yet."));
+                };
+                engine.options.push((option.to_owned(), value.to_owned()));
+            }
+            "--movetime" => limits.time = Some(Duration::from_millis(args.parse(&arg))),
+            "--depth" => limits.depth = Some(args.parse(&arg)),
+            "--margin" => margin = Duration::from_millis(args.parse(&arg)),
+            "--rounds" => rounds = args.parse(&arg),
+            "--opening-actions" => opening_actions = args.parse(&arg),
+            "--openings" => openings_file = Some(args.value(&arg)),
+            "--seed" => seed = args.parse(&arg),
+            "--max-actions" => max_actions = args.parse(&arg),
+            "--concurrency" => concurrency = args.parse(&arg),
+            "--records" => records_file = Some(args.value(&arg)),
+            "--sprt" => {
+                let value = args.value(&arg);
+                let elos = value
+                    .split_once(',')
+                    .and_then(|(elo0, elo1)| Some((elo0.parse().ok()?, elo1.parse().ok()?)));
+                let Some((elo0, elo1)) = elos else {
+                    args.fail("--sprt needs two Elo differences, e.g. 0,10.");
+                };
+                sprt = Some(Sprt::new(elo0, elo1));
+            }
+            _ => args.fail(format!("Unexpected argument: {arg}")),
+        }
+    }
+    if engines.len() < 2 {
+        args.fail("At least two engines are needed.");
+    }
+
+    let openings = match openings_file {
+        Some(path) => Input::records(&args, &path),
+        None => random_openings(rounds, opening_actions, seed).unwrap_or_else(|e| args.fail(e)),
+    };
+    let players = engines
+        .into_iter()
+        .map(|engine| {
+            let EngineArg {
+                name,
+                command,
+                options,
+            } = engine;
+            Player {
+                name,
+                start: Arc::new(move || {
+                    let mut engine = Engine::spawn(&command[0], &command[1..])?;
+                    for (option, value) in &options {
+                        engine.set_option(option, value)?;
+                    }
+                    engine.new_game()?;
+                    Ok(engine)
+                }),
+            }
+        })
+        .collect::<Vec<_>>();
+    let settings = Settings {
+        time_control: TimeControl { limits, margin },
+        concurrency,
+        openings,
+        max_actions,
+    };
+
+    let mut records = records_file
+        .map(|path| File::create(&path).unwrap_or_else(|e| args.fail(format!("{path}: {e}"))));
+    let mut games = vec![];
+    let results = play(&players, &settings, |result| {
+        let record = &result.record;
+        let outcome = match record.result {
+            Some(Winner(Some(team))) => format!("{team} wins"),
+            result => result.map_or("unfinished", Winner::name).to_owned(),
+        };
+        let reason = record
+            .tag("Termination")
+            .map(|t| format!(" ({t})"))
+            .unwrap_or_default();
+        println!(
+            "Game {}: {} (Blue) vs {} (Red): {outcome}{reason} after {} actions.",
+            games.len() + 1,
+            players[result.blue].name,
+            players[result.red].name,
+            record.actions.len()
+        );
+        if let Some(file) = &mut records {
+            if let Err(e) = writeln!(file, "{record}") {
+                eprintln!("Could not write the record: {e}");
+            }
+        }
+        games.push(result.clone());
+
+        match sprt {
+            Some(sprt) => {
+                let tally = tally(players.len(), &games)[0][1];
+                match sprt.test(&tally) {
+                    SprtResult::Continue => ControlFlow::Continue(()),
+                    _ => ControlFlow::Break(()),
+                }
+            }
+            None => ControlFlow::Continue(()),
+        }
+    });
+
+    let tallies = tally(players.len(), &results);
+    println!("\nResults:");
+    for (i, player) in players.iter().enumerate() {
+        for (j, opponent) in players.iter().enumerate() {
+            if i < j {
+                println!("{} vs {}: {}", player.name, opponent.name, tallies[i][j]);
+            }
+        }
+    }
+    if let Some(sprt) = sprt {
+        let tally = tallies[0][1];
+        let (lower, upper) = sprt.bounds();
+        let verdict = match sprt.test(&tally) {
+            SprtResult::AcceptH1 => format!("H1 accepted: {} is stronger", players[0].name),
+            SprtResult::AcceptH0 => format!("H0 accepted: {} is not stronger", players[0].name),
+            SprtResult::Continue => "no conclusion yet".to_owned(),
+        };
+        println!(
+            "SPRT [{}, {}]: LLR {:.2} ({lower:.2}, {upper:.2}), {verdict}.",
+            sprt.elo0,
+            sprt.elo1,
+            tally.llr(sprt.elo0, sprt.elo1)
+        );
+    }
+}
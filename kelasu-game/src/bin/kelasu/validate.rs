@@ -0,0 +1,70 @@
+use crate::{Args, Input};
+use kelasu_game::board::{GameState, Winner};
+use std::process::exit;
+
+pub const USAGE: &str = "\
+    Usage: kelasu validate <records>\n\
+    \n\
+    Plays every game of the records, checking that every action is legal and that\n\
+    the result agrees with how the game ended. Exits with status 1 if any game is invalid.";
+
+fn outcome(winner: Winner) -> String {
+    match winner.0 {
+        Some(team) => format!("{team} wins"),
+        None => "drawn".to_owned(),
+    }
+}
+
+pub fn run(mut args: Args) {
+    let Some(arg) = args.next() else {
+        args.fail("No records given.");
+    };
+    if let Some(arg) = args.next() {
+        args.fail(format!("Unexpected argument: {arg}"));
+    }
+    let Input::Records(records) = Input::read(&args, &arg) else {
+        args.fail("That is not a game record.");
+    };
+
+    let mut invalid = 0;
+    for (i, record) in records.iter().enumerate() {
+        let game = match record.replay() {
+            Ok(game) => game,
+            Err(e) => {
+                println!("Game {}: {e}", i + 1);
+                invalid += 1;
+                continue;
+            }
+        };
+        let ended = match game.state {
+            GameState::Finished(winner) => Some(winner),
+            GameState::Ongoing { .. } => None,
+        };
+        // games can also end outside the rules, e.g. on time.
+        match (record.result, ended) {
+            (Some(result), Some(ended)) if result != ended => {
+                println!(
+                    "Game {}: the result says {}, but the game ended {}.",
+                    i + 1,
+                    outcome(result),
+                    outcome(ended)
+                );
+                invalid += 1;
+            }
+            (result, ended) => println!(
+                "Game {}: {} actions, {}.",
+                i + 1,
+                record.actions.len(),
+                result.or(ended).map_or("unfinished".to_owned(), outcome)
+            ),
+        }
+    }
+    println!(
+        "{} of {} games are valid.",
+        records.len() - invalid,
+        records.len()
+    );
+    if invalid > 0 {
+        exit(1);
+    }
+}
@@ -1,5 +1,8 @@
-use super::piece::{Icon, InvalidPieceMove, MoveKind, Piece, PieceKind, Team, Tile};
-use crate::util::{verify_polyomino, NonPolyomino};
+use super::piece::{InvalidPieceMove, MoveKind, Piece, PieceKind, Team, Tile};
+use crate::{
+    render::Renderer,
+    util::{verify_polyomino, NonPolyomino},
+};
 use std::{
     collections::HashMap,
     fmt::Display,
@@ -10,9 +13,18 @@ use std::{
 
 use thiserror::Error;
 
+mod editor;
+mod hash;
+mod movegen;
+mod position;
+mod symmetry;
 #[cfg(test)]
 mod tests;
 
+pub use editor::{Editor, Problem, PIECE_SET};
+pub use position::{InvalidPosition, Position};
+pub use symmetry::Symmetry;
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub struct Pos(pub i8);
 
@@ -91,7 +103,7 @@ impl Board {
         self.tiles
             .iter()
             .filter(|t| {
-                t.0.map_or(false, |p| p.team == team && p.kind != PieceKind::Stone)
+                t.0.is_some_and(|p| p.team == team && p.kind != PieceKind::Stone)
             })
             .count() as u8
     }
@@ -103,9 +115,52 @@ impl Board {
         };
         self.tiles
             .iter()
-            .filter(|t| t.0.map_or(false, |p| p == stone))
+            .filter(|t| t.0.is_some_and(|p| p == stone))
             .count() as u8
     }
+
+    /// checks the movement rules of the piece on `from`, whichever team it is on.
+    ///
+    /// locked tiles and whose turn it is are left to [`Game::verify_move`].
+    pub fn verify_piece_move(&self, from: Pos, to: Pos) -> Result<(), InvalidPieceMove> {
+        let piece = self[from].0.ok_or(InvalidPieceMove::NoPiece)?;
+        let ([dx, dy], dist) = from.dir_to(to).ok_or(InvalidPieceMove::NonCompassMove)?;
+
+        let ray_index = Piece::ray_index(dx, dy).unwrap();
+        let moves = piece.moves();
+        let (move_kind, range) = moves[ray_index];
+        if range < dist {
+            return Err(InvalidPieceMove::TooFar);
+        }
+
+        if move_kind != MoveKind::Recall {
+            let mut temp = from;
+            for _ in 1..dist {
+                temp = temp
+                    .shift(dx, dy)
+                    .expect("from and to are guaranteed to be within bounds.");
+                if self[temp].0.is_some() {
+                    return Err(InvalidPieceMove::Blocked);
+                }
+            }
+        }
+
+        if self[to].0.is_some_and(|t| t.team == piece.team) {
+            return Err(InvalidPieceMove::FriendlyFire);
+        }
+
+        match move_kind {
+            MoveKind::MoveOnly if self[to].0.is_some() => Err(InvalidPieceMove::Blocked),
+            MoveKind::CaptureOnly | MoveKind::Convert if self[to].0.is_none() => {
+                Err(InvalidPieceMove::MustCapture)
+            }
+            MoveKind::MoveMoveCapture if dist == 1 && self[to].0.is_some() => {
+                Err(InvalidPieceMove::NoMelee)
+            }
+            MoveKind::Recall if dist < range => Err(InvalidPieceMove::CannotRecallHere),
+            _ => Ok(()),
+        }
+    }
 }
 
 impl Default for Board {
@@ -130,10 +185,81 @@ impl IndexMut<Pos> for Board {
     }
 }
 
+impl Board {
+    /// reads a board drawn in the plain or box [`Style`](crate::render::Style), as in the
+    /// README and the bot, ignoring the frame, the labels and the fences.
+    /// `None` if `s` is not such a drawing.
+    fn from_diagram(s: &str) -> Option<Result<Self, &'static str>> {
+        let mut rows = [None; 10];
+        let mut found = false;
+        for line in s.lines() {
+            let chars = line.chars().collect::<Vec<_>>();
+            // the row, and where its first tile is.
+            let row = match chars.iter().position(|&c| c == '║') {
+                // ` A ║ B B …`, or `"D=║-.-.…` when its digit is held.
+                Some(frame) => chars[..frame]
+                    .iter()
+                    .rev()
+                    .find(|c| !c.is_whitespace() && **c != '=')
+                    .filter(|c| ('A'..='J').contains(c))
+                    .map(|&c| (c as usize - 'A' as usize, frame + 2)),
+                // `0 |B|B|…`, or `3=-.-.…` when its digit is held, but not the header.
+                None => {
+                    let indent = chars.iter().take_while(|c| c.is_whitespace()).count();
+                    match chars.get(indent..indent + 3) {
+                        Some(&[y, ' ' | '=', fence])
+                            if y.is_ascii_digit() && "|-()[]<>{}".contains(fence) =>
+                        {
+                            Some((y as usize - '0' as usize, indent + 3))
+                        }
+                        _ => None,
+                    }
+                }
+            };
+            let Some((y, start)) = row else {
+                continue;
+            };
+            found = true;
+
+            let mut tiles = [Tile::default(); 10];
+            for (x, tile) in tiles.iter_mut().enumerate() {
+                *tile = match chars.get(start + 2 * x) {
+                    Some(' ') => Tile::default(),
+                    Some(&c) => match c.try_into() {
+                        Ok(tile) => tile,
+                        Err(_) => return Some(Err("Invalid tile in string.")),
+                    },
+                    None => return Some(Err("A row of the diagram is cut short.")),
+                };
+            }
+            if rows[y].replace(tiles).is_some() {
+                return Some(Err("A row of the diagram appears twice."));
+            }
+        }
+        if !found {
+            return None;
+        }
+
+        let mut board = Self::default();
+        for (y, row) in rows.into_iter().enumerate() {
+            let Some(row) = row else {
+                return Some(Err("A row of the diagram is missing."));
+            };
+            board.tiles[y * 10..y * 10 + 10].copy_from_slice(&row);
+        }
+        Some(Ok(board))
+    }
+}
+
+/// accepts the 100 tiles in rows, with `.`, `:` or `_` for empty tiles, or a drawing of the board
+/// as [`Display`] and the box [`Style`](crate::render::Style) make them.
 impl FromStr for Board {
     type Err = &'static str;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if let Some(board) = Self::from_diagram(s) {
+            return board;
+        }
         let Some(tiles) = s
             .chars()
             .filter(|c| !c.is_ascii_whitespace())
@@ -154,21 +280,10 @@ impl FromStr for Board {
     }
 }
 
+/// see [`Renderer`](crate::render::Renderer) for other styles.
 impl Display for Board {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        writeln!(f, "   0 1 2 3 4 5 6 7 8 9")?;
-        // writeln!(f, "   {}", "-".repeat(21))?;
-        for (y, row) in self.tiles.chunks(10).enumerate() {
-            write!(f, "{y} ")?;
-            for (x, tile) in row.iter().enumerate() {
-                let is_victory = [4, 5].contains(&x) && [4, 5].contains(&y) && tile.0.is_none();
-                write!(f, "|{}", if is_victory { ':' } else { tile.icon() })?;
-            }
-            writeln!(f, "|")?;
-        }
-        // writeln!(f, "   {}", "-".repeat(21))?;
-
-        Ok(())
+        f.write_str(&Renderer::default().render_board(self))
     }
 }
 
@@ -194,21 +309,51 @@ impl Move {
           \tmove <yx> to <yx>\n\
           \tmerge <piece> at <yx> with <yx> <yx> ...\n\
           \tresign\n\
-          \tdraw";
+          \tdraw\n\
+          \tdecline";
+}
+
+/// written the same way that [`Move::from_str`] reads it.
+impl Display for Move {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Move::Resign => write!(f, "resign"),
+            Move::Draw => write!(f, "draw"),
+            Move::DeclineDraw => write!(f, "decline"),
+            Move::Move { from, to } => write!(f, "move {:02} to {:02}", from.0, to.0),
+            Move::Merge { kind, pieces } => {
+                let kind = format!("{kind:?}").to_ascii_lowercase();
+                let Some((dest, rest)) = pieces.split_last() else {
+                    return write!(f, "merge {kind}");
+                };
+                write!(f, "merge {kind} at {:02} with", dest.0)?;
+                for p in rest {
+                    write!(f, " {:02}", p.0)?;
+                }
+                Ok(())
+            }
+        }
+    }
 }
 
 /// just a way to encode trustedness in the type system
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct VerifiedMove(Move);
 
+impl VerifiedMove {
+    pub fn action(&self) -> &Move {
+        &self.0
+    }
+}
+
 #[derive(Error, Debug)]
 pub enum InvalidMove {
     #[error("You cannot move after the game is over.")]
     GameOver,
     #[error("Did you just try to decline without first being offered a draw?")]
     DrawNotOffered,
-    #[error("You must accept or decline the draw.")]
-    DrawOffered,
+    #[error("You have already offered a draw.")]
+    DrawAlreadyOffered,
     #[error("You cannot move an empty tile.")]
     EmptyTile,
     #[error("You cannot move your opponent's pieces.")]
@@ -327,10 +472,17 @@ impl FromStr for Move {
 #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub struct Winner(pub Option<Team>);
 
+impl Winner {
+    /// `blue`, `red` or `draw`, as results are written in records and messages.
+    pub fn name(self) -> &'static str {
+        self.0.map_or("draw", Team::name)
+    }
+}
+
 impl Display for Winner {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self.0 {
-            Some(team) => write!(f, "Winner: {team:?}."),
+            Some(team) => write!(f, "Winner: {team}."),
             None => write!(f, "Draw."),
         }
     }
@@ -338,25 +490,30 @@ impl Display for Winner {
 
 #[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub enum GameState {
-    Ongoing { draw_offered: bool },
+    /// `draw_offer` is the team with a pending draw offer, if any.
+    ///
+    /// An offer stays open for the rest of the offering team's turn and all of the opponent's
+    /// next turn, so the opponent may keep playing before answering, as in chess.
+    /// It lapses once the turn passes back to the team that offered it.
+    Ongoing {
+        draw_offer: Option<Team>,
+    },
     Finished(Winner),
 }
 
 impl Display for GameState {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match *self {
-            GameState::Ongoing { draw_offered: true } => {
-                write!(f, "The opponent is offering a draw.")
-            }
             GameState::Ongoing {
-                draw_offered: false,
-            } => write!(f, "Ongoing match."),
+                draw_offer: Some(team),
+            } => write!(f, "{team} is offering a draw."),
+            GameState::Ongoing { draw_offer: None } => write!(f, "Ongoing match."),
             GameState::Finished(winner) => write!(f, "{winner}"),
         }
     }
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct Game {
     pub state: GameState,
     pub turn: Team,
@@ -364,32 +521,66 @@ pub struct Game {
     pub board: Board,
     pub locked_tiles: Vec<Pos>,
     position_tracker: HashMap<(Team, Board), usize>,
+    /// the number of turns that ended without progress since the last progress was made.
     stagnation: u8,
+    /// whether the current turn has made progress yet.
+    progressed: bool,
 }
 
 impl Game {
+    /// how many turns without progress draw the game, counting each team's turn separately.
+    /// that makes 64 full turns, as the rules put it.
+    pub const STAGNATION_LIMIT: u8 = 128;
+
     pub fn new() -> Self {
         Self::from_position(Team::default(), Board::new())
     }
 
     pub fn from_position(turn: Team, board: Board) -> Self {
-        Self {
-            state: GameState::Ongoing {
-                draw_offered: false,
-            },
+        let mut game = Self {
+            state: GameState::Ongoing { draw_offer: None },
             turn,
             power: board.stone_count(turn),
             board,
             locked_tiles: Vec::with_capacity(8),
             position_tracker: HashMap::new(),
             stagnation: 0,
-        }
+            progressed: false,
+        };
+        game.check_stalemate();
+        game
+    }
+
+    /// the number of turns, counting each team's turn separately, that have ended
+    /// since the last Blank move, Merge, Capture or Conversion.
+    pub fn stagnation(&self) -> u8 {
+        self.stagnation
+    }
+
+    /// how many more full turns, one of each team, without progress it takes for the game
+    /// to be drawn. a half-played full turn counts as a whole one.
+    pub fn turns_until_draw(&self) -> u8 {
+        Self::STAGNATION_LIMIT
+            .saturating_sub(self.stagnation)
+            .div_ceil(2)
+    }
+
+    fn make_progress(&mut self) {
+        self.stagnation = 0;
+        self.progressed = true;
     }
 
     pub fn is_ongoing(&self) -> bool {
         matches!(self.state, GameState::Ongoing { .. })
     }
 
+    /// a team to move without any legal action is stalemated, which draws the game.
+    fn check_stalemate(&mut self) {
+        if self.is_ongoing() && !self.has_legal_action() {
+            self.state = GameState::Finished(Winner(None));
+        }
+    }
+
     pub fn verify_move(&self, from: Pos, to: Pos) -> Result<(), InvalidMove> {
         // we don't have to check for power because it should immediately switch turns then
 
@@ -405,52 +596,17 @@ impl Game {
             return Err(InvalidMove::NotYourPiece);
         }
 
-        let ([dx, dy], dist) = from.dir_to(to).ok_or(InvalidPieceMove::NonCompassMove)?;
-
-        let ray_index = Piece::ray_index(dx, dy).unwrap();
-        let moves = piece.moves();
-        let (move_kind, range) = moves[ray_index];
-        if range < dist {
-            Err(InvalidPieceMove::TooFar)?;
-        }
-
-        if move_kind != MoveKind::Recall {
-            let mut temp = from;
-            for _ in 1..range {
-                temp = temp
-                    .shift(dx, dy)
-                    .expect("from and to are guaranteed to be within bounds.");
-                if self.board[temp].0.is_some() {
-                    Err(InvalidPieceMove::Blocked)?;
-                }
-            }
-        }
-
-        if self.board[to].0.map_or(false, |t| t.team == self.turn) {
-            Err(InvalidPieceMove::FriendlyFire)?;
-        }
-
-        match move_kind {
-            MoveKind::MoveOnly if self.board[to].0.is_some() => Err(InvalidPieceMove::Blocked),
-            MoveKind::CaptureOnly | MoveKind::Convert if self.board[to].0.is_none() => {
-                Err(InvalidPieceMove::MustCapture)
-            }
-            MoveKind::MoveMoveCapture if dist == 1 && self.board[to].0.is_some() => {
-                Err(InvalidPieceMove::NoMelee)
-            }
-            MoveKind::Recall if dist < range => Err(InvalidPieceMove::CannotRecallHere),
-            _ => Ok(()),
-        }?;
+        self.board.verify_piece_move(from, to)?;
         Ok(())
     }
 
     /// the number of pieces required for the merge kind is checked during input
     ///
     /// juuust in case people specify 10,000 different pieces
-    pub fn verify_merge(&self, pieces: &mut [Pos]) -> Result<(), InvalidMove> {
+    pub fn verify_merge(&self, pieces: &[Pos]) -> Result<(), InvalidMove> {
         let home_rows = match self.turn {
             Team::Blue => 00..20,
-            Team::Red => 90..100,
+            Team::Red => 80..100,
         };
 
         for p in pieces.iter() {
@@ -471,7 +627,8 @@ impl Game {
             }
         }
 
-        verify_polyomino(pieces)?;
+        // checking a copy keeps the destination at the end of the list.
+        verify_polyomino(&mut pieces.to_vec())?;
 
         Ok(())
     }
@@ -482,22 +639,25 @@ impl Game {
     }
 
     pub fn verify_action(&self, mut p_move: Move) -> Result<VerifiedMove, InvalidMove> {
-        let GameState::Ongoing { draw_offered, .. } = self.state else {
+        let GameState::Ongoing { draw_offer } = self.state else {
             return Err(InvalidMove::GameOver);
         };
+        let offered_by_opponent = draw_offer == Some(!self.turn);
         match &mut p_move {
-            Move::Resign | Move::Draw => Ok(()),
-            Move::DeclineDraw if draw_offered => Ok(()),
+            Move::Resign => Ok(()),
+            Move::Draw if draw_offer == Some(self.turn) => Err(InvalidMove::DrawAlreadyOffered),
+            Move::Draw => Ok(()),
+            Move::DeclineDraw if offered_by_opponent => Ok(()),
             Move::DeclineDraw => Err(InvalidMove::DrawNotOffered),
-            _ if draw_offered => Err(InvalidMove::DrawOffered),
             Move::Move { from, to } => self.verify_move(*from, *to),
             Move::Merge { kind: _, pieces } => self.verify_merge(pieces),
         }
         .map(|_| VerifiedMove(p_move))
     }
 
+    /// offering, accepting or declining a draw never uses up any energy or ends the turn.
     pub fn make_move(&mut self, p_move: VerifiedMove) {
-        let GameState::Ongoing { draw_offered } = &mut self.state else {
+        let GameState::Ongoing { draw_offer } = &mut self.state else {
             panic!("make_move must only be called while the game is ongoing.");
         };
 
@@ -507,26 +667,23 @@ impl Game {
                 return;
             }
             Move::Draw => {
-                if *draw_offered {
+                if *draw_offer == Some(!self.turn) {
                     self.state = GameState::Finished(Winner(None));
                 } else {
-                    self.turn = !self.turn;
-                    *draw_offered = true;
+                    *draw_offer = Some(self.turn);
                 }
                 return;
             }
             Move::DeclineDraw => {
-                if *draw_offered {
-                    *draw_offered = false;
-                    self.turn = !self.turn;
-                }
+                *draw_offer = None;
                 return;
             }
             Move::Move { from, to } => {
-                self.power -= 1;
+                self.power = self.power.saturating_sub(1);
                 let kind = self.board[from].0.unwrap().kind;
-                if kind == PieceKind::Blank {
-                    self.stagnation = 0;
+                // captures and conversions both land on an enemy piece
+                if kind == PieceKind::Blank || self.board[to].0.is_some() {
+                    self.make_progress();
                 }
                 // check if a diplomat made a diagonal move, i.e. when neither x nor y are 0
                 if kind == PieceKind::Diplomat && !from.dir_to(to).unwrap().0.contains(&0) {
@@ -539,7 +696,7 @@ impl Game {
                 self.board[from].0 = None;
             }
             Move::Merge { kind, mut pieces } => {
-                self.stagnation = 0;
+                self.make_progress();
                 self.power = self.power.saturating_sub(pieces.len() as u8);
                 let dest = pieces.pop().unwrap();
                 for pos in pieces {
@@ -557,7 +714,7 @@ impl Game {
         let victory_by_occupation = [44, 45, 54, 55]
             .map(Pos)
             .into_iter()
-            .all(|pos| self.board[pos].0.map_or(false, |p| p.team == self.turn));
+            .all(|pos| self.board[pos].0.is_some_and(|p| p.team == self.turn));
 
         if victory_by_occupation {
             self.state = GameState::Finished(Winner(Some(self.turn)));
@@ -573,11 +730,19 @@ impl Game {
         }
 
         if self.power > 0 {
+            self.check_stalemate();
             return;
         }
 
         self.turn = !self.turn;
 
+        // the opponent had their whole turn to answer, so the offer lapses.
+        if let GameState::Ongoing { draw_offer } = &mut self.state {
+            if *draw_offer == Some(self.turn) {
+                *draw_offer = None;
+            }
+        }
+
         let repetitions = self
             .position_tracker
             .entry((self.turn, self.board.clone()))
@@ -589,12 +754,14 @@ impl Game {
 
         self.power = enemy_stone_count;
         self.locked_tiles.clear();
-        if self.turn == Team::Blue {
-            self.stagnation += 1;
-            if self.stagnation > 64 {
-                self.state = GameState::Finished(Winner(None));
-            }
+        if !self.progressed {
+            self.stagnation = self.stagnation.saturating_add(1);
+        }
+        self.progressed = false;
+        if self.stagnation >= Self::STAGNATION_LIMIT {
+            self.state = GameState::Finished(Winner(None));
         }
+        self.check_stalemate();
     }
 }
 
@@ -607,8 +774,9 @@ impl Default for Game {
 impl Display for Game {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         writeln!(f, "{}", self.state)?;
-        writeln!(f, "{:?}'s turn.", self.turn)?;
+        writeln!(f, "{}'s turn.", self.turn)?;
         writeln!(f, "Remaining Stone Power: {}.", self.power)?;
+        writeln!(f, "Turns until a draw: {}.", self.turns_until_draw())?;
         writeln!(f, "\n{}", self.board)
     }
 }
@@ -0,0 +1,277 @@
+use super::{Board, Game, GameState, Pos, Position};
+use crate::piece::{Piece, PieceKind, Team, Tile};
+
+use thiserror::Error;
+
+/// how many pieces of each kind one piece set has.
+pub const PIECE_SET: [(PieceKind, u8); 7] = [
+    (PieceKind::Blank, 20),
+    (PieceKind::Warrior, 10),
+    (PieceKind::Runner, 5),
+    (PieceKind::Diplomat, 5),
+    (PieceKind::Champion, 4),
+    (PieceKind::General, 2),
+    (PieceKind::Stone, 4),
+];
+
+/// the Blanks of both piece sets, which every piece other than the starting Stones is merged from.
+const BLANKS: usize = 40;
+/// the Stones of both piece sets.
+const STONES: usize = 8;
+
+/// what [`Position::validate`] found wrong with a position.
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+pub enum Problem {
+    #[error("{0} has already won, but the game is still going.")]
+    AlreadyWon(Team),
+    #[error("The pieces on the board take {0} Blanks to merge, but both piece sets only have 40.")]
+    TooMuchMaterial(usize),
+    #[error(
+        "The power is {power}, but {turn} only has {stones} Stones and {locked} locked tiles."
+    )]
+    TooMuchPower {
+        turn: Team,
+        power: u8,
+        stones: u8,
+        locked: usize,
+    },
+    #[error("The power is 0, so the turn should already have passed.")]
+    NoPower,
+    #[error("The locked tile {0:02} does not hold a piece of the team to move.")]
+    LockedTile(i8),
+    #[error("The stagnation count is past the limit, so the game should already be drawn.")]
+    Stagnated,
+    // the rest are possible, but unusual.
+    #[error("The game is already over.")]
+    Finished,
+    #[error(
+        "{team} has {count} {kind:?} pieces, more than one piece set has without conversions."
+    )]
+    BeyondSet {
+        team: Team,
+        kind: PieceKind,
+        count: u8,
+    },
+    #[error(
+        "The power is {power}, but {turn} has {stones} Stones and has not played yet this turn."
+    )]
+    SpentPower { turn: Team, power: u8, stones: u8 },
+}
+
+impl Problem {
+    /// whether no game played by the rules can reach the position.
+    /// otherwise, the position is only suspicious.
+    pub fn is_impossible(&self) -> bool {
+        !matches!(
+            self,
+            Problem::Finished | Problem::BeyondSet { .. } | Problem::SpentPower { .. }
+        )
+    }
+}
+
+/// the Blanks it takes to merge a piece of the kind. Blanks take themselves.
+fn blank_cost(kind: PieceKind) -> usize {
+    kind.merge_costs().unwrap_or(1)
+}
+
+impl Position {
+    /// everything that is impossible or suspicious about the position, impossible problems first.
+    pub fn validate(&self) -> Vec<Problem> {
+        let mut problems = vec![];
+        let board = &self.board;
+        let ongoing = matches!(self.state, GameState::Ongoing { .. });
+
+        if ongoing {
+            for team in [self.turn, !self.turn] {
+                let occupied = [44, 45, 54, 55]
+                    .into_iter()
+                    .all(|p| board[Pos(p)].0.is_some_and(|p| p.team == team));
+                if occupied || board.stone_count(!team) == 0 || board.piece_count(!team) == 0 {
+                    problems.push(Problem::AlreadyWon(team));
+                }
+            }
+        }
+
+        // stones beyond the starting ones must have been merged.
+        let pieces = board.tiles.iter().filter_map(|t| t.0);
+        let stones = pieces
+            .clone()
+            .filter(|p| p.kind == PieceKind::Stone)
+            .count();
+        let cost = pieces
+            .filter(|p| p.kind != PieceKind::Stone)
+            .map(|p| blank_cost(p.kind))
+            .sum::<usize>()
+            + stones.saturating_sub(STONES) * blank_cost(PieceKind::Stone);
+        if cost > BLANKS {
+            problems.push(Problem::TooMuchMaterial(cost));
+        }
+
+        if ongoing {
+            // stones are never lost during their own team's turn, and every action costs power.
+            let stones = board.stone_count(self.turn);
+            if self.power as usize + self.locked_tiles.len() > stones as usize {
+                problems.push(Problem::TooMuchPower {
+                    turn: self.turn,
+                    power: self.power,
+                    stones,
+                    locked: self.locked_tiles.len(),
+                });
+            }
+            if self.power == 0 {
+                problems.push(Problem::NoPower);
+            }
+            for &pos in &self.locked_tiles {
+                if board[pos].0.is_none_or(|p| p.team != self.turn) {
+                    problems.push(Problem::LockedTile(pos.0));
+                }
+            }
+            if self.stagnation >= Game::STAGNATION_LIMIT {
+                problems.push(Problem::Stagnated);
+            }
+        } else {
+            problems.push(Problem::Finished);
+        }
+
+        for team in [Team::Blue, Team::Red] {
+            for (kind, max) in PIECE_SET {
+                let count = board
+                    .tiles
+                    .iter()
+                    .filter(|t| t.0 == Some(Piece { team, kind }))
+                    .count() as u8;
+                if count > max {
+                    problems.push(Problem::BeyondSet { team, kind, count });
+                }
+            }
+        }
+
+        if ongoing && self.locked_tiles.is_empty() && !self.progressed {
+            let stones = board.stone_count(self.turn);
+            if self.power < stones {
+                problems.push(Problem::SpentPower {
+                    turn: self.turn,
+                    power: self.power,
+                    stones,
+                });
+            }
+        }
+
+        problems
+    }
+}
+
+/// sets up positions for lessons and tests, one piece at a time.
+///
+/// the power follows the Stones of the team to move, as at the start of its turn,
+/// until it is set with [`Editor::power`].
+#[derive(Debug, Clone)]
+pub struct Editor {
+    position: Position,
+    fixed_power: bool,
+}
+
+impl Editor {
+    /// an empty board, with Blue to move.
+    pub fn new() -> Self {
+        // an empty board is stalemated, but it is only something to edit.
+        let position = Position {
+            state: GameState::Ongoing { draw_offer: None },
+            ..Game::default().position()
+        };
+        Self {
+            position,
+            fixed_power: false,
+        }
+    }
+
+    pub fn position(&self) -> &Position {
+        &self.position
+    }
+
+    fn edited(&mut self) -> &mut Self {
+        if !self.fixed_power {
+            self.position.power = self.position.board.stone_count(self.position.turn);
+        }
+        self
+    }
+
+    /// puts the piece on `pos`, replacing whatever was there.
+    pub fn place(&mut self, pos: Pos, piece: Piece) -> &mut Self {
+        self.position.board[pos] = Tile(Some(piece));
+        self.edited()
+    }
+
+    /// takes the piece off `pos`, if there is one.
+    pub fn remove(&mut self, pos: Pos) -> Option<Piece> {
+        let piece = self.position.board[pos].0.take();
+        self.edited();
+        piece
+    }
+
+    /// gives the piece on `pos` to the other team, as a Diplomat would.
+    pub fn swap_team(&mut self, pos: Pos) -> &mut Self {
+        if let Some(piece) = &mut self.position.board[pos].0 {
+            piece.team = !piece.team;
+        }
+        self.edited()
+    }
+
+    /// takes every piece off the board.
+    pub fn clear(&mut self) -> &mut Self {
+        self.position.board = Board::default();
+        self.position.locked_tiles.clear();
+        self.edited()
+    }
+
+    /// starts the team's turn: nothing is locked, and the power follows its Stones again.
+    pub fn turn(&mut self, team: Team) -> &mut Self {
+        self.position.turn = team;
+        self.position.locked_tiles.clear();
+        self.position.progressed = false;
+        self.fixed_power = false;
+        self.edited()
+    }
+
+    /// sets the power left in the turn, which then stays as it is.
+    pub fn power(&mut self, power: u8) -> &mut Self {
+        self.position.power = power;
+        self.fixed_power = true;
+        self
+    }
+
+    pub fn validate(&self) -> Vec<Problem> {
+        self.position.validate()
+    }
+
+    /// the game at the position, unless the position is impossible.
+    /// suspicious positions are allowed.
+    pub fn build(&self) -> Result<Game, Vec<Problem>> {
+        let problems = self
+            .validate()
+            .into_iter()
+            .filter(Problem::is_impossible)
+            .collect::<Vec<_>>();
+        if problems.is_empty() {
+            Ok(self.position.clone().into())
+        } else {
+            Err(problems)
+        }
+    }
+}
+
+impl Default for Editor {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// edits a copy of the position, keeping its power as it is.
+impl From<Position> for Editor {
+    fn from(position: Position) -> Self {
+        Self {
+            position,
+            fixed_power: true,
+        }
+    }
+}
@@ -0,0 +1,57 @@
+use super::Game;
+use crate::{piece::Team, util::Rng};
+use std::sync::OnceLock;
+
+/// random numbers for every feature of a position, from a fixed seed.
+///
+/// opening books store these hashes, so the seed and order must never change.
+struct Keys {
+    /// indexed by tile, then by team and kind.
+    pieces: [[u64; 14]; 100],
+    locked: [u64; 100],
+    power: [u64; 256],
+    red_to_move: u64,
+}
+
+fn keys() -> &'static Keys {
+    static KEYS: OnceLock<Box<Keys>> = OnceLock::new();
+    KEYS.get_or_init(|| {
+        let mut rng = Rng::new(0x6b65_6c61_7375);
+        let mut keys = Box::new(Keys {
+            pieces: [[0; 14]; 100],
+            locked: [0; 100],
+            power: [0; 256],
+            red_to_move: 0,
+        });
+        for tile in &mut keys.pieces {
+            tile.fill_with(|| rng.next_u64());
+        }
+        keys.locked.fill_with(|| rng.next_u64());
+        keys.power.fill_with(|| rng.next_u64());
+        keys.red_to_move = rng.next_u64();
+        keys
+    })
+}
+
+impl Game {
+    /// a Zobrist hash of everything that decides which actions are legal:
+    /// the board, the team to move, its remaining power and the locked tiles.
+    ///
+    /// it is the same on every platform and in every version, so it can be saved.
+    pub fn position_key(&self) -> u64 {
+        let keys = keys();
+        let mut key = keys.power[self.power as usize];
+        if self.turn == Team::Red {
+            key ^= keys.red_to_move;
+        }
+        for (tile, piece) in self.board.tiles.iter().enumerate() {
+            if let Some(piece) = piece.0 {
+                key ^= keys.pieces[tile][piece.team as usize * 7 + piece.kind as usize];
+            }
+        }
+        for tile in &self.locked_tiles {
+            key ^= keys.locked[tile.0 as usize];
+        }
+        key
+    }
+}
@@ -0,0 +1,200 @@
+use super::{Board, Game, Move, Pos, VerifiedMove};
+use crate::piece::{Piece, PieceKind, Team};
+
+const DIRECTIONS: [(i8, i8); 8] = [
+    (0, 1),
+    (1, 1),
+    (1, 0),
+    (1, -1),
+    (0, -1),
+    (-1, -1),
+    (-1, 0),
+    (-1, 1),
+];
+
+/// one bit per tile, indexed by [`Pos`].
+type TileSet = u128;
+
+fn bit(Pos(p): Pos) -> TileSet {
+    1 << p
+}
+
+fn neighbors(pos: Pos) -> TileSet {
+    [(-1, 0), (1, 0), (0, -1), (0, 1)]
+        .into_iter()
+        .filter_map(|(dx, dy)| pos.shift(dx, dy))
+        .fold(0, |set, p| set | bit(p))
+}
+
+fn tiles(mut set: TileSet) -> impl Iterator<Item = Pos> {
+    std::iter::from_fn(move || {
+        (set != 0).then(|| {
+            let p = set.trailing_zeros();
+            set &= set - 1;
+            Pos(p as i8)
+        })
+    })
+}
+
+/// collects every connected group of `size` tiles out of `allowed`, each exactly once.
+///
+/// this is the ESU algorithm: groups only grow into tiles past their first tile,
+/// and only through tiles that no earlier tile of the group could have reached.
+fn connected_groups(allowed: TileSet, size: usize, out: &mut Vec<TileSet>) {
+    fn extend(
+        group: TileSet,
+        len: usize,
+        mut frontier: TileSet,
+        reached: TileSet,
+        above_first: TileSet,
+        size: usize,
+        out: &mut Vec<TileSet>,
+    ) {
+        if len == size {
+            out.push(group);
+            return;
+        }
+        while frontier != 0 {
+            let next = frontier & frontier.wrapping_neg();
+            frontier &= !next;
+            let pos = Pos(next.trailing_zeros() as i8);
+            let new_neighbors = neighbors(pos) & above_first & !reached;
+            extend(
+                group | next,
+                len + 1,
+                frontier | new_neighbors,
+                reached | new_neighbors | neighbors(pos),
+                above_first,
+                size,
+                out,
+            );
+        }
+    }
+
+    for first in tiles(allowed) {
+        let above_first = allowed & !(bit(first) | (bit(first) - 1));
+        let frontier = neighbors(first) & above_first;
+        let reached = bit(first) | neighbors(first);
+        extend(bit(first), 1, frontier, reached, above_first, size, out);
+    }
+}
+
+impl Board {
+    /// every move the pieces of `team` could make, as `(from, to)` pairs,
+    /// if it was their turn and none of their pieces had moved yet.
+    pub fn piece_moves(&self, team: Team) -> Vec<(Pos, Pos)> {
+        let mut moves = vec![];
+        for from in (0..100).map(Pos) {
+            if self[from].0.map(|p| p.team) != Some(team) {
+                continue;
+            }
+            for (dx, dy) in DIRECTIONS {
+                let mut to = from;
+                while let Some(next) = to.shift(dx, dy) {
+                    to = next;
+                    if self.verify_piece_move(from, to).is_ok() {
+                        moves.push((from, to));
+                    }
+                }
+            }
+        }
+        moves
+    }
+}
+
+impl Game {
+    /// every legal piece move for the team to move, in no particular order.
+    pub fn legal_moves(&self) -> Vec<VerifiedMove> {
+        if !self.is_ongoing() {
+            return vec![];
+        }
+        self.board
+            .piece_moves(self.turn)
+            .into_iter()
+            .filter(|(from, _)| !self.locked_tiles.contains(from))
+            .map(|(from, to)| VerifiedMove(Move::Move { from, to }))
+            .collect()
+    }
+
+    /// whether the team to move has any legal move or merge, without listing them all.
+    pub fn has_legal_action(&self) -> bool {
+        if !self.is_ongoing() {
+            return false;
+        }
+        let can_move = (0..100).map(Pos).any(|from| {
+            self.board[from].0.map(|p| p.team) == Some(self.turn)
+                && !self.locked_tiles.contains(&from)
+                && DIRECTIONS.into_iter().any(|(dx, dy)| {
+                    std::iter::successors(from.shift(dx, dy), |to| to.shift(dx, dy))
+                        .any(|to| self.board.verify_piece_move(from, to).is_ok())
+                })
+        });
+        can_move || !self.legal_merges().is_empty()
+    }
+
+    /// every tile the piece on `from` can legally move to.
+    pub fn destinations(&self, from: Pos) -> Vec<Pos> {
+        self.legal_moves()
+            .into_iter()
+            .filter_map(|action| match *action.action() {
+                Move::Move { from: f, to } if f == from => Some(to),
+                _ => None,
+            })
+            .collect()
+    }
+
+    /// every legal merge for the team to move.
+    ///
+    /// the blanks of each merge are listed in ascending order, followed by the destination.
+    pub fn legal_merges(&self) -> Vec<VerifiedMove> {
+        let mut merges = vec![];
+        if !self.is_ongoing() {
+            return merges;
+        }
+        let home_rows = match self.turn {
+            Team::Blue => 00..20,
+            Team::Red => 80..100,
+        };
+        let blank = Some(Piece {
+            team: self.turn,
+            kind: PieceKind::Blank,
+        });
+        let allowed = (0..100)
+            .map(Pos)
+            .filter(|p| {
+                self.board[*p].0 == blank
+                    && !home_rows.contains(&p.0)
+                    && !self.locked_tiles.contains(p)
+            })
+            .fold(0, |set, p| set | bit(p));
+
+        let mut groups = vec![];
+        for kind in [
+            PieceKind::Warrior,
+            PieceKind::Runner,
+            PieceKind::Diplomat,
+            PieceKind::Champion,
+            PieceKind::General,
+            PieceKind::Stone,
+        ] {
+            let size = kind.merge_costs().unwrap();
+            groups.clear();
+            connected_groups(allowed, size, &mut groups);
+            for &group in &groups {
+                for dest in tiles(group) {
+                    let mut pieces = tiles(group & !bit(dest)).collect::<Vec<_>>();
+                    pieces.push(dest);
+                    merges.push(VerifiedMove(Move::Merge { kind, pieces }));
+                }
+            }
+        }
+        merges
+    }
+
+    /// every legal move and merge. resigning and draws are left out.
+    pub fn legal_actions(&self) -> Vec<VerifiedMove> {
+        let mut actions = self.legal_moves();
+        actions.extend(self.legal_merges());
+        actions
+    }
+}
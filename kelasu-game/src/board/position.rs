@@ -0,0 +1,195 @@
+use super::{Board, Game, GameState, Pos, Winner};
+use crate::piece::{Icon, Team};
+use std::{collections::HashMap, fmt::Display, str::FromStr};
+
+use thiserror::Error;
+
+/// Everything needed to resume a game, written on a single line:
+///
+/// `<board> <turn> <power> <locked tiles> <state> <stagnation>`
+///
+/// - the board is its 10 rows separated by `/`, with `.` for empty tiles.
+/// - the locked tiles are `<yx>` positions separated by `,`, or `-` if there are none.
+/// - the state is `ongoing`, `offer-blue`, `offer-red`, `draw`, `win-blue` or `win-red`.
+/// - the stagnation is [`Game::stagnation`], followed by `+` if the current turn has made progress.
+///
+/// The starting position is:
+///
+/// `BBBBBBBBBB/BBBBBBBBBB/S.S....S.S/........../........../........../........../s.s....s.s/bbbbbbbbbb/bbbbbbbbbb blue 4 - ongoing 0`
+///
+/// The positions seen so far are not saved, so repetitions are counted from scratch after loading.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Position {
+    pub state: GameState,
+    pub turn: Team,
+    pub power: u8,
+    pub board: Board,
+    pub locked_tiles: Vec<Pos>,
+    pub stagnation: u8,
+    pub progressed: bool,
+}
+
+impl Game {
+    /// the locked tiles are sorted, so the order pieces moved in does not matter.
+    pub fn position(&self) -> Position {
+        let mut locked_tiles = self.locked_tiles.clone();
+        locked_tiles.sort();
+        Position {
+            state: self.state.clone(),
+            turn: self.turn,
+            power: self.power,
+            board: self.board.clone(),
+            locked_tiles,
+            stagnation: self.stagnation,
+            progressed: self.progressed,
+        }
+    }
+}
+
+impl From<Position> for Game {
+    fn from(position: Position) -> Self {
+        let mut game = Self {
+            state: position.state,
+            turn: position.turn,
+            power: position.power,
+            board: position.board,
+            locked_tiles: position.locked_tiles,
+            position_tracker: HashMap::new(),
+            stagnation: position.stagnation,
+            progressed: position.progressed,
+        };
+        game.check_stalemate();
+        game
+    }
+}
+
+impl Display for Position {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for (y, row) in self.board.tiles.chunks(10).enumerate() {
+            if y > 0 {
+                write!(f, "/")?;
+            }
+            for tile in row {
+                write!(f, "{}", tile.0.map_or('.', |p| p.icon()))?;
+            }
+        }
+
+        write!(f, " {} {} ", self.turn.name(), self.power)?;
+
+        if self.locked_tiles.is_empty() {
+            write!(f, "-")?;
+        }
+        for (i, Pos(p)) in self.locked_tiles.iter().enumerate() {
+            if i > 0 {
+                write!(f, ",")?;
+            }
+            write!(f, "{p:02}")?;
+        }
+
+        let state = match self.state {
+            GameState::Ongoing { draw_offer: None } => "ongoing",
+            GameState::Ongoing {
+                draw_offer: Some(Team::Blue),
+            } => "offer-blue",
+            GameState::Ongoing {
+                draw_offer: Some(Team::Red),
+            } => "offer-red",
+            GameState::Finished(Winner(None)) => "draw",
+            GameState::Finished(Winner(Some(Team::Blue))) => "win-blue",
+            GameState::Finished(Winner(Some(Team::Red))) => "win-red",
+        };
+        write!(f, " {state} {}", self.stagnation)?;
+        if self.progressed {
+            write!(f, "+")?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Error, Debug)]
+pub enum InvalidPosition {
+    #[error("The position is missing its {0}.")]
+    MissingField(&'static str),
+    #[error("The position has more fields than expected.")]
+    TooManyFields,
+    #[error("The board is invalid: {0}")]
+    InvalidBoard(&'static str),
+    #[error("That field is invalid. {0}")]
+    InvalidField(&'static str),
+}
+
+impl FromStr for Position {
+    type Err = InvalidPosition;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut fields = s.split_whitespace();
+        let mut next_field = |name| fields.next().ok_or(InvalidPosition::MissingField(name));
+
+        let board = next_field("board")?
+            .replace('/', "")
+            .parse()
+            .map_err(InvalidPosition::InvalidBoard)?;
+
+        let turn = next_field("turn")?
+            .parse()
+            .map_err(|_| InvalidPosition::InvalidField("Turns are either `blue` or `red`."))?;
+
+        let power = next_field("power")?
+            .parse()
+            .map_err(|_| InvalidPosition::InvalidField("Power must be a number."))?;
+
+        let locked_tiles = match next_field("locked tiles")? {
+            "-" => vec![],
+            tiles => tiles
+                .split(',')
+                .map(|p| p.parse())
+                .collect::<Result<_, _>>()
+                .map_err(|_| {
+                    InvalidPosition::InvalidField(
+                        "Locked tiles must be <yx> positions separated by commas.",
+                    )
+                })?,
+        };
+
+        let state = match next_field("state")? {
+            "ongoing" => GameState::Ongoing { draw_offer: None },
+            "offer-blue" => GameState::Ongoing {
+                draw_offer: Some(Team::Blue),
+            },
+            "offer-red" => GameState::Ongoing {
+                draw_offer: Some(Team::Red),
+            },
+            "draw" => GameState::Finished(Winner(None)),
+            "win-blue" => GameState::Finished(Winner(Some(Team::Blue))),
+            "win-red" => GameState::Finished(Winner(Some(Team::Red))),
+            _ => {
+                return Err(InvalidPosition::InvalidField(
+                    "States are `ongoing`, `offer-<team>`, `draw` or `win-<team>`.",
+                ))
+            }
+        };
+
+        let stagnation = next_field("stagnation")?;
+        let (stagnation, progressed) = match stagnation.strip_suffix('+') {
+            Some(stagnation) => (stagnation, true),
+            None => (stagnation, false),
+        };
+        let stagnation = stagnation
+            .parse()
+            .map_err(|_| InvalidPosition::InvalidField("Stagnation must be a number."))?;
+
+        if fields.next().is_some() {
+            return Err(InvalidPosition::TooManyFields);
+        }
+
+        Ok(Self {
+            state,
+            turn,
+            power,
+            board,
+            locked_tiles,
+            stagnation,
+            progressed,
+        })
+    }
+}
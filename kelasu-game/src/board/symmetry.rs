@@ -0,0 +1,137 @@
+use super::{Board, Game, GameState, Move, Pos, Position, Winner};
+use crate::piece::Team;
+
+/// a way to move the whole board that keeps the rules the same.
+///
+/// flipping the board upside down also swaps the teams, since Blue moves down the board
+/// and Red moves up it, and each team's home rows are at its own end. every symmetry undoes itself.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum Symmetry {
+    #[default]
+    Identity,
+    /// left to right.
+    Mirror,
+    /// top to bottom, swapping the teams.
+    Flip,
+    /// both, which turns the board half way round.
+    MirrorFlip,
+}
+
+impl Symmetry {
+    pub const ALL: [Self; 4] = [Self::Identity, Self::Mirror, Self::Flip, Self::MirrorFlip];
+
+    fn mirrors(self) -> bool {
+        matches!(self, Self::Mirror | Self::MirrorFlip)
+    }
+
+    pub fn swaps_teams(self) -> bool {
+        matches!(self, Self::Flip | Self::MirrorFlip)
+    }
+
+    pub fn pos(self, pos: Pos) -> Pos {
+        let [mut x, mut y] = pos.xy();
+        if self.mirrors() {
+            x = 9 - x;
+        }
+        if self.swaps_teams() {
+            y = 9 - y;
+        }
+        Pos(y * 10 + x)
+    }
+
+    pub fn team(self, team: Team) -> Team {
+        if self.swaps_teams() {
+            !team
+        } else {
+            team
+        }
+    }
+
+    pub fn board(self, board: &Board) -> Board {
+        let mut out = Board::default();
+        for (i, tile) in board.tiles.iter().enumerate() {
+            let mut tile = *tile;
+            if let Some(piece) = &mut tile.0 {
+                piece.team = self.team(piece.team);
+            }
+            out[self.pos(Pos(i as i8))] = tile;
+        }
+        out
+    }
+
+    /// the same action on the moved board. merges keep their destination last.
+    pub fn action(self, action: &Move) -> Move {
+        match action {
+            Move::Move { from, to } => Move::Move {
+                from: self.pos(*from),
+                to: self.pos(*to),
+            },
+            Move::Merge { kind, pieces } => Move::Merge {
+                kind: *kind,
+                pieces: pieces.iter().map(|&p| self.pos(p)).collect(),
+            },
+            action => action.clone(),
+        }
+    }
+
+    pub fn position(self, position: &Position) -> Position {
+        let state = match position.state {
+            GameState::Ongoing { draw_offer } => GameState::Ongoing {
+                draw_offer: draw_offer.map(|team| self.team(team)),
+            },
+            GameState::Finished(Winner(winner)) => {
+                GameState::Finished(Winner(winner.map(|team| self.team(team))))
+            }
+        };
+        let mut locked_tiles = position
+            .locked_tiles
+            .iter()
+            .map(|&p| self.pos(p))
+            .collect::<Vec<_>>();
+        locked_tiles.sort();
+        Position {
+            state,
+            turn: self.team(position.turn),
+            power: position.power,
+            board: self.board(&position.board),
+            locked_tiles,
+            stagnation: position.stagnation,
+            progressed: position.progressed,
+        }
+    }
+}
+
+impl Board {
+    /// left to right.
+    pub fn mirrored(&self) -> Self {
+        Symmetry::Mirror.board(self)
+    }
+
+    /// top to bottom, swapping the teams.
+    pub fn flipped(&self) -> Self {
+        Symmetry::Flip.board(self)
+    }
+}
+
+impl Position {
+    /// the same position for every position that a symmetry turns into each other,
+    /// and the symmetry that turns this position into it.
+    ///
+    /// the canonical position always has Blue to move.
+    pub fn canonical(&self) -> (Self, Symmetry) {
+        Symmetry::ALL
+            .into_iter()
+            .filter(|s| s.team(self.turn) == Team::Blue)
+            .map(|s| (s.position(self), s))
+            .min_by(|(a, _), (b, _)| (&a.board, &a.locked_tiles).cmp(&(&b.board, &b.locked_tiles)))
+            .unwrap()
+    }
+}
+
+impl Game {
+    /// the [`Game::position_key`] of the [`Position::canonical`] position,
+    /// which is the same for positions that only differ by a symmetry.
+    pub fn canonical_key(&self) -> u64 {
+        Game::from(self.position().canonical().0).position_key()
+    }
+}
@@ -1,3 +1,5 @@
+#![allow(clippy::zero_prefixed_literal)]
+
 use super::*;
 
 #[test]
@@ -145,6 +147,16 @@ pub(crate) fn test_recall() {
     );
 }
 
+#[test]
+pub(crate) fn test_verify_empty_tile() {
+    let board = Board::new();
+    assert!(matches!(
+        board.verify_piece_move(Pos(50), Pos(60)),
+        Err(InvalidPieceMove::NoPiece)
+    ));
+    assert!(board.verify_piece_move(Pos(11), Pos(21)).is_ok());
+}
+
 #[test]
 pub(crate) fn test_repetition() {
     let mut game = Game::from_position(
@@ -207,3 +219,572 @@ pub(crate) fn test_repetition() {
     // draw
     assert_eq!(game.state, GameState::Finished(Winner(None)));
 }
+
+#[test]
+pub(crate) fn test_stalemate() {
+    // red has power left after moving its only piece that can move, which is then locked.
+    let mut game = Game::from_position(
+        Team::Blue,
+        "
+            B........S
+            ..........
+            ..........
+            ..........
+            ..........
+            ..........
+            ..........
+            ..........
+            s.........
+            s........b
+        "
+        .parse()
+        .unwrap(),
+    );
+    for action in ["move 00 to 10", "move 99 to 89"] {
+        assert!(game.has_legal_action());
+        game.make_move(game.verify_move_str(action).unwrap());
+    }
+    assert_eq!(game.turn, Team::Red);
+    assert_eq!(game.state, GameState::Finished(Winner(None)));
+    assert!(!game.has_legal_action());
+
+    // games can start stalemated too, whether from a board or from a position.
+    let board = "S........./........../........../........../........../........../........../........../........../.........s";
+    let game = Game::from_position(Team::Blue, board.replace('/', "").parse().unwrap());
+    assert_eq!(game.state, GameState::Finished(Winner(None)));
+    let position = format!("{board} blue 1 - ongoing 0")
+        .parse::<Position>()
+        .unwrap();
+    assert_eq!(
+        Game::from(position).state,
+        GameState::Finished(Winner(None))
+    );
+}
+
+fn draw_offer_game() -> Game {
+    Game::from_position(
+        Team::Blue,
+        "
+            W..W....SS
+            ..........
+            ..........
+            ..........
+            ..........
+            ..........
+            ..........
+            ..........
+            ..........
+            w..w....ss
+        "
+        .parse()
+        .unwrap(),
+    )
+}
+
+fn play(game: &mut Game, p_move: Move) {
+    game.make_move(game.verify_action(p_move).unwrap());
+}
+
+fn draw_offer(game: &Game) -> Option<Team> {
+    match game.state {
+        GameState::Ongoing { draw_offer } => draw_offer,
+        GameState::Finished(_) => panic!("the game should still be ongoing."),
+    }
+}
+
+#[test]
+pub(crate) fn test_draw_offer_keeps_turn() {
+    let mut game = draw_offer_game();
+    play(
+        &mut game,
+        Move::Move {
+            from: Pos(00),
+            to: Pos(01),
+        },
+    );
+    play(&mut game, Move::Draw);
+    assert_eq!(draw_offer(&game), Some(Team::Blue));
+    assert_eq!(game.turn, Team::Blue);
+    assert_eq!(game.power, 1);
+    assert_eq!(game.locked_tiles, vec![Pos(01)]);
+
+    // the offering team cannot answer its own offer
+    assert!(matches!(
+        game.verify_action(Move::Draw),
+        Err(InvalidMove::DrawAlreadyOffered)
+    ));
+    assert!(matches!(
+        game.verify_action(Move::DeclineDraw),
+        Err(InvalidMove::DrawNotOffered)
+    ));
+}
+
+#[test]
+pub(crate) fn test_draw_offer_accepted() {
+    let mut game = draw_offer_game();
+    play(&mut game, Move::Draw);
+    play(
+        &mut game,
+        Move::Move {
+            from: Pos(00),
+            to: Pos(01),
+        },
+    );
+    play(
+        &mut game,
+        Move::Move {
+            from: Pos(03),
+            to: Pos(04),
+        },
+    );
+    assert_eq!(game.turn, Team::Red);
+    assert_eq!(draw_offer(&game), Some(Team::Blue));
+
+    // the opponent may keep playing before answering
+    play(
+        &mut game,
+        Move::Move {
+            from: Pos(90),
+            to: Pos(91),
+        },
+    );
+    assert_eq!(draw_offer(&game), Some(Team::Blue));
+    play(&mut game, Move::Draw);
+    assert_eq!(game.state, GameState::Finished(Winner(None)));
+}
+
+#[test]
+pub(crate) fn test_draw_offer_declined() {
+    let mut game = draw_offer_game();
+    play(&mut game, Move::Draw);
+    play(
+        &mut game,
+        Move::Move {
+            from: Pos(00),
+            to: Pos(01),
+        },
+    );
+    play(
+        &mut game,
+        Move::Move {
+            from: Pos(03),
+            to: Pos(04),
+        },
+    );
+    play(&mut game, Move::DeclineDraw);
+    assert_eq!(draw_offer(&game), None);
+    assert_eq!(game.turn, Team::Red);
+    assert_eq!(game.power, 2);
+    assert!(matches!(
+        game.verify_action(Move::DeclineDraw),
+        Err(InvalidMove::DrawNotOffered)
+    ));
+
+    // declining does not stop the opponent from offering again later
+    play(&mut game, Move::Draw);
+    assert_eq!(draw_offer(&game), Some(Team::Red));
+}
+
+#[test]
+pub(crate) fn test_draw_offer_lapses() {
+    let mut game = draw_offer_game();
+    play(&mut game, Move::Draw);
+    for (from, to) in [(00, 01), (03, 04), (90, 91), (93, 94)] {
+        play(
+            &mut game,
+            Move::Move {
+                from: Pos(from),
+                to: Pos(to),
+            },
+        );
+    }
+    // red ignored the offer for a whole turn
+    assert_eq!(game.turn, Team::Blue);
+    assert_eq!(draw_offer(&game), None);
+
+    // so blue cannot decline it, and drawing is a fresh offer instead of an agreement
+    assert!(matches!(
+        game.verify_action(Move::DeclineDraw),
+        Err(InvalidMove::DrawNotOffered)
+    ));
+    play(&mut game, Move::Draw);
+    assert_eq!(draw_offer(&game), Some(Team::Blue));
+}
+
+#[test]
+pub(crate) fn test_resign_with_draw_offered() {
+    let mut game = draw_offer_game();
+    play(&mut game, Move::Draw);
+    play(&mut game, Move::Resign);
+    assert_eq!(game.state, GameState::Finished(Winner(Some(Team::Red))));
+    assert!(matches!(
+        game.verify_action(Move::Draw),
+        Err(InvalidMove::GameOver)
+    ));
+}
+
+#[test]
+pub(crate) fn test_position_round_trip() {
+    let start = "\
+        BBBBBBBBBB/BBBBBBBBBB/S.S....S.S/........../........../\
+        ........../........../s.s....s.s/bbbbbbbbbb/bbbbbbbbbb \
+        blue 4 - ongoing 0";
+    assert_eq!(Game::new().position().to_string(), start);
+    assert_eq!(start.parse::<Position>().unwrap(), Game::new().position());
+
+    let mut game = draw_offer_game();
+    play(
+        &mut game,
+        Move::Move {
+            from: Pos(00),
+            to: Pos(01),
+        },
+    );
+    play(&mut game, Move::Draw);
+    let position = game.position().to_string();
+    assert!(position.ends_with(" blue 1 01 offer-blue 0"), "{position}");
+    let restored = Game::from(position.parse::<Position>().unwrap());
+    assert_eq!(restored.position(), game.position());
+
+    "........../ blue 1 - ongoing 0"
+        .parse::<Position>()
+        .unwrap_err();
+    format!("{start} 0").parse::<Position>().unwrap_err();
+}
+
+#[test]
+pub(crate) fn test_stagnation_survives_serialization() {
+    let mut game = Game::from_position(
+        Team::Blue,
+        "
+            SS........
+            B.G.......
+            ..........
+            ..........
+            ..........
+            ..........
+            ..........
+            ..........
+            ..........
+            .......wss
+        "
+        .parse()
+        .unwrap(),
+    );
+    // a blank move partway through the turn
+    play(
+        &mut game,
+        Move::Move {
+            from: Pos(10),
+            to: Pos(20),
+        },
+    );
+    let position = game.position();
+    assert!(position.to_string().ends_with(" 0+"));
+
+    let mut restored = Game::from(position);
+    for game in [&mut game, &mut restored] {
+        play(
+            game,
+            Move::Move {
+                from: Pos(12),
+                to: Pos(13),
+            },
+        );
+        assert_eq!(game.turn, Team::Red);
+        assert_eq!(game.stagnation(), 0);
+        assert_eq!(game.turns_until_draw(), 64);
+    }
+}
+
+/// every move that passes verification should be generated, and nothing else.
+fn check_legal_moves(game: &Game) {
+    let mut generated = game
+        .legal_moves()
+        .into_iter()
+        .map(|m| m.action().clone())
+        .collect::<Vec<_>>();
+    generated.sort_by_key(|m| m.to_string());
+
+    let mut expected = vec![];
+    for from in (0..100).map(Pos) {
+        for to in (0..100).map(Pos) {
+            let p_move = Move::Move { from, to };
+            if game.verify_action(p_move.clone()).is_ok() {
+                expected.push(p_move);
+            }
+        }
+    }
+    expected.sort_by_key(|m| m.to_string());
+    assert_eq!(generated, expected);
+}
+
+#[test]
+pub(crate) fn test_legal_moves() {
+    check_legal_moves(&Game::new());
+    check_legal_moves(&Game::from_position(
+        Team::Red,
+        "
+            S..G......
+            ..R.......
+            ....D.....
+            .w...c....
+            ....::W...
+            ..b.:C....
+            .r...d....
+            s.....w...
+            .g.......b
+            ......B...
+        "
+        .parse()
+        .unwrap(),
+    ));
+}
+
+#[test]
+pub(crate) fn test_legal_merges() {
+    let game = Game::from_position(
+        Team::Blue,
+        "
+            S.........
+            BB........
+            BB..B.....
+            ....BB....
+            .....B....
+            ..........
+            ..........
+            ..........
+            ..........
+            .........s
+        "
+        .parse()
+        .unwrap(),
+    );
+    let merges = game.legal_merges();
+    for merge in &merges {
+        game.verify_action(merge.action().clone()).unwrap();
+    }
+    let count = |kind| {
+        merges
+            .iter()
+            .filter(|m| matches!(m.action(), Move::Merge { kind: k, .. } if *k == kind))
+            .count()
+    };
+    // 20, 21 | 24, 34, 35, 45: blanks in the home rows cannot merge
+    // warriors: {20, 21}, {24, 34}, {34, 35}, {35, 45}, each with 2 destinations
+    assert_eq!(count(PieceKind::Warrior), 8);
+    // runners: {24, 34, 35, 45}, with 4 destinations
+    assert_eq!(count(PieceKind::Runner), 4);
+    assert_eq!(count(PieceKind::Champion), 0);
+    assert_eq!(
+        merges[0].action().to_string(),
+        "merge warrior at 20 with 21"
+    );
+}
+
+#[test]
+pub(crate) fn test_position_key() {
+    let start = Game::new();
+    assert_eq!(start.position_key(), Game::new().position_key());
+
+    // the same pieces moved in either order reach the same position.
+    let mut a = Game::new();
+    play(&mut a, "move 11 to 21".parse().unwrap());
+    play(&mut a, "move 13 to 23".parse().unwrap());
+    let mut b = Game::new();
+    play(&mut b, "move 13 to 23".parse().unwrap());
+    play(&mut b, "move 11 to 21".parse().unwrap());
+    assert_eq!(a.position_key(), b.position_key());
+    assert_ne!(a.position_key(), start.position_key());
+
+    let mut red = a.clone();
+    red.turn = Team::Red;
+    assert_ne!(red.position_key(), a.position_key());
+    let mut unlocked = a.clone();
+    unlocked.locked_tiles.clear();
+    assert_ne!(unlocked.position_key(), a.position_key());
+}
+
+#[test]
+pub(crate) fn test_editor() {
+    let piece = |team, kind| Piece { team, kind };
+    let mut editor = Editor::new();
+    editor
+        .place(Pos(20), piece(Team::Blue, PieceKind::Stone))
+        .place(Pos(22), piece(Team::Blue, PieceKind::Stone))
+        .place(Pos(33), piece(Team::Blue, PieceKind::General))
+        .place(Pos(70), piece(Team::Red, PieceKind::Stone))
+        .place(Pos(90), piece(Team::Red, PieceKind::Blank));
+    assert_eq!(editor.position().power, 2);
+    assert_eq!(editor.validate(), []);
+    assert_eq!(editor.build().unwrap().power, 2);
+
+    // without pieces, Red has already lost.
+    editor.swap_team(Pos(90));
+    assert_eq!(editor.validate(), [Problem::AlreadyWon(Team::Blue)]);
+    assert!(editor.build().is_err());
+    assert_eq!(
+        editor.remove(Pos(90)),
+        Some(piece(Team::Blue, PieceKind::Blank))
+    );
+    editor.place(Pos(91), piece(Team::Red, PieceKind::Blank));
+
+    editor.turn(Team::Red);
+    assert_eq!(editor.position().power, 1);
+    editor.power(3);
+    assert!(matches!(
+        editor.validate()[..],
+        [Problem::TooMuchPower { stones: 1, .. }]
+    ));
+
+    editor.clear();
+    let problems = editor.validate();
+    assert!(problems.contains(&Problem::AlreadyWon(Team::Blue)));
+    assert!(problems.contains(&Problem::AlreadyWon(Team::Red)));
+}
+
+#[test]
+pub(crate) fn test_validate() {
+    assert_eq!(Game::new().position().validate(), []);
+
+    let mut generals = Game::new().position();
+    for p in 30..60 {
+        generals.board[Pos(p)] = Tile(Some(Piece {
+            team: Team::Blue,
+            kind: PieceKind::General,
+        }));
+    }
+    let problems = generals.validate();
+    assert!(problems.contains(&Problem::TooMuchMaterial(340)));
+    assert!(problems.contains(&Problem::BeyondSet {
+        team: Team::Blue,
+        kind: PieceKind::General,
+        count: 30,
+    }));
+
+    // a finished game is unusual, but possible.
+    let finished: Position = "BBBBBBBBBB/BBBBBBBBBB/S.S....S.S/........../........../........../........../s.s....s.s/bbbbbbbbbb/bbbbbbbbbb red 4 - draw 0"
+        .parse()
+        .unwrap();
+    let problems = finished.validate();
+    assert_eq!(problems, [Problem::Finished]);
+    assert!(!problems[0].is_impossible());
+
+    let spent: Position = "BBBBBBBBBB/BBBBBBBBBB/S.S....S.S/........../........../........../........../s.s....s.s/bbbbbbbbbb/bbbbbbbbbb blue 2 - ongoing 0"
+        .parse()
+        .unwrap();
+    assert!(matches!(spent.validate()[..], [Problem::SpentPower { .. }]));
+    let moved: Position = "BBBBBBBBBB/B.BBBBBBBB/SBS....S.S/........../........../........../........../s.s....s.s/bbbbbbbbbb/bbbbbbbbbb blue 3 21 ongoing 0+"
+        .parse()
+        .unwrap();
+    assert_eq!(moved.validate(), []);
+}
+
+#[test]
+pub(crate) fn test_symmetry() {
+    assert_eq!(Board::new().mirrored(), Board::new());
+    assert_eq!(Board::new().flipped(), Board::new());
+
+    let mut rng = crate::util::Rng::new(7);
+    let mut game = Game::new();
+    for _ in 0..60 {
+        let original = game.position();
+        for symmetry in Symmetry::ALL {
+            let moved = Game::from(symmetry.position(&original));
+            let actions = game.legal_actions();
+            assert_eq!(actions.len(), moved.legal_actions().len());
+            for action in &actions {
+                // playing the moved action on the moved position is the same as moving afterwards.
+                let verified = moved
+                    .verify_action(symmetry.action(action.action()))
+                    .unwrap();
+                let mut after = moved.clone();
+                after.make_move(verified);
+                let mut expected = game.clone();
+                expected.make_move(action.clone());
+                assert_eq!(after.position(), symmetry.position(&expected.position()));
+            }
+            assert_eq!(moved.position().canonical().0, original.canonical().0);
+            assert_eq!(moved.canonical_key(), game.canonical_key());
+        }
+        let (canonical, symmetry) = original.canonical();
+        assert_eq!(canonical.turn, Team::Blue);
+        assert_eq!(symmetry.position(&canonical), original);
+
+        let mut actions = game.legal_actions();
+        if actions.is_empty() || !game.is_ongoing() {
+            break;
+        }
+        let action = actions.swap_remove(rng.below(actions.len()));
+        game.make_move(action);
+    }
+}
+
+#[test]
+pub(crate) fn test_parse_diagram() {
+    use crate::render::{Renderer, Style};
+
+    // the empty board from the README.
+    let board: Board = "
+        ```hs
+           ╔[0-1-2-3-4-5-6-7-8-9]╗
+         A ║                     ║ A
+         B ║                     ║ B
+         C ║                     ║ C
+         D ║                     ║ D
+         E ║         : :         ║ E
+         F ║         : :         ║ F
+         G ║                     ║ G
+         H ║                     ║ H
+         I ║                     ║ I
+         J ║                     ║ J
+           ╚[0-1-2-3-4-5-6-7-8-9]╝
+        ```
+    "
+    .parse()
+    .unwrap();
+    assert_eq!(board, Board::default());
+
+    // pasted from the bot, with fences, a held digit and the energy meter.
+    let game = Game::new();
+    let renderer = Renderer {
+        style: Style::Box,
+        selected: vec![Pos(0), Pos(1), Pos(20)],
+        cursor: Some(Pos(20)),
+        cursor_row: Some(3),
+        energy: true,
+        ..Default::default()
+    };
+    let pasted = format!("```hs\n{}```", renderer.render(&game));
+    assert_eq!(pasted.parse::<Board>(), Ok(game.board.clone()));
+
+    // displayed boards read back, fences and all.
+    assert_eq!(
+        game.board.to_string().parse::<Board>(),
+        Ok(game.board.clone())
+    );
+    let renderer = Renderer {
+        selected: vec![Pos(11), Pos(12)],
+        cursor_row: Some(5),
+        ..Default::default()
+    };
+    let rendered = renderer.render_board(&game.board);
+    assert_eq!(rendered.parse::<Board>(), Ok(game.board.clone()));
+
+    let mut rows = game
+        .board
+        .to_string()
+        .lines()
+        .map(str::to_owned)
+        .collect::<Vec<_>>();
+    rows.remove(4);
+    assert_eq!(
+        rows.join("\n").parse::<Board>(),
+        Err("A row of the diagram is missing.")
+    );
+    rows[2].replace_range(3..4, "x");
+    assert_eq!(
+        rows.join("\n").parse::<Board>(),
+        Err("Invalid tile in string.")
+    );
+}
@@ -0,0 +1,293 @@
+//! Opening books: what was played from a position, and how well it went.
+//!
+//! Positions are looked up by [`Game::position_key`]. Each position has a list of
+//! [`Choice`]s, which are either single actions or the rest of a whole turn.
+//!
+//! Books are saved in a small binary format, with every number in little-endian:
+//! - the magic bytes `KBOOK`, then the format version, `1`, as a byte.
+//! - the number of positions, as a `u32`. For each position, in ascending order of keys:
+//!   - the key as a `u64`, then the number of choices as a `u16`. For each choice:
+//!     - the weight as a `u32`, then the number of actions as a byte, then the actions.
+//!
+//! An action is a byte for its kind, followed by its positions as one byte each:
+//! - `0`: a move, followed by where it is from and where it goes to.
+//! - `1`: a merge, followed by the [`PieceKind`] it merges into (in declaration order),
+//!   the number of Blanks, and each Blank in the [`Move::Merge`] order.
+
+use crate::{
+    board::{Move, Pos, Winner},
+    piece::PieceKind,
+    record::Record,
+    util::Rng,
+    Game,
+};
+use std::{
+    collections::BTreeMap,
+    fs,
+    io::{self, Read},
+    path::Path,
+};
+
+use thiserror::Error;
+
+#[cfg(test)]
+mod tests;
+
+const MAGIC: &[u8] = b"KBOOK";
+const VERSION: u8 = 1;
+
+const PIECE_KINDS: [PieceKind; 7] = [
+    PieceKind::Blank,
+    PieceKind::Warrior,
+    PieceKind::Runner,
+    PieceKind::Diplomat,
+    PieceKind::Champion,
+    PieceKind::General,
+    PieceKind::Stone,
+];
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Choice {
+    /// played in order, all by the team to move.
+    pub actions: Vec<Move>,
+    /// how many games played this, counting wins twice and losses not at all.
+    pub weight: u32,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Granularity {
+    /// every choice is a single action.
+    Action,
+    /// every choice is everything left of the current turn.
+    Turn,
+}
+
+#[derive(Error, Debug)]
+pub enum InvalidBook {
+    #[error("The book could not be read: {0}")]
+    Io(#[from] io::Error),
+    #[error("That is not an opening book.")]
+    NotABook,
+    #[error("That book is version {0}, which this version cannot read.")]
+    UnknownVersion(u8),
+    #[error("The book is corrupted: {0}")]
+    Corrupted(&'static str),
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Book {
+    positions: BTreeMap<u64, Vec<Choice>>,
+}
+
+impl Book {
+    pub fn len(&self) -> usize {
+        self.positions.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.positions.is_empty()
+    }
+
+    /// every choice for the position, the heaviest first.
+    pub fn probe(&self, game: &Game) -> &[Choice] {
+        self.positions
+            .get(&game.position_key())
+            .map_or(&[], Vec::as_slice)
+    }
+
+    /// a random choice for the position, where heavier choices are picked more often.
+    pub fn pick(&self, game: &Game, rng: &mut Rng) -> Option<&Choice> {
+        let choices = self.probe(game);
+        let total = choices.iter().map(|c| c.weight as usize).sum::<usize>();
+        if total == 0 {
+            return None;
+        }
+        let mut n = rng.below(total);
+        choices.iter().find(|c| {
+            let found = n < c.weight as usize;
+            n = n.saturating_sub(c.weight as usize);
+            found
+        })
+    }
+
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, InvalidBook> {
+        Self::from_bytes(&fs::read(path)?)
+    }
+
+    pub fn save(&self, path: impl AsRef<Path>) -> io::Result<()> {
+        fs::write(path, self.to_bytes())
+    }
+
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = MAGIC.to_vec();
+        bytes.push(VERSION);
+        bytes.extend((self.positions.len() as u32).to_le_bytes());
+        for (key, choices) in &self.positions {
+            bytes.extend(key.to_le_bytes());
+            bytes.extend((choices.len() as u16).to_le_bytes());
+            for choice in choices {
+                bytes.extend(choice.weight.to_le_bytes());
+                bytes.push(choice.actions.len() as u8);
+                for action in &choice.actions {
+                    match action {
+                        Move::Move { from, to } => bytes.extend([0, from.0 as u8, to.0 as u8]),
+                        Move::Merge { kind, pieces } => {
+                            let kind = PIECE_KINDS.iter().position(|k| k == kind).unwrap();
+                            bytes.extend([1, kind as u8, pieces.len() as u8]);
+                            bytes.extend(pieces.iter().map(|p| p.0 as u8));
+                        }
+                        _ => unreachable!("books only hold moves and merges."),
+                    }
+                }
+            }
+        }
+        bytes
+    }
+
+    pub fn from_bytes(mut bytes: &[u8]) -> Result<Self, InvalidBook> {
+        fn take<const N: usize>(bytes: &mut &[u8]) -> Result<[u8; N], InvalidBook> {
+            let mut buf = [0; N];
+            bytes
+                .read_exact(&mut buf)
+                .map_err(|_| InvalidBook::Corrupted("it ends too early."))?;
+            Ok(buf)
+        }
+        fn pos(bytes: &mut &[u8]) -> Result<Pos, InvalidBook> {
+            let [p] = take(bytes)?;
+            (p < 100)
+                .then_some(Pos(p as i8))
+                .ok_or(InvalidBook::Corrupted("a position is off the board."))
+        }
+
+        if take::<5>(&mut bytes).ok().as_ref().map(|m| &m[..]) != Some(MAGIC) {
+            return Err(InvalidBook::NotABook);
+        }
+        let [version] = take(&mut bytes)?;
+        if version != VERSION {
+            return Err(InvalidBook::UnknownVersion(version));
+        }
+
+        let mut book = Self::default();
+        for _ in 0..u32::from_le_bytes(take(&mut bytes)?) {
+            let key = u64::from_le_bytes(take(&mut bytes)?);
+            let mut choices = vec![];
+            for _ in 0..u16::from_le_bytes(take(&mut bytes)?) {
+                let weight = u32::from_le_bytes(take(&mut bytes)?);
+                let mut actions = vec![];
+                for _ in 0..take::<1>(&mut bytes)?[0] {
+                    actions.push(match take(&mut bytes)? {
+                        [0] => Move::Move {
+                            from: pos(&mut bytes)?,
+                            to: pos(&mut bytes)?,
+                        },
+                        [1] => {
+                            let [kind, count] = take(&mut bytes)?;
+                            let kind = *PIECE_KINDS
+                                .get(kind as usize)
+                                .ok_or(InvalidBook::Corrupted("a piece kind is unknown."))?;
+                            let pieces = (0..count)
+                                .map(|_| pos(&mut bytes))
+                                .collect::<Result<_, _>>()?;
+                            Move::Merge { kind, pieces }
+                        }
+                        _ => return Err(InvalidBook::Corrupted("an action is unknown.")),
+                    });
+                }
+                choices.push(Choice { actions, weight });
+            }
+            book.positions.insert(key, choices);
+        }
+        if !bytes.is_empty() {
+            return Err(InvalidBook::Corrupted(
+                "there is more after the last position.",
+            ));
+        }
+        Ok(book)
+    }
+}
+
+/// collects the openings of game records into a [`Book`].
+#[derive(Debug, Clone)]
+pub struct BookBuilder {
+    granularity: Granularity,
+    /// how many turns of each game go into the book.
+    turns: usize,
+    positions: BTreeMap<u64, Vec<Choice>>,
+}
+
+impl BookBuilder {
+    pub fn new(granularity: Granularity, turns: usize) -> Self {
+        Self {
+            granularity,
+            turns,
+            positions: BTreeMap::new(),
+        }
+    }
+
+    fn add_choice(&mut self, key: u64, actions: &[Move], weight: u32) {
+        let choices = self.positions.entry(key).or_default();
+        match choices.iter_mut().find(|c| c.actions == actions) {
+            Some(choice) => choice.weight = choice.weight.saturating_add(weight),
+            None => choices.push(Choice {
+                actions: actions.to_vec(),
+                weight,
+            }),
+        }
+    }
+
+    /// adds the first turns of a finished game. unfinished games and illegal actions are skipped,
+    /// and so is everything after a resignation or a draw offer.
+    pub fn add(&mut self, record: &Record) {
+        let Some(Winner(winner)) = record.result else {
+            return;
+        };
+        let mut game = record.start_game();
+        let mut turns = 0;
+        // the actions of the turn so far, with the positions before each of them.
+        let mut turn: Vec<(u64, Move)> = vec![];
+
+        for action in &record.actions {
+            if turns >= self.turns || !matches!(action, Move::Move { .. } | Move::Merge { .. }) {
+                return;
+            }
+            let key = game.position_key();
+            let team = game.turn;
+            let Ok(verified) = game.verify_action(action.clone()) else {
+                return;
+            };
+            game.make_move(verified);
+            turn.push((key, action.clone()));
+
+            let weight = match winner {
+                Some(winner) if winner == team => 2,
+                Some(_) => 0,
+                None => 1,
+            };
+            if self.granularity == Granularity::Action {
+                self.add_choice(key, std::slice::from_ref(action), weight);
+            }
+            if game.turn != team || !game.is_ongoing() {
+                if self.granularity == Granularity::Turn {
+                    let actions = turn.iter().map(|(_, a)| a.clone()).collect::<Vec<_>>();
+                    for (i, (key, _)) in turn.iter().enumerate() {
+                        self.add_choice(*key, &actions[i..], weight);
+                    }
+                }
+                turn.clear();
+                turns += 1;
+            }
+        }
+    }
+
+    /// leaves out choices that never did well, and sorts the rest from the heaviest.
+    pub fn build(mut self) -> Book {
+        self.positions.retain(|_, choices| {
+            choices.retain(|c| c.weight > 0);
+            choices.sort_by_key(|c| std::cmp::Reverse(c.weight));
+            !choices.is_empty()
+        });
+        Book {
+            positions: self.positions,
+        }
+    }
+}
@@ -0,0 +1,116 @@
+use super::*;
+
+fn record(result: &str, actions: &[&str]) -> Record {
+    let mut text = format!("[Result \"{result}\"]\n");
+    for action in actions {
+        text.push_str(action);
+        text.push('\n');
+    }
+    text.parse().unwrap()
+}
+
+const BLUE_TURN: [&str; 4] = [
+    "move 11 to 21",
+    "move 13 to 23",
+    "move 14 to 24",
+    "move 16 to 26",
+];
+
+fn records() -> Vec<Record> {
+    let mut other_turn = BLUE_TURN;
+    other_turn[0] = "move 18 to 28";
+    vec![
+        record("blue", &BLUE_TURN),
+        record("draw", &[BLUE_TURN.as_slice(), &["move 81 to 71"]].concat()),
+        record("red", &other_turn),
+        // unfinished games are left out.
+        record("*", &other_turn),
+    ]
+}
+
+#[test]
+pub(crate) fn test_action_book() {
+    let mut builder = BookBuilder::new(Granularity::Action, 2);
+    for record in records() {
+        builder.add(&record);
+    }
+    let book = builder.build();
+
+    let start = Game::new();
+    let choices = book.probe(&start);
+    // the losing first action is left out.
+    assert_eq!(choices.len(), 1);
+    assert_eq!(choices[0].actions, ["move 11 to 21".parse().unwrap()]);
+    assert_eq!(choices[0].weight, 3);
+
+    // red's reply is in the second turn, from the drawn game.
+    let mut game = start.clone();
+    for action in BLUE_TURN {
+        game.make_move(game.verify_move_str(action).unwrap());
+    }
+    assert_eq!(book.probe(&game)[0].weight, 1);
+
+    let mut rng = Rng::new(0);
+    assert_eq!(book.pick(&start, &mut rng), Some(&choices[0]));
+    assert_eq!(book.pick(&game, &mut rng).unwrap().actions.len(), 1);
+}
+
+#[test]
+pub(crate) fn test_turn_book() {
+    let mut builder = BookBuilder::new(Granularity::Turn, 1);
+    for record in records() {
+        builder.add(&record);
+    }
+    let book = builder.build();
+
+    let mut game = Game::new();
+    assert_eq!(book.probe(&game)[0].actions.len(), 4);
+    game.make_move(game.verify_move_str(BLUE_TURN[0]).unwrap());
+    // partway through a turn, the rest of the turn is the choice.
+    let rest = &book.probe(&game)[0].actions;
+    assert_eq!(rest.len(), 3);
+    assert_eq!(rest[0].to_string(), BLUE_TURN[1]);
+    game.make_move(game.verify_move_str(BLUE_TURN[1]).unwrap());
+    game.make_move(game.verify_move_str(BLUE_TURN[2]).unwrap());
+    game.make_move(game.verify_move_str(BLUE_TURN[3]).unwrap());
+    // only one turn goes into the book.
+    assert!(book.probe(&game).is_empty());
+}
+
+#[test]
+pub(crate) fn test_book_bytes() {
+    let mut builder = BookBuilder::new(Granularity::Turn, 4);
+    for record in records() {
+        builder.add(&record);
+    }
+    let mut merge_builder = BookBuilder::new(Granularity::Action, 1);
+    merge_builder.add(&Record {
+        start: Some(
+            "BBBBBBBBBB/B..BBBBBBB/S.S....S.S/.BB......./........../........../........../s.s....s.s/bbbbbbbbbb/bbbbbbbbbb blue 4 - ongoing 0"
+                .parse()
+                .unwrap(),
+        ),
+        ..record("blue", &["merge warrior at 32 with 31"])
+    });
+
+    for book in [builder.build(), merge_builder.build()] {
+        assert!(!book.is_empty());
+        let bytes = book.to_bytes();
+        assert_eq!(Book::from_bytes(&bytes).unwrap(), book);
+
+        assert!(matches!(
+            Book::from_bytes(&bytes[..bytes.len() - 1]),
+            Err(InvalidBook::Corrupted(_))
+        ));
+        let mut newer = bytes.clone();
+        newer[5] = 2;
+        assert!(matches!(
+            Book::from_bytes(&newer),
+            Err(InvalidBook::UnknownVersion(2))
+        ));
+    }
+    assert!(matches!(
+        Book::from_bytes(b"hello"),
+        Err(InvalidBook::NotABook)
+    ));
+}
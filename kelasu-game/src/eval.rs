@@ -0,0 +1,315 @@
+//! Scores a board for one team, as a sum of weighted terms.
+//!
+//! Scores are in hundredths of a Blank by default: positive is good for the team,
+//! negative is good for its opponent. Every term compares the team with its opponent,
+//! so a position scores exactly the opposite for the other team.
+//!
+//! The weights can be loaded from a config file with one `<term> = <weight>` per line,
+//! e.g. `mobility = 5`. Blank lines and lines starting with `#` are ignored,
+//! and terms that are left out keep their default weight.
+//! `weights/default.txt` lists every term with its default.
+
+use crate::{
+    board::{Board, Pos},
+    piece::{PieceKind, Team},
+};
+use std::{fmt::Display, fs, io, path::Path, str::FromStr};
+
+use thiserror::Error;
+
+#[cfg(test)]
+mod tests;
+
+const VICTORY_TILES: [Pos; 4] = [Pos(44), Pos(45), Pos(54), Pos(55)];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Term {
+    /// the number of pieces of a kind other than Stone, which are counted by [`Term::Stones`].
+    Material(PieceKind),
+    /// the number of Stones, which is also the energy of the next turn.
+    Stones,
+    /// the number of Blanks outside the home rows that have another such Blank next to them.
+    MergePotential,
+    /// the number of victory tiles occupied.
+    VictoryControl,
+    /// how far each victory tile is from the nearest piece that can move, summed.
+    /// fewer is better, so this term is usually weighted negatively.
+    VictoryDistance,
+    /// the number of piece moves available, as if no piece had moved yet.
+    Mobility,
+    /// the number of enemy pieces a Diplomat could convert.
+    Conversions,
+}
+
+impl Term {
+    pub const ALL: [Self; 12] = [
+        Self::Material(PieceKind::Blank),
+        Self::Material(PieceKind::Warrior),
+        Self::Material(PieceKind::Runner),
+        Self::Material(PieceKind::Diplomat),
+        Self::Material(PieceKind::Champion),
+        Self::Material(PieceKind::General),
+        Self::Stones,
+        Self::MergePotential,
+        Self::VictoryControl,
+        Self::VictoryDistance,
+        Self::Mobility,
+        Self::Conversions,
+    ];
+
+    fn index(self) -> usize {
+        Self::ALL.iter().position(|t| *t == self).unwrap()
+    }
+
+    /// the value of this term for one team.
+    fn measure(self, board: &Board, team: Team) -> i32 {
+        let home_rows = match team {
+            Team::Blue => 00..20,
+            Team::Red => 80..100,
+        };
+        let is_own = |pos: Pos, kind: Option<PieceKind>| {
+            board[pos]
+                .0
+                .is_some_and(|p| p.team == team && kind.is_none_or(|k| p.kind == k))
+        };
+        let tiles = (0..100).map(Pos);
+        match self {
+            Term::Material(kind) => tiles.filter(|p| is_own(*p, Some(kind))).count() as i32,
+            Term::Stones => board.stone_count(team) as i32,
+            Term::MergePotential => {
+                let mergeable =
+                    |p: Pos| is_own(p, Some(PieceKind::Blank)) && !home_rows.contains(&p.0);
+                tiles
+                    .filter(|p| mergeable(*p))
+                    .filter(|p| {
+                        [(-1, 0), (1, 0), (0, -1), (0, 1)]
+                            .into_iter()
+                            .filter_map(|(dx, dy)| p.shift(dx, dy))
+                            .any(mergeable)
+                    })
+                    .count() as i32
+            }
+            Term::VictoryControl => {
+                VICTORY_TILES.iter().filter(|p| is_own(**p, None)).count() as i32
+            }
+            Term::VictoryDistance => {
+                let movers = tiles
+                    .filter(|p| is_own(*p, None) && !is_own(*p, Some(PieceKind::Stone)))
+                    .collect::<Vec<_>>();
+                VICTORY_TILES
+                    .iter()
+                    .map(|v| {
+                        let [vx, vy] = v.xy();
+                        movers
+                            .iter()
+                            .map(|p| {
+                                let [x, y] = p.xy();
+                                (x - vx).abs().max((y - vy).abs()) as i32
+                            })
+                            .min()
+                            // with nothing left to move, the tile is as far as it can be.
+                            .unwrap_or(9)
+                    })
+                    .sum()
+            }
+            Term::Mobility => board.piece_moves(team).len() as i32,
+            Term::Conversions => {
+                let mut targets = board
+                    .piece_moves(team)
+                    .into_iter()
+                    .filter(|(from, to)| {
+                        is_own(*from, Some(PieceKind::Diplomat))
+                            && board[*to].0.is_some()
+                            && !from.dir_to(*to).unwrap().0.contains(&0)
+                    })
+                    .map(|(_, to)| to)
+                    .collect::<Vec<_>>();
+                targets.sort();
+                targets.dedup();
+                targets.len() as i32
+            }
+        }
+    }
+}
+
+impl Display for Term {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Term::Material(kind) => write!(f, "material.{}", format!("{kind:?}").to_lowercase()),
+            Term::Stones => write!(f, "stones"),
+            Term::MergePotential => write!(f, "merge_potential"),
+            Term::VictoryControl => write!(f, "victory_control"),
+            Term::VictoryDistance => write!(f, "victory_distance"),
+            Term::Mobility => write!(f, "mobility"),
+            Term::Conversions => write!(f, "conversions"),
+        }
+    }
+}
+
+#[derive(Error, Debug)]
+#[error("I don't recognize that evaluation term.")]
+pub struct UnknownTerm;
+
+impl FromStr for Term {
+    type Err = UnknownTerm;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::ALL
+            .into_iter()
+            .find(|t| t.to_string() == s)
+            .ok_or(UnknownTerm)
+    }
+}
+
+/// how much each [`Term`] is worth.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Weights([i32; Term::ALL.len()]);
+
+impl Weights {
+    pub fn get(&self, term: Term) -> i32 {
+        self.0[term.index()]
+    }
+
+    pub fn set(&mut self, term: Term, weight: i32) {
+        self.0[term.index()] = weight;
+    }
+
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, InvalidWeights> {
+        fs::read_to_string(path)?.parse()
+    }
+
+    /// scores the board for `team`, term by term.
+    ///
+    /// whether the game is over is not taken into account.
+    pub fn evaluate(&self, board: &Board, team: Team) -> Evaluation {
+        let scores = Term::ALL.map(|term| TermScore {
+            term,
+            own: term.measure(board, team),
+            enemy: term.measure(board, !team),
+            weight: self.get(term),
+        });
+        Evaluation { team, scores }
+    }
+}
+
+/// material starts from the merge costs, with a Blank worth 100.
+impl Default for Weights {
+    fn default() -> Self {
+        let mut weights = Self([0; Term::ALL.len()]);
+        for kind in [
+            PieceKind::Warrior,
+            PieceKind::Runner,
+            PieceKind::Diplomat,
+            PieceKind::Champion,
+            PieceKind::General,
+        ] {
+            weights.set(
+                Term::Material(kind),
+                100 * kind.merge_costs().unwrap() as i32,
+            );
+        }
+        weights.set(Term::Material(PieceKind::Blank), 100);
+        weights.set(Term::Stones, 400);
+        weights.set(Term::MergePotential, 20);
+        weights.set(Term::VictoryControl, 150);
+        weights.set(Term::VictoryDistance, -30);
+        weights.set(Term::Mobility, 5);
+        weights.set(Term::Conversions, 60);
+        weights
+    }
+}
+
+/// written in the config file format.
+impl Display for Weights {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for term in Term::ALL {
+            writeln!(f, "{term} = {}", self.get(term))?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Error, Debug)]
+pub enum InvalidWeights {
+    #[error("The weights could not be read: {0}")]
+    Io(#[from] io::Error),
+    #[error("line {0}: expected `<term> = <weight>`.")]
+    Syntax(usize),
+    #[error("line {0}: {1}")]
+    UnknownTerm(usize, UnknownTerm),
+    #[error("line {0}: weights must be whole numbers.")]
+    InvalidWeight(usize),
+}
+
+impl FromStr for Weights {
+    type Err = InvalidWeights;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut weights = Self::default();
+        for (i, line) in s.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let (term, weight) = line.split_once('=').ok_or(InvalidWeights::Syntax(i + 1))?;
+            let term = term
+                .trim()
+                .parse()
+                .map_err(|e| InvalidWeights::UnknownTerm(i + 1, e))?;
+            let weight = weight
+                .trim()
+                .parse()
+                .map_err(|_| InvalidWeights::InvalidWeight(i + 1))?;
+            weights.set(term, weight);
+        }
+        Ok(weights)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TermScore {
+    pub term: Term,
+    /// the value of the term for the evaluated team.
+    pub own: i32,
+    /// the value of the term for its opponent.
+    pub enemy: i32,
+    pub weight: i32,
+}
+
+impl TermScore {
+    pub fn score(&self) -> i32 {
+        (self.own - self.enemy) * self.weight
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Evaluation {
+    pub team: Team,
+    /// in the order of [`Term::ALL`].
+    pub scores: [TermScore; Term::ALL.len()],
+}
+
+impl Evaluation {
+    pub fn total(&self) -> i32 {
+        self.scores.iter().map(TermScore::score).sum()
+    }
+}
+
+/// one line per term that is not worth 0, then the total.
+impl Display for Evaluation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "Evaluation for {}:", self.team)?;
+        for s in self.scores.iter().filter(|s| s.score() != 0) {
+            writeln!(
+                f,
+                "{}: {} vs {}, {:+} each: {:+}",
+                s.term,
+                s.own,
+                s.enemy,
+                s.weight,
+                s.score()
+            )?;
+        }
+        write!(f, "Total: {:+}", self.total())
+    }
+}
@@ -0,0 +1,83 @@
+use super::*;
+
+fn term(evaluation: &Evaluation, term: Term) -> (i32, i32) {
+    let s = evaluation.scores[term.index()];
+    (s.own, s.enemy)
+}
+
+#[test]
+pub(crate) fn test_default_weights_file() {
+    let path = Path::new(env!("CARGO_MANIFEST_DIR")).join("weights/default.txt");
+    assert_eq!(Weights::load(path).unwrap(), Weights::default());
+    let written = Weights::default().to_string();
+    assert_eq!(written.parse::<Weights>().unwrap(), Weights::default());
+}
+
+#[test]
+pub(crate) fn test_invalid_weights() {
+    let err = |s: &str| s.parse::<Weights>().unwrap_err().to_string();
+    assert_eq!(
+        err("# comment\nmobility 5"),
+        "line 2: expected `<term> = <weight>`."
+    );
+    assert_eq!(
+        err("material.stone = 5"),
+        "line 1: I don't recognize that evaluation term."
+    );
+    assert_eq!(
+        err("\n\nstones = many"),
+        "line 3: weights must be whole numbers."
+    );
+
+    let weights = "mobility = -2".parse::<Weights>().unwrap();
+    assert_eq!(weights.get(Term::Mobility), -2);
+    assert_eq!(
+        weights.get(Term::Stones),
+        Weights::default().get(Term::Stones)
+    );
+}
+
+#[test]
+pub(crate) fn test_start_is_even() {
+    let evaluation = Weights::default().evaluate(&Board::new(), Team::Blue);
+    for s in evaluation.scores {
+        assert_eq!(s.own, s.enemy, "{}", s.term);
+    }
+    assert_eq!(evaluation.total(), 0);
+}
+
+#[test]
+pub(crate) fn test_terms() {
+    let board = "
+        S.........
+        ..........
+        ..........
+        ...BB.....
+        ....W.....
+        .....d....
+        ..........
+        ..........
+        ..........
+        .........s
+    "
+    .parse::<Board>()
+    .unwrap();
+    let weights = Weights::default();
+    let blue = weights.evaluate(&board, Team::Blue);
+
+    assert_eq!(term(&blue, Term::Material(PieceKind::Blank)), (2, 0));
+    assert_eq!(term(&blue, Term::Material(PieceKind::Warrior)), (1, 0));
+    assert_eq!(term(&blue, Term::Material(PieceKind::Diplomat)), (0, 1));
+    assert_eq!(term(&blue, Term::Stones), (1, 1));
+    assert_eq!(term(&blue, Term::MergePotential), (2, 0));
+    assert_eq!(term(&blue, Term::VictoryControl), (1, 1));
+    assert_eq!(term(&blue, Term::VictoryDistance), (3, 3));
+    // the red diplomat can convert the warrior on 44.
+    assert_eq!(term(&blue, Term::Conversions), (0, 1));
+
+    let red = weights.evaluate(&board, Team::Red);
+    assert_eq!(red.total(), -blue.total());
+    assert!(blue
+        .to_string()
+        .ends_with(&format!("Total: {:+}", blue.total())));
+}
@@ -0,0 +1,400 @@
+//! A small JSON value, for the protocols that speak JSON.
+//!
+//! Objects keep their keys in order. Numbers are `f64`s, as in JavaScript.
+
+use std::{fmt::Display, str::FromStr};
+
+use thiserror::Error;
+
+#[cfg(test)]
+mod tests;
+
+/// how deep arrays and objects may nest, so that parsing cannot overflow the stack.
+const MAX_DEPTH: usize = 64;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Json {
+    Null,
+    Bool(bool),
+    Number(f64),
+    String(String),
+    Array(Vec<Json>),
+    Object(Vec<(String, Json)>),
+}
+
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+#[error("Invalid JSON at byte {offset}: {message}")]
+pub struct InvalidJson {
+    pub offset: usize,
+    pub message: &'static str,
+}
+
+impl Json {
+    /// an object with these fields, in order.
+    pub fn object<const N: usize>(fields: [(&str, Json); N]) -> Self {
+        Self::Object(
+            fields
+                .into_iter()
+                .map(|(key, value)| (key.to_owned(), value))
+                .collect(),
+        )
+    }
+
+    /// the value of the field, if this is an object that has it.
+    pub fn get(&self, key: &str) -> Option<&Json> {
+        match self {
+            Self::Object(fields) => fields.iter().find(|(k, _)| k == key).map(|(_, v)| v),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            Self::String(s) => Some(s),
+            _ => None,
+        }
+    }
+
+    /// whole numbers from 0 up.
+    pub fn as_u64(&self) -> Option<u64> {
+        match *self {
+            Self::Number(n) if n >= 0.0 && n.fract() == 0.0 && n <= u64::MAX as f64 => {
+                Some(n as u64)
+            }
+            _ => None,
+        }
+    }
+
+    pub fn as_bool(&self) -> Option<bool> {
+        match *self {
+            Self::Bool(b) => Some(b),
+            _ => None,
+        }
+    }
+
+    pub fn as_array(&self) -> Option<&[Json]> {
+        match self {
+            Self::Array(items) => Some(items),
+            _ => None,
+        }
+    }
+
+    pub fn is_null(&self) -> bool {
+        *self == Self::Null
+    }
+}
+
+impl From<bool> for Json {
+    fn from(b: bool) -> Self {
+        Self::Bool(b)
+    }
+}
+
+impl From<&str> for Json {
+    fn from(s: &str) -> Self {
+        Self::String(s.to_owned())
+    }
+}
+
+impl From<String> for Json {
+    fn from(s: String) -> Self {
+        Self::String(s)
+    }
+}
+
+impl From<f64> for Json {
+    fn from(n: f64) -> Self {
+        Self::Number(n)
+    }
+}
+
+impl From<i32> for Json {
+    fn from(n: i32) -> Self {
+        Self::Number(n.into())
+    }
+}
+
+impl From<u32> for Json {
+    fn from(n: u32) -> Self {
+        Self::Number(n.into())
+    }
+}
+
+impl From<u64> for Json {
+    fn from(n: u64) -> Self {
+        Self::Number(n as f64)
+    }
+}
+
+impl From<usize> for Json {
+    fn from(n: usize) -> Self {
+        Self::Number(n as f64)
+    }
+}
+
+impl From<Vec<Json>> for Json {
+    fn from(items: Vec<Json>) -> Self {
+        Self::Array(items)
+    }
+}
+
+impl<T: Into<Json>> From<Option<T>> for Json {
+    fn from(value: Option<T>) -> Self {
+        value.map_or(Self::Null, Into::into)
+    }
+}
+
+fn write_string(f: &mut std::fmt::Formatter<'_>, s: &str) -> std::fmt::Result {
+    f.write_str("\"")?;
+    for c in s.chars() {
+        match c {
+            '"' => f.write_str("\\\"")?,
+            '\\' => f.write_str("\\\\")?,
+            '\n' => f.write_str("\\n")?,
+            '\r' => f.write_str("\\r")?,
+            '\t' => f.write_str("\\t")?,
+            c if c < ' ' => write!(f, "\\u{:04x}", c as u32)?,
+            c => write!(f, "{c}")?,
+        }
+    }
+    f.write_str("\"")
+}
+
+/// on one line, without any spaces.
+impl Display for Json {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Json::Null => f.write_str("null"),
+            Json::Bool(b) => write!(f, "{b}"),
+            // JSON has no infinities.
+            Json::Number(n) if !n.is_finite() => f.write_str("null"),
+            Json::Number(n) => write!(f, "{n}"),
+            Json::String(s) => write_string(f, s),
+            Json::Array(items) => {
+                f.write_str("[")?;
+                for (i, item) in items.iter().enumerate() {
+                    if i > 0 {
+                        f.write_str(",")?;
+                    }
+                    write!(f, "{item}")?;
+                }
+                f.write_str("]")
+            }
+            Json::Object(fields) => {
+                f.write_str("{")?;
+                for (i, (key, value)) in fields.iter().enumerate() {
+                    if i > 0 {
+                        f.write_str(",")?;
+                    }
+                    write_string(f, key)?;
+                    write!(f, ":{value}")?;
+                }
+                f.write_str("}")
+            }
+        }
+    }
+}
+
+struct Parser<'a> {
+    s: &'a [u8],
+    i: usize,
+}
+
+impl Parser<'_> {
+    fn error<T>(&self, message: &'static str) -> Result<T, InvalidJson> {
+        Err(InvalidJson {
+            offset: self.i,
+            message,
+        })
+    }
+
+    fn skip_whitespace(&mut self) {
+        while self.s.get(self.i).is_some_and(|c| b" \t\r\n".contains(c)) {
+            self.i += 1;
+        }
+    }
+
+    fn expect(&mut self, literal: &str) -> Result<(), InvalidJson> {
+        if !self.s[self.i..].starts_with(literal.as_bytes()) {
+            return self.error("Unexpected character.");
+        }
+        self.i += literal.len();
+        Ok(())
+    }
+
+    fn value(&mut self, depth: usize) -> Result<Json, InvalidJson> {
+        if depth > MAX_DEPTH {
+            return self.error("Too deeply nested.");
+        }
+        self.skip_whitespace();
+        let value = match self.s.get(self.i) {
+            None => return self.error("Unexpected end."),
+            Some(b'n') => self.expect("null").map(|_| Json::Null)?,
+            Some(b't') => self.expect("true").map(|_| Json::Bool(true))?,
+            Some(b'f') => self.expect("false").map(|_| Json::Bool(false))?,
+            Some(b'"') => Json::String(self.string()?),
+            Some(b'[') => {
+                self.i += 1;
+                let mut items = vec![];
+                self.skip_whitespace();
+                if self.s.get(self.i) == Some(&b']') {
+                    self.i += 1;
+                } else {
+                    loop {
+                        items.push(self.value(depth + 1)?);
+                        self.skip_whitespace();
+                        match self.s.get(self.i) {
+                            Some(b',') => self.i += 1,
+                            Some(b']') => {
+                                self.i += 1;
+                                break;
+                            }
+                            _ => return self.error("Expected `,` or `]`."),
+                        }
+                    }
+                }
+                Json::Array(items)
+            }
+            Some(b'{') => {
+                self.i += 1;
+                let mut fields = vec![];
+                self.skip_whitespace();
+                if self.s.get(self.i) == Some(&b'}') {
+                    self.i += 1;
+                } else {
+                    loop {
+                        self.skip_whitespace();
+                        if self.s.get(self.i) != Some(&b'"') {
+                            return self.error("Expected a key.");
+                        }
+                        let key = self.string()?;
+                        self.skip_whitespace();
+                        self.expect(":")?;
+                        fields.push((key, self.value(depth + 1)?));
+                        self.skip_whitespace();
+                        match self.s.get(self.i) {
+                            Some(b',') => self.i += 1,
+                            Some(b'}') => {
+                                self.i += 1;
+                                break;
+                            }
+                            _ => return self.error("Expected `,` or `}`."),
+                        }
+                    }
+                }
+                Json::Object(fields)
+            }
+            Some(b'-' | b'0'..=b'9') => self.number()?,
+            Some(_) => return self.error("Unexpected character."),
+        };
+        Ok(value)
+    }
+
+    fn number(&mut self) -> Result<Json, InvalidJson> {
+        let start = self.i;
+        while self
+            .s
+            .get(self.i)
+            .is_some_and(|c| b"+-.eE0123456789".contains(c))
+        {
+            self.i += 1;
+        }
+        // the characters are all ASCII.
+        let text = std::str::from_utf8(&self.s[start..self.i]).unwrap();
+        match text.parse() {
+            Ok(n) => Ok(Json::Number(n)),
+            Err(_) => Err(InvalidJson {
+                offset: start,
+                message: "Invalid number.",
+            }),
+        }
+    }
+
+    fn hex(&mut self) -> Result<u32, InvalidJson> {
+        let digits = self
+            .s
+            .get(self.i..self.i + 4)
+            .and_then(|d| std::str::from_utf8(d).ok())
+            .and_then(|d| u32::from_str_radix(d, 16).ok());
+        match digits {
+            Some(n) => {
+                self.i += 4;
+                Ok(n)
+            }
+            None => self.error("Expected 4 hexadecimal digits."),
+        }
+    }
+
+    fn string(&mut self) -> Result<String, InvalidJson> {
+        // skip the opening quote.
+        self.i += 1;
+        let mut out = vec![];
+        loop {
+            match self.s.get(self.i) {
+                None => return self.error("Unterminated string."),
+                Some(b'"') => {
+                    self.i += 1;
+                    break;
+                }
+                Some(b'\\') => {
+                    self.i += 1;
+                    let c = match self.s.get(self.i) {
+                        Some(b'"') => '"',
+                        Some(b'\\') => '\\',
+                        Some(b'/') => '/',
+                        Some(b'b') => '\u{8}',
+                        Some(b'f') => '\u{c}',
+                        Some(b'n') => '\n',
+                        Some(b'r') => '\r',
+                        Some(b't') => '\t',
+                        Some(b'u') => {
+                            self.i += 1;
+                            let mut code = self.hex()?;
+                            // characters outside the basic plane come as a surrogate pair.
+                            if (0xd800..0xdc00).contains(&code) {
+                                self.expect("\\u")?;
+                                let low = self.hex()?;
+                                if !(0xdc00..0xe000).contains(&low) {
+                                    return self.error("Invalid surrogate pair.");
+                                }
+                                code = 0x10000 + ((code - 0xd800) << 10) + (low - 0xdc00);
+                            }
+                            let Some(c) = char::from_u32(code) else {
+                                return self.error("Invalid character code.");
+                            };
+                            out.extend_from_slice(c.encode_utf8(&mut [0; 4]).as_bytes());
+                            continue;
+                        }
+                        _ => return self.error("Invalid escape."),
+                    };
+                    self.i += 1;
+                    out.extend_from_slice(c.encode_utf8(&mut [0; 4]).as_bytes());
+                }
+                Some(c) if *c < b' ' => return self.error("Control character in string."),
+                Some(&c) => {
+                    out.push(c);
+                    self.i += 1;
+                }
+            }
+        }
+        // the input was a `str`, and only whole characters were added.
+        Ok(String::from_utf8(out).unwrap())
+    }
+}
+
+impl FromStr for Json {
+    type Err = InvalidJson;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut parser = Parser {
+            s: s.as_bytes(),
+            i: 0,
+        };
+        let value = parser.value(0)?;
+        parser.skip_whitespace();
+        if parser.i < s.len() {
+            return parser.error("Unexpected text after the value.");
+        }
+        Ok(value)
+    }
+}
@@ -0,0 +1,59 @@
+use super::*;
+
+#[test]
+pub(crate) fn test_round_trip() {
+    let value = Json::object([
+        ("type", "state".into()),
+        ("count", 3u32.into()),
+        ("ratio", (-1.5).into()),
+        ("done", false.into()),
+        ("none", Json::Null),
+        ("text", "a \"quote\"\\\n\té".into()),
+        (
+            "list",
+            vec![Json::from(1u32), Json::object([]), Json::Array(vec![])].into(),
+        ),
+    ]);
+    let text = value.to_string();
+    assert_eq!(
+        text,
+        r#"{"type":"state","count":3,"ratio":-1.5,"done":false,"none":null,"text":"a \"quote\"\\\n\té","list":[1,{},[]]}"#
+    );
+    assert_eq!(text.parse::<Json>(), Ok(value.clone()));
+
+    assert_eq!(value.get("type").and_then(Json::as_str), Some("state"));
+    assert_eq!(value.get("count").and_then(Json::as_u64), Some(3));
+    assert_eq!(value.get("ratio").and_then(Json::as_u64), None);
+    assert_eq!(value.get("done").and_then(Json::as_bool), Some(false));
+    assert!(value.get("none").is_some_and(Json::is_null));
+    assert_eq!(value.get("missing"), None);
+}
+
+#[test]
+pub(crate) fn test_parse() {
+    assert_eq!(
+        " { \"a\" : [ 1e3 , \"\\u00e9\\ud83d\\ude00\" ] } ".parse::<Json>(),
+        Ok(Json::object([(
+            "a",
+            vec![Json::Number(1000.0), "é😀".into()].into()
+        )]))
+    );
+    for invalid in [
+        "",
+        "{",
+        "[1,]",
+        "{\"a\" 1}",
+        "\"unterminated",
+        "nul",
+        "1 2",
+        "\"\\ud83d\"",
+        "--1",
+    ] {
+        assert!(invalid.parse::<Json>().is_err(), "{invalid}");
+    }
+    let deep = "[".repeat(100) + &"]".repeat(100);
+    assert_eq!(
+        deep.parse::<Json>().map_err(|e| e.message),
+        Err("Too deeply nested.")
+    );
+}
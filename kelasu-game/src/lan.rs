@@ -0,0 +1,518 @@
+//! Kelasu over a local network: a server that hosts lobbies and referees their games,
+//! and a client to talk to it.
+//!
+//! Clients connect over TCP and send JSON objects, one per line, each with a `type`.
+//! The server plays every action on its own [`Game`], so a client cannot make an illegal one.
+//!
+//! Client to server:
+//! - `{"type":"hello","name":"alice"}`: the first message. The server answers `welcome` with
+//!   a `token`. After losing the connection, `{"type":"hello","name":"alice","token":"…"}`
+//!   resumes the session, and the server follows `welcome` with `joined` and `state` if the
+//!   session was in a lobby.
+//! - `{"type":"lobbies"}`: the server answers `lobbies`.
+//! - `{"type":"host","lobby":"den","setup":"wings","team":"red","clock":"5+3"}`: opens a lobby
+//!   and waits in it for an opponent. Only `lobby` is needed: the setup is `standard`, the team
+//!   `blue` and the game untimed by default. Clocks are written as a [`Clock`].
+//! - `{"type":"join","lobby":"den"}`: takes the free seat of a lobby, which starts its game.
+//! - `{"type":"spectate","lobby":"den"}`: watches the game of a lobby.
+//! - `{"type":"action","action":"move 11 to 21"}`: plays an action, in the [`Move`] syntax.
+//! - `{"type":"leave"}`: leaves the lobby. Leaving a game in progress loses it.
+//!
+//! Server to client:
+//! - `{"type":"welcome","name":"alice","token":"…"}`
+//! - `{"type":"lobbies","lobbies":[{"name":"den","blue":"alice","red":null,"spectators":0,"status":"waiting","setup":"kelasu960 9","clock":"5+3"}]}`,
+//!   where the status is `waiting`, `playing` or `finished`.
+//! - `{"type":"joined","lobby":"den","team":"blue"}`, with a `null` team for spectators.
+//! - `{"type":"state",…}` to everyone in a lobby whenever anything in it changes, with:
+//!   - `lobby`, and `blue` and `red`: the players' names, or `null` for a free seat.
+//!   - `connected`: `{"blue":true,"red":false}`.
+//!   - `start`: the [`Position`] the game started from, `actions`: every action since, and
+//!     `position`: the position now. Replaying the actions counts repetitions as the server does.
+//!   - `clock`: `{"blue":<ms>,"red":<ms>}`, the time each team has left, or `null` if the game
+//!     is untimed. The clock of the team to move runs from the moment the message is sent.
+//!   - `result`: `blue`, `red` or `draw`, or `null` while the game goes on, and `termination`:
+//!     `time` or `left` if the game ended outside the rules, or `null`.
+//!   - `spectators`: how many are watching.
+//! - `{"type":"left"}` once the client is out of its lobby.
+//! - `{"type":"error","message":"…"}` when a message could not be handled.
+//!
+//! Unknown fields are ignored. Players who lose their connection keep their seats,
+//! and their clocks keep running. A finished lobby closes once nobody is left in it.
+
+use crate::{
+    board::{Move, Position, Winner},
+    json::{InvalidJson, Json},
+    piece::Team,
+    record::{IllegalAction, Record},
+    setup::Setup,
+    Game,
+};
+use std::{fmt::Display, str::FromStr, time::Duration};
+
+use thiserror::Error;
+
+mod client;
+mod server;
+#[cfg(test)]
+mod tests;
+
+pub use client::Client;
+pub use server::serve;
+
+/// how long each team has for the whole game, and what it gets back at the end of each turn.
+///
+/// written as `<minutes>+<seconds>`, e.g. `5+3`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Clock {
+    pub initial: Duration,
+    pub increment: Duration,
+}
+
+#[derive(Error, Debug)]
+#[error("Clocks are written as <minutes>+<seconds>, e.g. `5+3`.")]
+pub struct InvalidClock;
+
+impl Display for Clock {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}+{}",
+            self.initial.as_secs_f64() / 60.0,
+            self.increment.as_secs_f64()
+        )
+    }
+}
+
+impl FromStr for Clock {
+    type Err = InvalidClock;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (minutes, seconds) = s.trim().split_once('+').ok_or(InvalidClock)?;
+        let duration = |s: &str, unit: f64| {
+            s.trim()
+                .parse::<f64>()
+                .ok()
+                .and_then(|n| Duration::try_from_secs_f64(n * unit).ok())
+                .ok_or(InvalidClock)
+        };
+        Ok(Self {
+            initial: duration(minutes, 60.0)?,
+            increment: duration(seconds, 1.0)?,
+        })
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ClientMessage {
+    /// `token` resumes an earlier session.
+    Hello {
+        name: String,
+        token: Option<String>,
+    },
+    Lobbies,
+    Host {
+        lobby: String,
+        setup: Setup,
+        team: Team,
+        clock: Option<Clock>,
+    },
+    Join {
+        lobby: String,
+    },
+    Spectate {
+        lobby: String,
+    },
+    Action(Move),
+    Leave,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Status {
+    Waiting,
+    Playing,
+    Finished,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LobbyInfo {
+    pub name: String,
+    pub blue: Option<String>,
+    pub red: Option<String>,
+    pub spectators: usize,
+    pub status: Status,
+    pub setup: Setup,
+    pub clock: Option<Clock>,
+}
+
+/// a lobby and its game, as everyone in it sees them.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GameView {
+    pub lobby: String,
+    pub blue: Option<String>,
+    pub red: Option<String>,
+    /// indexed by team.
+    pub connected: [bool; 2],
+    pub start: Position,
+    pub actions: Vec<Move>,
+    pub position: Position,
+    /// the time each team has left, indexed by team.
+    pub clock: Option<[Duration; 2]>,
+    pub result: Option<Winner>,
+    /// why the game ended outside the rules.
+    pub termination: Option<String>,
+    pub spectators: usize,
+}
+
+impl GameView {
+    /// the game, with its history, so that repetitions are counted.
+    pub fn game(&self) -> Result<Game, IllegalAction> {
+        let mut game = Record {
+            start: Some(self.start.clone()),
+            actions: self.actions.clone(),
+            ..Default::default()
+        }
+        .replay()?;
+        // the server may have ended the game outside the rules.
+        game.state = self.position.state.clone();
+        Ok(game)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ServerMessage {
+    Welcome {
+        name: String,
+        token: String,
+    },
+    Lobbies(Vec<LobbyInfo>),
+    /// `None` for spectators.
+    Joined {
+        lobby: String,
+        team: Option<Team>,
+    },
+    State(Box<GameView>),
+    Left,
+    Error(String),
+}
+
+#[derive(Error, Debug)]
+pub enum InvalidMessage {
+    #[error("{0}")]
+    Json(#[from] InvalidJson),
+    #[error("I don't recognize that message.")]
+    Unknown,
+    #[error("The message is missing its {0}.")]
+    MissingField(&'static str),
+    #[error("The {0} of the message is invalid.")]
+    InvalidField(&'static str),
+}
+
+/// the field, which may not be missing or `null`.
+fn field<'a>(json: &'a Json, key: &'static str) -> Result<&'a Json, InvalidMessage> {
+    json.get(key)
+        .filter(|value| !value.is_null())
+        .ok_or(InvalidMessage::MissingField(key))
+}
+
+fn string(json: &Json, key: &'static str) -> Result<String, InvalidMessage> {
+    field(json, key)?
+        .as_str()
+        .map(str::to_owned)
+        .ok_or(InvalidMessage::InvalidField(key))
+}
+
+/// the string field, which may be missing or `null`.
+fn optional_string(json: &Json, key: &'static str) -> Result<Option<String>, InvalidMessage> {
+    match json.get(key) {
+        None | Some(Json::Null) => Ok(None),
+        Some(_) => string(json, key).map(Some),
+    }
+}
+
+/// the string field, parsed, which may be missing or `null`.
+fn optional_parsed<T: FromStr>(
+    json: &Json,
+    key: &'static str,
+) -> Result<Option<T>, InvalidMessage> {
+    optional_string(json, key)?
+        .map(|s| s.parse().map_err(|_| InvalidMessage::InvalidField(key)))
+        .transpose()
+}
+
+fn parsed<T: FromStr>(json: &Json, key: &'static str) -> Result<T, InvalidMessage> {
+    optional_parsed(json, key)?.ok_or(InvalidMessage::MissingField(key))
+}
+
+fn number(json: &Json, key: &'static str) -> Result<u64, InvalidMessage> {
+    field(json, key)?
+        .as_u64()
+        .ok_or(InvalidMessage::InvalidField(key))
+}
+
+fn result(json: &Json) -> Result<Option<Winner>, InvalidMessage> {
+    Ok(match optional_string(json, "result")?.as_deref() {
+        None => None,
+        Some("draw") => Some(Winner(None)),
+        Some(team) => Some(Winner(Some(
+            team.parse()
+                .map_err(|_| InvalidMessage::InvalidField("result"))?,
+        ))),
+    })
+}
+
+impl ClientMessage {
+    pub fn to_json(&self) -> Json {
+        match self {
+            ClientMessage::Hello { name, token } => Json::object([
+                ("type", "hello".into()),
+                ("name", name.as_str().into()),
+                ("token", token.clone().into()),
+            ]),
+            ClientMessage::Lobbies => Json::object([("type", "lobbies".into())]),
+            ClientMessage::Host {
+                lobby,
+                setup,
+                team,
+                clock,
+            } => Json::object([
+                ("type", "host".into()),
+                ("lobby", lobby.as_str().into()),
+                ("setup", setup.to_string().into()),
+                ("team", team.name().into()),
+                ("clock", clock.map(|c| c.to_string()).into()),
+            ]),
+            ClientMessage::Join { lobby } => {
+                Json::object([("type", "join".into()), ("lobby", lobby.as_str().into())])
+            }
+            ClientMessage::Spectate { lobby } => Json::object([
+                ("type", "spectate".into()),
+                ("lobby", lobby.as_str().into()),
+            ]),
+            ClientMessage::Action(action) => Json::object([
+                ("type", "action".into()),
+                ("action", action.to_string().into()),
+            ]),
+            ClientMessage::Leave => Json::object([("type", "leave".into())]),
+        }
+    }
+
+    pub fn from_json(json: &Json) -> Result<Self, InvalidMessage> {
+        Ok(match string(json, "type")?.as_str() {
+            "hello" => Self::Hello {
+                name: string(json, "name")?,
+                token: optional_string(json, "token")?,
+            },
+            "lobbies" => Self::Lobbies,
+            "host" => Self::Host {
+                lobby: string(json, "lobby")?,
+                setup: optional_parsed(json, "setup")?.unwrap_or_default(),
+                team: optional_parsed(json, "team")?.unwrap_or_default(),
+                clock: optional_parsed(json, "clock")?,
+            },
+            "join" => Self::Join {
+                lobby: string(json, "lobby")?,
+            },
+            "spectate" => Self::Spectate {
+                lobby: string(json, "lobby")?,
+            },
+            "action" => Self::Action(parsed(json, "action")?),
+            "leave" => Self::Leave,
+            _ => return Err(InvalidMessage::Unknown),
+        })
+    }
+}
+
+impl LobbyInfo {
+    fn to_json(&self) -> Json {
+        let status = match self.status {
+            Status::Waiting => "waiting",
+            Status::Playing => "playing",
+            Status::Finished => "finished",
+        };
+        Json::object([
+            ("name", self.name.as_str().into()),
+            ("blue", self.blue.clone().into()),
+            ("red", self.red.clone().into()),
+            ("spectators", self.spectators.into()),
+            ("status", status.into()),
+            ("setup", self.setup.to_string().into()),
+            ("clock", self.clock.map(|c| c.to_string()).into()),
+        ])
+    }
+
+    fn from_json(json: &Json) -> Result<Self, InvalidMessage> {
+        Ok(Self {
+            name: string(json, "name")?,
+            blue: optional_string(json, "blue")?,
+            red: optional_string(json, "red")?,
+            spectators: number(json, "spectators")? as usize,
+            status: match string(json, "status")?.as_str() {
+                "waiting" => Status::Waiting,
+                "playing" => Status::Playing,
+                "finished" => Status::Finished,
+                _ => return Err(InvalidMessage::InvalidField("status")),
+            },
+            setup: parsed(json, "setup")?,
+            clock: optional_parsed(json, "clock")?,
+        })
+    }
+}
+
+impl GameView {
+    fn to_json(&self) -> Json {
+        let teams = |[blue, red]: [Json; 2]| Json::object([("blue", blue), ("red", red)]);
+        Json::object([
+            ("type", "state".into()),
+            ("lobby", self.lobby.as_str().into()),
+            ("blue", self.blue.clone().into()),
+            ("red", self.red.clone().into()),
+            ("connected", teams(self.connected.map(Json::from))),
+            ("start", self.start.to_string().into()),
+            (
+                "actions",
+                Json::Array(
+                    self.actions
+                        .iter()
+                        .map(|action| action.to_string().into())
+                        .collect(),
+                ),
+            ),
+            ("position", self.position.to_string().into()),
+            (
+                "clock",
+                self.clock.map_or(Json::Null, |clock| {
+                    teams(clock.map(|t| (t.as_millis() as u64).into()))
+                }),
+            ),
+            ("result", self.result.map(Winner::name).into()),
+            ("termination", self.termination.clone().into()),
+            ("spectators", self.spectators.into()),
+        ])
+    }
+
+    fn from_json(json: &Json) -> Result<Self, InvalidMessage> {
+        let connected = field(json, "connected")?;
+        let connected = [Team::Blue, Team::Red].map(|team| {
+            connected
+                .get(team.name())
+                .and_then(Json::as_bool)
+                .unwrap_or(false)
+        });
+        let clock = match json.get("clock") {
+            None | Some(Json::Null) => None,
+            Some(clock) => Some([
+                Duration::from_millis(number(clock, "blue")?),
+                Duration::from_millis(number(clock, "red")?),
+            ]),
+        };
+        let actions = field(json, "actions")?
+            .as_array()
+            .ok_or(InvalidMessage::InvalidField("actions"))?
+            .iter()
+            .map(|action| {
+                action
+                    .as_str()
+                    .and_then(|action| action.parse().ok())
+                    .ok_or(InvalidMessage::InvalidField("actions"))
+            })
+            .collect::<Result<_, _>>()?;
+        Ok(Self {
+            lobby: string(json, "lobby")?,
+            blue: optional_string(json, "blue")?,
+            red: optional_string(json, "red")?,
+            connected,
+            start: parsed(json, "start")?,
+            actions,
+            position: parsed(json, "position")?,
+            clock,
+            result: result(json)?,
+            termination: optional_string(json, "termination")?,
+            spectators: number(json, "spectators")? as usize,
+        })
+    }
+}
+
+impl ServerMessage {
+    pub fn to_json(&self) -> Json {
+        match self {
+            ServerMessage::Welcome { name, token } => Json::object([
+                ("type", "welcome".into()),
+                ("name", name.as_str().into()),
+                ("token", token.as_str().into()),
+            ]),
+            ServerMessage::Lobbies(lobbies) => Json::object([
+                ("type", "lobbies".into()),
+                (
+                    "lobbies",
+                    Json::Array(lobbies.iter().map(LobbyInfo::to_json).collect()),
+                ),
+            ]),
+            ServerMessage::Joined { lobby, team } => Json::object([
+                ("type", "joined".into()),
+                ("lobby", lobby.as_str().into()),
+                ("team", team.map(Team::name).into()),
+            ]),
+            ServerMessage::State(view) => view.to_json(),
+            ServerMessage::Left => Json::object([("type", "left".into())]),
+            ServerMessage::Error(message) => Json::object([
+                ("type", "error".into()),
+                ("message", message.as_str().into()),
+            ]),
+        }
+    }
+
+    pub fn from_json(json: &Json) -> Result<Self, InvalidMessage> {
+        Ok(match string(json, "type")?.as_str() {
+            "welcome" => Self::Welcome {
+                name: string(json, "name")?,
+                token: string(json, "token")?,
+            },
+            "lobbies" => Self::Lobbies(
+                field(json, "lobbies")?
+                    .as_array()
+                    .ok_or(InvalidMessage::InvalidField("lobbies"))?
+                    .iter()
+                    .map(LobbyInfo::from_json)
+                    .collect::<Result<_, _>>()?,
+            ),
+            "joined" => Self::Joined {
+                lobby: string(json, "lobby")?,
+                team: optional_parsed(json, "team")?,
+            },
+            "state" => Self::State(Box::new(GameView::from_json(json)?)),
+            "left" => Self::Left,
+            "error" => Self::Error(string(json, "message")?),
+            _ => return Err(InvalidMessage::Unknown),
+        })
+    }
+}
+
+/// one line of JSON.
+impl Display for ClientMessage {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.to_json())
+    }
+}
+
+impl FromStr for ClientMessage {
+    type Err = InvalidMessage;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::from_json(&s.parse()?)
+    }
+}
+
+/// one line of JSON.
+impl Display for ServerMessage {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.to_json())
+    }
+}
+
+impl FromStr for ServerMessage {
+    type Err = InvalidMessage;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::from_json(&s.parse()?)
+    }
+}
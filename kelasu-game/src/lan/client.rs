@@ -0,0 +1,58 @@
+use super::{ClientMessage, ServerMessage};
+use std::{
+    io::{self, BufRead, BufReader, Write},
+    net::{Shutdown, TcpStream, ToSocketAddrs},
+    sync::mpsc::{self, Receiver},
+    thread,
+};
+
+/// a connection to a [server](super::serve).
+pub struct Client {
+    stream: TcpStream,
+}
+
+impl Client {
+    /// connects and says hello. every message from the server goes to the channel,
+    /// which disconnects when the connection closes.
+    ///
+    /// pass the token of an earlier [welcome](ServerMessage::Welcome) to resume that session.
+    pub fn connect(
+        address: impl ToSocketAddrs,
+        name: &str,
+        token: Option<String>,
+    ) -> io::Result<(Self, Receiver<ServerMessage>)> {
+        let stream = TcpStream::connect(address)?;
+        let reader = BufReader::new(stream.try_clone()?);
+        let (messages, received) = mpsc::channel();
+        thread::spawn(move || {
+            for line in reader.lines() {
+                let Ok(line) = line else {
+                    break;
+                };
+                // a newer server may say things this client does not understand.
+                let Ok(message) = line.parse() else {
+                    continue;
+                };
+                if messages.send(message).is_err() {
+                    break;
+                }
+            }
+        });
+        let client = Self { stream };
+        client.send(&ClientMessage::Hello {
+            name: name.to_owned(),
+            token,
+        })?;
+        Ok((client, received))
+    }
+
+    pub fn send(&self, message: &ClientMessage) -> io::Result<()> {
+        writeln!(&self.stream, "{message}")
+    }
+}
+
+impl Drop for Client {
+    fn drop(&mut self) {
+        let _ = self.stream.shutdown(Shutdown::Both);
+    }
+}
@@ -0,0 +1,594 @@
+use super::{ClientMessage, Clock, GameView, LobbyInfo, ServerMessage, Status};
+use crate::{
+    board::{GameState, Move, Position, Winner},
+    piece::Team,
+    setup::Setup,
+    Game,
+};
+use std::{
+    collections::{hash_map::RandomState, BTreeMap, HashMap},
+    hash::BuildHasher,
+    io::{BufRead, BufReader, Write},
+    net::{Shutdown, TcpListener, TcpStream},
+    sync::mpsc::{self, Receiver, RecvTimeoutError, Sender},
+    thread,
+    time::{Duration, Instant},
+};
+
+/// how often the clocks are checked for flags.
+const TICK: Duration = Duration::from_millis(50);
+const MAX_NAME_LENGTH: usize = 32;
+/// a client that takes longer than this to read what it was sent is disconnected.
+const WRITE_TIMEOUT: Duration = Duration::from_secs(10);
+
+type ConnId = u64;
+
+/// the hub's end of a connection. lines are written by the connection's own thread,
+/// so that a client that stops reading cannot hold up the hub.
+struct Connection {
+    stream: TcpStream,
+    lines: Sender<String>,
+}
+
+enum Event {
+    Connected(ConnId, Connection),
+    Line(ConnId, String),
+    Closed(ConnId),
+}
+
+/// a player, or a spectator, across connections.
+struct Session {
+    name: String,
+    conn: Option<ConnId>,
+    lobby: Option<String>,
+}
+
+struct Seat {
+    token: String,
+    name: String,
+}
+
+struct Lobby {
+    name: String,
+    setup: Setup,
+    clock: Option<Clock>,
+    /// indexed by team.
+    seats: [Option<Seat>; 2],
+    /// the tokens of the spectators' sessions.
+    spectators: Vec<String>,
+    start: Position,
+    game: Game,
+    actions: Vec<Move>,
+    started: bool,
+    /// the time each team has left.
+    remaining: [Duration; 2],
+    /// when the clock of the team to move was last charged.
+    last_tick: Instant,
+    termination: Option<String>,
+}
+
+impl Lobby {
+    fn status(&self) -> Status {
+        match self.game.state {
+            _ if !self.started => Status::Waiting,
+            GameState::Ongoing { .. } => Status::Playing,
+            GameState::Finished(_) => Status::Finished,
+        }
+    }
+
+    /// the team of the session, if it has a seat.
+    fn team(&self, token: &str) -> Option<Team> {
+        [Team::Blue, Team::Red].into_iter().find(|&team| {
+            self.seats[team as usize]
+                .as_ref()
+                .is_some_and(|s| s.token == token)
+        })
+    }
+
+    fn finish(&mut self, winner: Winner, termination: &str) {
+        self.game.state = GameState::Finished(winner);
+        self.termination = Some(termination.to_owned());
+    }
+
+    /// runs the clock of the team to move up to now. returns whether its time ran out.
+    fn charge(&mut self) -> bool {
+        if self.clock.is_none() || !self.started || !self.game.is_ongoing() {
+            return false;
+        }
+        let now = Instant::now();
+        let team = self.game.turn;
+        let left = &mut self.remaining[team as usize];
+        *left = left.saturating_sub(now - self.last_tick);
+        self.last_tick = now;
+        if left.is_zero() {
+            self.finish(Winner(Some(!team)), "time");
+            return true;
+        }
+        false
+    }
+}
+
+/// everything the server knows. only the thread in [`serve`] touches it.
+struct Hub {
+    connections: HashMap<ConnId, Connection>,
+    /// the session of every connection that said hello.
+    conn_sessions: HashMap<ConnId, String>,
+    /// by token.
+    sessions: HashMap<String, Session>,
+    lobbies: BTreeMap<String, Lobby>,
+    /// secret keys from the operating system's randomness, which session tokens are hashed with.
+    keys: RandomState,
+    /// how many sessions were started, so that no two tokens hash the same input.
+    sessions_started: u64,
+}
+
+impl Hub {
+    fn send(&self, conn: ConnId, message: &ServerMessage) {
+        // a broken connection is noticed by its reader.
+        if let Some(connection) = self.connections.get(&conn) {
+            let _ = connection.lines.send(message.to_string());
+        }
+    }
+
+    fn error(&self, conn: ConnId, message: impl ToString) {
+        self.send(conn, &ServerMessage::Error(message.to_string()));
+    }
+
+    /// whether the session is connected and in the lobby.
+    fn present(&self, token: &str, lobby: &str) -> bool {
+        self.sessions
+            .get(token)
+            .is_some_and(|s| s.conn.is_some() && s.lobby.as_deref() == Some(lobby))
+    }
+
+    fn view(&self, lobby: &Lobby) -> GameView {
+        let name = |team: Team| lobby.seats[team as usize].as_ref().map(|s| s.name.clone());
+        let connected = [Team::Blue, Team::Red].map(|team| {
+            lobby.seats[team as usize]
+                .as_ref()
+                .is_some_and(|s| self.present(&s.token, &lobby.name))
+        });
+        GameView {
+            lobby: lobby.name.clone(),
+            blue: name(Team::Blue),
+            red: name(Team::Red),
+            connected,
+            start: lobby.start.clone(),
+            actions: lobby.actions.clone(),
+            position: lobby.game.position(),
+            clock: lobby.clock.map(|_| lobby.remaining),
+            result: match lobby.game.state {
+                GameState::Finished(winner) => Some(winner),
+                GameState::Ongoing { .. } => None,
+            },
+            termination: lobby.termination.clone(),
+            spectators: lobby
+                .spectators
+                .iter()
+                .filter(|token| self.present(token, &lobby.name))
+                .count(),
+        }
+    }
+
+    /// sends the state of the lobby to everyone in it, or closes it if it is over and empty.
+    fn update(&mut self, name: &str) {
+        let Some(lobby) = self.lobbies.get(name) else {
+            return;
+        };
+        let members = self
+            .sessions
+            .values()
+            .filter(|s| s.lobby.as_deref() == Some(name))
+            .filter_map(|s| s.conn)
+            .collect::<Vec<_>>();
+        if members.is_empty() && lobby.status() == Status::Finished {
+            self.lobbies.remove(name);
+            for session in self.sessions.values_mut() {
+                if session.lobby.as_deref() == Some(name) {
+                    session.lobby = None;
+                }
+            }
+            return;
+        }
+        let message = ServerMessage::State(Box::new(self.view(lobby)));
+        for conn in members {
+            self.send(conn, &message);
+        }
+    }
+
+    /// a token nobody can guess, even knowing when the server started or how many
+    /// sessions it has.
+    fn new_token(&mut self, conn: ConnId) -> String {
+        self.sessions_started += 1;
+        let half = |part: u8| self.keys.hash_one((self.sessions_started, conn, part));
+        format!("{:016x}{:016x}", half(0), half(1))
+    }
+
+    /// forgets the sessions that lost their connection and are not in a lobby,
+    /// since there is nothing left for them to resume.
+    fn forget_idle_sessions(&mut self) {
+        self.sessions
+            .retain(|_, session| session.conn.is_some() || session.lobby.is_some());
+    }
+
+    /// puts the session out of its lobby.
+    fn leave(&mut self, token: &str) {
+        let Some(name) = self.sessions.get_mut(token).and_then(|s| s.lobby.take()) else {
+            return;
+        };
+        let Some(lobby) = self.lobbies.get_mut(&name) else {
+            return;
+        };
+        match lobby.team(token) {
+            // nobody is waiting for a lobby without its host.
+            Some(_) if lobby.status() == Status::Waiting => {
+                self.lobbies.remove(&name);
+                for session in self.sessions.values_mut() {
+                    if session.lobby.as_deref() == Some(name.as_str()) {
+                        session.lobby = None;
+                    }
+                }
+                return;
+            }
+            Some(team) if lobby.status() == Status::Playing => {
+                lobby.charge();
+                if lobby.game.is_ongoing() {
+                    lobby.finish(Winner(Some(!team)), "left");
+                }
+            }
+            Some(_) => {}
+            None => lobby.spectators.retain(|t| t != token),
+        }
+        self.update(&name);
+    }
+
+    fn hello(&mut self, conn: ConnId, name: String, token: Option<String>) {
+        if self.conn_sessions.contains_key(&conn) {
+            return self.error(conn, "You already said hello.");
+        }
+        let token = match token {
+            Some(token) => {
+                let Some(session) = self.sessions.get_mut(&token) else {
+                    return self.error(conn, "That session does not exist any more.");
+                };
+                // the old connection is replaced.
+                if let Some(old) = session.conn.replace(conn) {
+                    self.conn_sessions.remove(&old);
+                    if let Some(connection) = self.connections.remove(&old) {
+                        let _ = connection.stream.shutdown(Shutdown::Both);
+                    }
+                }
+                token
+            }
+            None => {
+                let name = name.trim();
+                if name.is_empty() || name.chars().count() > MAX_NAME_LENGTH {
+                    return self.error(
+                        conn,
+                        format!("Names have 1 to {MAX_NAME_LENGTH} characters."),
+                    );
+                }
+                let token = self.new_token(conn);
+                self.sessions.insert(
+                    token.clone(),
+                    Session {
+                        name: name.to_owned(),
+                        conn: Some(conn),
+                        lobby: None,
+                    },
+                );
+                token
+            }
+        };
+        self.conn_sessions.insert(conn, token.clone());
+
+        let session = &self.sessions[&token];
+        self.send(
+            conn,
+            &ServerMessage::Welcome {
+                name: session.name.clone(),
+                token: token.clone(),
+            },
+        );
+        let Some(lobby) = session.lobby.clone() else {
+            return;
+        };
+        let team = self.lobbies.get(&lobby).and_then(|l| l.team(&token));
+        self.send(
+            conn,
+            &ServerMessage::Joined {
+                lobby: lobby.clone(),
+                team,
+            },
+        );
+        self.update(&lobby);
+    }
+
+    fn handle_message(&mut self, conn: ConnId, message: ClientMessage) {
+        if let ClientMessage::Hello { name, token } = message {
+            return self.hello(conn, name, token);
+        }
+        let Some(token) = self.conn_sessions.get(&conn).cloned() else {
+            return self.error(conn, "Say hello first.");
+        };
+        let in_lobby = self.sessions[&token].lobby.clone();
+        match message {
+            ClientMessage::Hello { .. } => unreachable!(),
+            ClientMessage::Lobbies => {
+                let lobbies = self
+                    .lobbies
+                    .values()
+                    .map(|lobby| {
+                        let view = self.view(lobby);
+                        LobbyInfo {
+                            name: lobby.name.clone(),
+                            blue: view.blue,
+                            red: view.red,
+                            spectators: view.spectators,
+                            status: lobby.status(),
+                            setup: lobby.setup,
+                            clock: lobby.clock,
+                        }
+                    })
+                    .collect();
+                self.send(conn, &ServerMessage::Lobbies(lobbies));
+            }
+            ClientMessage::Host { .. }
+            | ClientMessage::Join { .. }
+            | ClientMessage::Spectate { .. }
+                if in_lobby.is_some() =>
+            {
+                self.error(conn, "You are already in a lobby. Leave it first.")
+            }
+            ClientMessage::Host {
+                lobby: name,
+                setup,
+                team,
+                clock,
+            } => {
+                let name = name.trim().to_owned();
+                if name.is_empty() || name.chars().count() > MAX_NAME_LENGTH {
+                    return self.error(
+                        conn,
+                        format!("Lobby names have 1 to {MAX_NAME_LENGTH} characters."),
+                    );
+                }
+                if self.lobbies.contains_key(&name) {
+                    return self.error(conn, "That lobby already exists.");
+                }
+                let game = setup.game();
+                let mut seats = [None, None];
+                seats[team as usize] = Some(Seat {
+                    token: token.clone(),
+                    name: self.sessions[&token].name.clone(),
+                });
+                let initial = clock.map_or(Duration::ZERO, |c| c.initial);
+                self.lobbies.insert(
+                    name.clone(),
+                    Lobby {
+                        name: name.clone(),
+                        setup,
+                        clock,
+                        seats,
+                        spectators: vec![],
+                        start: game.position(),
+                        game,
+                        actions: vec![],
+                        started: false,
+                        remaining: [initial; 2],
+                        last_tick: Instant::now(),
+                        termination: None,
+                    },
+                );
+                self.sessions.get_mut(&token).unwrap().lobby = Some(name.clone());
+                self.send(
+                    conn,
+                    &ServerMessage::Joined {
+                        lobby: name.clone(),
+                        team: Some(team),
+                    },
+                );
+                self.update(&name);
+            }
+            ClientMessage::Join { lobby: name } => {
+                let player = self.sessions[&token].name.clone();
+                let Some(lobby) = self.lobbies.get_mut(&name) else {
+                    return self.error(conn, "That lobby does not exist.");
+                };
+                let Some(free) = lobby.seats.iter().position(Option::is_none) else {
+                    return self.error(conn, "That lobby is full.");
+                };
+                lobby.seats[free] = Some(Seat {
+                    token: token.clone(),
+                    name: player,
+                });
+                lobby.started = true;
+                lobby.last_tick = Instant::now();
+                let team = [Team::Blue, Team::Red][free];
+                self.sessions.get_mut(&token).unwrap().lobby = Some(name.clone());
+                self.send(
+                    conn,
+                    &ServerMessage::Joined {
+                        lobby: name.clone(),
+                        team: Some(team),
+                    },
+                );
+                self.update(&name);
+            }
+            ClientMessage::Spectate { lobby: name } => {
+                let Some(lobby) = self.lobbies.get_mut(&name) else {
+                    return self.error(conn, "That lobby does not exist.");
+                };
+                lobby.spectators.push(token.clone());
+                self.sessions.get_mut(&token).unwrap().lobby = Some(name.clone());
+                self.send(
+                    conn,
+                    &ServerMessage::Joined {
+                        lobby: name.clone(),
+                        team: None,
+                    },
+                );
+                self.update(&name);
+            }
+            ClientMessage::Action(action) => {
+                let Some(name) = in_lobby else {
+                    return self.error(conn, "You are not in a lobby.");
+                };
+                let lobby = self.lobbies.get_mut(&name).unwrap();
+                let Some(team) = lobby.team(&token) else {
+                    return self.error(conn, "Spectators cannot play.");
+                };
+                if !lobby.started {
+                    return self.error(conn, "The game has not started yet.");
+                }
+                if lobby.charge() {
+                    self.update(&name);
+                    return self.error(conn, "Your time ran out.");
+                }
+                if lobby.game.is_ongoing() && team != lobby.game.turn {
+                    return self.error(conn, "It is not your turn.");
+                }
+                match lobby.game.verify_action(action.clone()) {
+                    Ok(verified) => {
+                        lobby.game.make_move(verified);
+                        lobby.actions.push(action);
+                        if let Some(clock) = lobby.clock {
+                            if lobby.game.turn != team {
+                                lobby.remaining[team as usize] += clock.increment;
+                            }
+                        }
+                        self.update(&name);
+                    }
+                    Err(e) => self.error(conn, e),
+                }
+            }
+            ClientMessage::Leave => {
+                if in_lobby.is_none() {
+                    return self.error(conn, "You are not in a lobby.");
+                }
+                self.leave(&token);
+                self.send(conn, &ServerMessage::Left);
+            }
+        }
+    }
+
+    fn handle(&mut self, event: Event) {
+        match event {
+            Event::Connected(conn, connection) => {
+                self.connections.insert(conn, connection);
+            }
+            Event::Line(conn, line) => match line.parse() {
+                Ok(message) => self.handle_message(conn, message),
+                Err(e) => self.error(conn, e),
+            },
+            Event::Closed(conn) => {
+                self.connections.remove(&conn);
+                let Some(token) = self.conn_sessions.remove(&conn) else {
+                    return;
+                };
+                let session = self.sessions.get_mut(&token).unwrap();
+                session.conn = None;
+                let Some(name) = session.lobby.clone() else {
+                    return;
+                };
+                // a game waits for its players to come back, but a lobby does not wait for its host.
+                if self.lobbies.get(&name).map(Lobby::status) == Some(Status::Waiting) {
+                    self.leave(&token);
+                } else {
+                    self.update(&name);
+                }
+            }
+        }
+        self.forget_idle_sessions();
+    }
+
+    /// ends the games whose clocks ran out.
+    fn tick(&mut self) {
+        let flagged = self
+            .lobbies
+            .values_mut()
+            .filter_map(|lobby| lobby.charge().then(|| lobby.name.clone()))
+            .collect::<Vec<_>>();
+        if flagged.is_empty() {
+            return;
+        }
+        for name in flagged {
+            self.update(&name);
+        }
+        // a game that ended with nobody connected closes its lobby.
+        self.forget_idle_sessions();
+    }
+}
+
+fn read(conn: ConnId, stream: TcpStream, events: Sender<Event>) {
+    for line in BufReader::new(stream).lines() {
+        let Ok(line) = line else {
+            break;
+        };
+        if line.trim().is_empty() {
+            continue;
+        }
+        if events.send(Event::Line(conn, line)).is_err() {
+            return;
+        }
+    }
+    let _ = events.send(Event::Closed(conn));
+}
+
+/// writes lines until the hub drops the connection, or the client stops reading.
+fn write(mut stream: TcpStream, lines: Receiver<String>) {
+    for line in lines {
+        if writeln!(stream, "{line}").is_err() {
+            // the reader notices, and tells the hub.
+            let _ = stream.shutdown(Shutdown::Both);
+            return;
+        }
+    }
+}
+
+/// hosts lobbies for the clients that connect to `listener`. never returns.
+pub fn serve(listener: TcpListener) {
+    let (events, received) = mpsc::channel();
+    thread::spawn(move || {
+        let mut next = 0;
+        for stream in listener.incoming() {
+            // failed connections are the client's problem.
+            let Ok(stream) = stream else {
+                continue;
+            };
+            let (Ok(writer), Ok(hub_end)) = (stream.try_clone(), stream.try_clone()) else {
+                continue;
+            };
+            if writer.set_write_timeout(Some(WRITE_TIMEOUT)).is_err() {
+                continue;
+            }
+            next += 1;
+            let (lines, to_write) = mpsc::channel();
+            thread::spawn(move || write(writer, to_write));
+            let connection = Connection {
+                stream: hub_end,
+                lines,
+            };
+            let _ = events.send(Event::Connected(next, connection));
+            let events = events.clone();
+            thread::spawn(move || read(next, stream, events));
+        }
+    });
+
+    let mut hub = Hub {
+        connections: HashMap::new(),
+        conn_sessions: HashMap::new(),
+        sessions: HashMap::new(),
+        lobbies: BTreeMap::new(),
+        keys: RandomState::new(),
+        sessions_started: 0,
+    };
+    loop {
+        match received.recv_timeout(TICK) {
+            Ok(event) => hub.handle(event),
+            Err(RecvTimeoutError::Timeout) => {}
+            Err(RecvTimeoutError::Disconnected) => unreachable!("the listener never stops"),
+        }
+        hub.tick();
+    }
+}
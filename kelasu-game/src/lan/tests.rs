@@ -0,0 +1,309 @@
+use super::*;
+use std::{net::TcpListener, sync::mpsc::Receiver, thread, time::Duration};
+
+#[test]
+pub(crate) fn test_message_round_trip() {
+    let clock: Clock = "5+3".parse().unwrap();
+    assert_eq!(clock.initial, Duration::from_secs(300));
+    assert_eq!(clock.increment, Duration::from_secs(3));
+    assert_eq!(clock.to_string(), "5+3");
+    assert!("5".parse::<Clock>().is_err());
+
+    let client = [
+        ClientMessage::Hello {
+            name: "Alice \"the Bold\"".to_owned(),
+            token: Some("0123".to_owned()),
+        },
+        ClientMessage::Lobbies,
+        ClientMessage::Host {
+            lobby: "den".to_owned(),
+            setup: Setup::Kelasu960(9),
+            team: Team::Red,
+            clock: Some(clock),
+        },
+        ClientMessage::Join {
+            lobby: "den".to_owned(),
+        },
+        ClientMessage::Action("move 11 to 21".parse().unwrap()),
+        ClientMessage::Leave,
+    ];
+    for message in client {
+        let line = message.to_string();
+        assert!(!line.contains('\n'));
+        assert_eq!(line.parse::<ClientMessage>().unwrap(), message);
+    }
+    // everything but the lobby has a default.
+    assert_eq!(
+        r#"{"type":"host","lobby":"den"}"#.parse::<ClientMessage>().unwrap(),
+        ClientMessage::Host {
+            lobby: "den".to_owned(),
+            setup: Setup::Standard,
+            team: Team::Blue,
+            clock: None,
+        }
+    );
+    assert!(matches!(
+        r#"{"type":"join"}"#.parse::<ClientMessage>(),
+        Err(InvalidMessage::MissingField("lobby"))
+    ));
+    assert!(matches!(
+        r#"{"type":"dance"}"#.parse::<ClientMessage>(),
+        Err(InvalidMessage::Unknown)
+    ));
+
+    let mut game = Game::new();
+    let start = game.position();
+    let action: Move = "move 11 to 21".parse().unwrap();
+    game.make_move(game.verify_action(action.clone()).unwrap());
+    let view = GameView {
+        lobby: "den".to_owned(),
+        blue: Some("alice".to_owned()),
+        red: None,
+        connected: [true, false],
+        start,
+        actions: vec![action],
+        position: game.position(),
+        clock: Some([Duration::from_millis(1500), Duration::from_secs(60)]),
+        result: Some(Winner(None)),
+        termination: Some("left".to_owned()),
+        spectators: 2,
+    };
+    let server = [
+        ServerMessage::Welcome {
+            name: "alice".to_owned(),
+            token: "0123".to_owned(),
+        },
+        ServerMessage::Lobbies(vec![LobbyInfo {
+            name: "den".to_owned(),
+            blue: None,
+            red: Some("bob".to_owned()),
+            spectators: 1,
+            status: Status::Waiting,
+            setup: Setup::Kelasu960(9),
+            clock: None,
+        }]),
+        ServerMessage::Joined {
+            lobby: "den".to_owned(),
+            team: None,
+        },
+        ServerMessage::State(Box::new(view.clone())),
+        ServerMessage::Left,
+        ServerMessage::Error("No.".to_owned()),
+    ];
+    for message in server {
+        assert_eq!(
+            message.to_string().parse::<ServerMessage>().unwrap(),
+            message
+        );
+    }
+    assert_eq!(view.game().unwrap().position(), game.position());
+}
+
+fn next(messages: &Receiver<ServerMessage>) -> ServerMessage {
+    messages
+        .recv_timeout(Duration::from_secs(10))
+        .expect("the server did not answer")
+}
+
+/// skips to the next state.
+fn state(messages: &Receiver<ServerMessage>) -> GameView {
+    loop {
+        if let ServerMessage::State(view) = next(messages) {
+            return *view;
+        }
+    }
+}
+
+fn error(messages: &Receiver<ServerMessage>) -> String {
+    loop {
+        if let ServerMessage::Error(message) = next(messages) {
+            return message;
+        }
+    }
+}
+
+fn start_server() -> std::net::SocketAddr {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let address = listener.local_addr().unwrap();
+    thread::spawn(move || serve(listener));
+    address
+}
+
+fn connect(address: std::net::SocketAddr, name: &str) -> (Client, Receiver<ServerMessage>, String) {
+    let (client, messages) = Client::connect(address, name, None).unwrap();
+    let ServerMessage::Welcome { token, .. } = next(&messages) else {
+        panic!("expected a welcome");
+    };
+    (client, messages, token)
+}
+
+fn first_action(view: &GameView) -> Move {
+    let game = view.game().unwrap();
+    game.legal_moves()[0].action().clone()
+}
+
+#[test]
+pub(crate) fn test_server() {
+    let address = start_server();
+    let (alice, alice_messages, alice_token) = connect(address, "alice");
+    let (bob, bob_messages, _) = connect(address, "bob");
+
+    alice
+        .send(&ClientMessage::Host {
+            lobby: "den".to_owned(),
+            setup: Setup::Standard,
+            team: Team::Blue,
+            clock: None,
+        })
+        .unwrap();
+    assert_eq!(
+        next(&alice_messages),
+        ServerMessage::Joined {
+            lobby: "den".to_owned(),
+            team: Some(Team::Blue)
+        }
+    );
+    assert_eq!(state(&alice_messages).red, None);
+
+    bob.send(&ClientMessage::Lobbies).unwrap();
+    let ServerMessage::Lobbies(lobbies) = next(&bob_messages) else {
+        panic!("expected the lobbies");
+    };
+    assert_eq!(lobbies.len(), 1);
+    assert_eq!(lobbies[0].blue.as_deref(), Some("alice"));
+    assert_eq!(lobbies[0].status, Status::Waiting);
+
+    bob.send(&ClientMessage::Join {
+        lobby: "den".to_owned(),
+    })
+    .unwrap();
+    assert_eq!(
+        next(&bob_messages),
+        ServerMessage::Joined {
+            lobby: "den".to_owned(),
+            team: Some(Team::Red)
+        }
+    );
+    let view = state(&alice_messages);
+    assert_eq!(view.red.as_deref(), Some("bob"));
+    assert_eq!(view.connected, [true, true]);
+    state(&bob_messages);
+
+    let (carol, carol_messages, _) = connect(address, "carol");
+    carol
+        .send(&ClientMessage::Spectate {
+            lobby: "den".to_owned(),
+        })
+        .unwrap();
+    assert_eq!(
+        next(&carol_messages),
+        ServerMessage::Joined {
+            lobby: "den".to_owned(),
+            team: None
+        }
+    );
+    assert_eq!(state(&carol_messages).spectators, 1);
+    state(&alice_messages);
+    state(&bob_messages);
+
+    // only the team to move may play, and only legal actions.
+    let action = first_action(&view);
+    bob.send(&ClientMessage::Action(action.clone())).unwrap();
+    assert_eq!(error(&bob_messages), "It is not your turn.");
+    alice
+        .send(&ClientMessage::Action("move 00 to 99".parse().unwrap()))
+        .unwrap();
+    error(&alice_messages);
+    carol.send(&ClientMessage::Action(action.clone())).unwrap();
+    assert_eq!(error(&carol_messages), "Spectators cannot play.");
+
+    alice.send(&ClientMessage::Action(action.clone())).unwrap();
+    let view = state(&carol_messages);
+    assert_eq!(view.actions, vec![action.clone()]);
+    state(&bob_messages);
+    state(&alice_messages);
+
+    // alice loses the connection, and comes back.
+    drop(alice);
+    assert_eq!(state(&bob_messages).connected, [false, true]);
+    let (_alice, alice_messages) = Client::connect(address, "", Some(alice_token)).unwrap();
+    assert!(matches!(
+        next(&alice_messages),
+        ServerMessage::Welcome { name, .. } if name == "alice"
+    ));
+    assert_eq!(
+        next(&alice_messages),
+        ServerMessage::Joined {
+            lobby: "den".to_owned(),
+            team: Some(Team::Blue)
+        }
+    );
+    let view = state(&alice_messages);
+    assert_eq!(view.connected, [true, true]);
+    assert_eq!(view.actions, vec![action]);
+    assert_eq!(view.game().unwrap().position(), view.position);
+
+    // leaving a game loses it.
+    bob.send(&ClientMessage::Leave).unwrap();
+    while next(&bob_messages) != ServerMessage::Left {}
+    let view = state(&alice_messages);
+    assert_eq!(view.result, Some(Winner(Some(Team::Blue))));
+    assert_eq!(view.termination.as_deref(), Some("left"));
+}
+
+#[test]
+pub(crate) fn test_clock() {
+    let address = start_server();
+    let (alice, alice_messages, _) = connect(address, "alice");
+    let (bob, bob_messages, _) = connect(address, "bob");
+    alice
+        .send(&ClientMessage::Host {
+            lobby: "blitz".to_owned(),
+            setup: Setup::Standard,
+            team: Team::Red,
+            clock: Some("0.002+0".parse().unwrap()),
+        })
+        .unwrap();
+    state(&alice_messages);
+    bob.send(&ClientMessage::Join {
+        lobby: "blitz".to_owned(),
+    })
+    .unwrap();
+    let view = state(&bob_messages);
+    assert_eq!(view.blue.as_deref(), Some("bob"));
+    assert!(view.clock.is_some());
+
+    // blue is to move, and has only a moment to do it.
+    let view = state(&bob_messages);
+    assert_eq!(view.result, Some(Winner(Some(Team::Red))));
+    assert_eq!(view.termination.as_deref(), Some("time"));
+    assert_eq!(view.clock.unwrap()[Team::Blue as usize], Duration::ZERO);
+    let view = loop {
+        let view = state(&alice_messages);
+        if view.result.is_some() {
+            break view;
+        }
+    };
+    assert_eq!(view.termination.as_deref(), Some("time"));
+}
+
+#[test]
+pub(crate) fn test_idle_session() {
+    let address = start_server();
+    let (dave, _, dave_token) = connect(address, "dave");
+
+    // dave is in no lobby, so once the connection is gone there is nothing to resume.
+    drop(dave);
+    loop {
+        let (_dave, dave_messages) =
+            Client::connect(address, "", Some(dave_token.clone())).unwrap();
+        match next(&dave_messages) {
+            ServerMessage::Error(message) => {
+                assert_eq!(message, "That session does not exist any more.");
+                break;
+            }
+            // the server has not seen the first connection close yet.
+            _ => thread::sleep(Duration::from_millis(10)),
+        }
+    }
+}
@@ -1,5 +1,21 @@
+pub mod analysis;
 pub mod board;
+pub mod book;
+pub mod eval;
+pub mod json;
+pub mod lan;
+pub mod perft;
 pub mod piece;
+pub mod protocol;
+pub mod puzzle;
+pub mod record;
+pub mod render;
+pub mod replay;
+pub mod scenario;
+pub mod search;
+pub mod setup;
+pub mod solver;
+pub mod tournament;
 pub mod util;
 
 pub use board::Game;
@@ -0,0 +1,81 @@
+//! Counts every way a game can go on from a position, to catch accidental changes to the rules.
+//!
+//! Only moves and merges are played. Resigning and draws are left out.
+//! Sequences that end the game before reaching the full depth are not counted.
+
+use crate::{
+    board::{Move, Position},
+    Game,
+};
+use std::collections::HashSet;
+
+#[cfg(test)]
+mod tests;
+
+/// what the depth of a count is measured in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Unit {
+    /// a single move or merge.
+    Action,
+    /// every action until the turn passes to the other team, or the game ends.
+    Turn,
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct Count {
+    /// the number of distinct sequences of actions.
+    pub sequences: u64,
+    /// the number of distinct positions those sequences end in.
+    pub positions: usize,
+}
+
+fn walk(game: &Game, depth: u32, unit: Unit, leaves: &mut HashSet<Position>) -> u64 {
+    if depth == 0 {
+        leaves.insert(game.position());
+        return 1;
+    }
+    let mut sequences = 0;
+    for action in game.legal_actions() {
+        let mut next = game.clone();
+        next.make_move(action);
+        let turn_over = next.turn != game.turn || !next.is_ongoing();
+        let depth = match unit {
+            Unit::Turn if !turn_over => depth,
+            _ => depth - 1,
+        };
+        sequences += walk(&next, depth, unit, leaves);
+    }
+    sequences
+}
+
+pub fn perft(game: &Game, depth: u32, unit: Unit) -> Count {
+    let mut leaves = HashSet::new();
+    let sequences = walk(game, depth, unit, &mut leaves);
+    Count {
+        sequences,
+        positions: leaves.len(),
+    }
+}
+
+/// [`perft`], broken down by the first action played.
+///
+/// the positions of each action are counted separately, so they may add up to more than the total.
+pub fn divide(game: &Game, depth: u32, unit: Unit) -> Vec<(Move, Count)> {
+    if depth == 0 {
+        return vec![];
+    }
+    game.legal_actions()
+        .into_iter()
+        .map(|action| {
+            let mut next = game.clone();
+            let first = action.action().clone();
+            next.make_move(action);
+            let turn_over = next.turn != game.turn || !next.is_ongoing();
+            let depth = match unit {
+                Unit::Turn if !turn_over => depth,
+                _ => depth - 1,
+            };
+            (first, perft(&next, depth, unit))
+        })
+        .collect()
+}
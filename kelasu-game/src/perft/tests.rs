@@ -0,0 +1,49 @@
+use super::*;
+
+fn counts(game: &Game, unit: Unit, max_depth: u32) -> Vec<(u64, usize)> {
+    (1..=max_depth)
+        .map(|depth| perft(game, depth, unit))
+        .map(|c| (c.sequences, c.positions))
+        .collect()
+}
+
+#[test]
+pub(crate) fn test_perft_start() {
+    let game = Game::new();
+    assert_eq!(
+        counts(&game, Unit::Action, 4),
+        [(6, 6), (48, 33), (406, 138), (3580, 409)]
+    );
+    // blue's first turn is exactly its 4 actions
+    assert_eq!(counts(&game, Unit::Turn, 1), [(3580, 409)]);
+}
+
+#[test]
+pub(crate) fn test_perft_merges() {
+    let game = Game::from(
+        "\
+            ........../BBBBBBBBBB/S.S....S.S/...BB...../...BB...../\
+            ........../.....bb.../s.s....s.s/bbbbbbbbbb/.......... \
+            blue 4 - ongoing 0"
+            .parse::<Position>()
+            .unwrap(),
+    );
+    assert_eq!(
+        counts(&game, Unit::Action, 3),
+        [(28, 28), (478, 332), (6958, 2585)]
+    );
+}
+
+#[test]
+pub(crate) fn test_divide() {
+    let game = Game::new();
+    let divided = divide(&game, 3, Unit::Action);
+    assert_eq!(divided.len(), 6);
+    assert_eq!(
+        divided.iter().map(|(_, c)| c.sequences).sum::<u64>(),
+        perft(&game, 3, Unit::Action).sequences
+    );
+    assert!(divided
+        .iter()
+        .any(|(action, _)| action.to_string() == "move 11 to 21"));
+}
@@ -1,4 +1,4 @@
-use std::{ops::Not, str::FromStr};
+use std::{fmt::Display, ops::Not, str::FromStr};
 
 use thiserror::Error;
 
@@ -14,6 +14,42 @@ pub enum Team {
     Red,
 }
 
+#[derive(Error, Debug)]
+#[error("Teams are either `blue` or `red`.")]
+pub struct UnknownTeam;
+
+impl Team {
+    /// `blue` or `red`, as teams are written in positions, records and messages.
+    pub fn name(self) -> &'static str {
+        match self {
+            Team::Blue => "blue",
+            Team::Red => "red",
+        }
+    }
+}
+
+/// `Blue` or `Red`, as teams are written in sentences. data uses [`Team::name`].
+impl Display for Team {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Team::Blue => "Blue",
+            Team::Red => "Red",
+        })
+    }
+}
+
+impl FromStr for Team {
+    type Err = UnknownTeam;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(match s.to_ascii_lowercase().as_str() {
+            "blue" => Self::Blue,
+            "red" => Self::Red,
+            _ => return Err(UnknownTeam),
+        })
+    }
+}
+
 impl Not for Team {
     type Output = Self;
 
@@ -37,6 +73,8 @@ pub enum MoveKind {
 
 #[derive(Error, Debug)]
 pub enum InvalidPieceMove {
+    #[error("There is no piece there to move.")]
+    NoPiece,
     #[error("Moves must be either orthogonal or diagonal.")]
     NonCompassMove,
     #[error("The piece can't move that far in that direction.")]
@@ -168,6 +206,7 @@ impl Piece {
         let mut moves = self.kind.moves();
         if self.team == Team::Red {
             moves.swap(0, 4);
+            moves.swap(1, 3);
         }
         moves
     }
@@ -0,0 +1,346 @@
+//! The Kelasu Engine Protocol, a line-based protocol in the spirit of UCI.
+//!
+//! A client (a GUI, the bot, a tournament runner...) starts an engine process
+//! and talks to it over stdin and stdout, one message per line.
+//!
+//! Client to engine:
+//! - `kep`: the first message. The engine answers with its `id`s and `option`s, then `kepok`.
+//! - `isready`: the engine answers `readyok`, even while searching.
+//! - `setoption name <name> value <value>`
+//! - `newgame`: forget everything about the previous game.
+//! - `position startpos [moves <action>; <action>; ...]` or
+//!   `position pos <position> [moves ...]`, where the position is in the [`Position`] format.
+//!   The actions are those played since the position, so that repetitions can be counted.
+//! - `go [depth <actions>] [movetime <ms>]`, or `go infinite` to search until `stop`.
+//! - `stop`: finish the search as soon as possible.
+//! - `quit`
+//!
+//! Engine to client:
+//! - `id name <name>` and `id author <author>`
+//! - `option name <name> type spin default <n> min <n> max <n>` or
+//!   `option name <name> type string default <text>`. Option names are single words.
+//! - `kepok` and `readyok`
+//! - `info depth <actions> score <score> nodes <n> time <ms> pv <action>; <action>; ...`,
+//!   after each depth is searched. Scores are [`Score`]s, e.g. `120` or `win 3`.
+//! - `info string <text>`, for anything a human should read, such as errors.
+//! - `bestmove <action>` once the search is over, or `bestmove none` if there is no legal action.
+//!
+//! Actions are written in the [`Move`] syntax, e.g. `move 11 to 21` or `merge warrior at 20 with 21`.
+//! Unknown messages should be ignored.
+
+use crate::{
+    board::{InvalidMoveCommand, InvalidPosition, Move, Position},
+    search::{Info, Limits, Score},
+};
+use std::{fmt::Display, str::FromStr, time::Duration};
+
+use thiserror::Error;
+
+mod client;
+mod server;
+#[cfg(test)]
+mod tests;
+
+pub use client::{Engine, EngineError, Stopper};
+pub use server::serve;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Command {
+    Kep,
+    IsReady,
+    SetOption {
+        name: String,
+        value: String,
+    },
+    NewGame,
+    /// `None` is the starting position.
+    Position {
+        position: Option<Box<Position>>,
+        actions: Vec<Move>,
+    },
+    Go(Limits),
+    Stop,
+    Quit,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum OptionKind {
+    Spin { default: i32, min: i32, max: i32 },
+    String { default: String },
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EngineOption {
+    pub name: String,
+    pub kind: OptionKind,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Response {
+    IdName(String),
+    IdAuthor(String),
+    Option(EngineOption),
+    KepOk,
+    ReadyOk,
+    Info(Info),
+    Message(String),
+    BestMove(Option<Move>),
+}
+
+#[derive(Error, Debug)]
+pub enum InvalidMessage {
+    #[error("I don't recognize that message.")]
+    Unknown,
+    #[error("Expected another parameter: {0}")]
+    MissingParameter(&'static str),
+    #[error("That parameter is invalid. {0}")]
+    InvalidParameter(&'static str),
+    #[error("That position is invalid. {0}")]
+    InvalidPosition(#[from] InvalidPosition),
+    #[error("{0}")]
+    InvalidAction(#[from] InvalidMoveCommand),
+}
+
+fn write_actions(f: &mut std::fmt::Formatter<'_>, actions: &[Move]) -> std::fmt::Result {
+    for (i, action) in actions.iter().enumerate() {
+        if i > 0 {
+            write!(f, ";")?;
+        }
+        write!(f, " {action}")?;
+    }
+    Ok(())
+}
+
+fn parse_actions(s: &str) -> Result<Vec<Move>, InvalidMessage> {
+    if s.trim().is_empty() {
+        return Ok(vec![]);
+    }
+    Ok(s.split(';').map(str::parse).collect::<Result<_, _>>()?)
+}
+
+fn parse_number<T: FromStr>(word: Option<&str>, name: &'static str) -> Result<T, InvalidMessage> {
+    word.ok_or(InvalidMessage::MissingParameter(name))?
+        .parse()
+        .map_err(|_| InvalidMessage::InvalidParameter("Expected a number."))
+}
+
+impl Display for Command {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Command::Kep => write!(f, "kep"),
+            Command::IsReady => write!(f, "isready"),
+            Command::SetOption { name, value } => write!(f, "setoption name {name} value {value}"),
+            Command::NewGame => write!(f, "newgame"),
+            Command::Position { position, actions } => {
+                match position {
+                    None => write!(f, "position startpos")?,
+                    Some(position) => write!(f, "position pos {position}")?,
+                }
+                if !actions.is_empty() {
+                    write!(f, " moves")?;
+                    write_actions(f, actions)?;
+                }
+                Ok(())
+            }
+            Command::Go(Limits {
+                depth: None,
+                time: None,
+            }) => write!(f, "go infinite"),
+            Command::Go(limits) => {
+                write!(f, "go")?;
+                if let Some(depth) = limits.depth {
+                    write!(f, " depth {depth}")?;
+                }
+                if let Some(time) = limits.time {
+                    write!(f, " movetime {}", time.as_millis())?;
+                }
+                Ok(())
+            }
+            Command::Stop => write!(f, "stop"),
+            Command::Quit => write!(f, "quit"),
+        }
+    }
+}
+
+impl FromStr for Command {
+    type Err = InvalidMessage;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        let (keyword, rest) = s.split_once(' ').unwrap_or((s, ""));
+        let rest = rest.trim();
+        Ok(match keyword {
+            "kep" => Self::Kep,
+            "isready" => Self::IsReady,
+            "newgame" => Self::NewGame,
+            "stop" => Self::Stop,
+            "quit" => Self::Quit,
+            "setoption" => {
+                let rest = rest
+                    .strip_prefix("name ")
+                    .ok_or(InvalidMessage::MissingParameter("name"))?;
+                let (name, value) = rest
+                    .split_once(" value")
+                    .ok_or(InvalidMessage::MissingParameter("value"))?;
+                Self::SetOption {
+                    name: name.trim().to_owned(),
+                    value: value.trim().to_owned(),
+                }
+            }
+            "position" => {
+                let (position, actions) = match rest.split_once("moves") {
+                    Some((position, actions)) => (position.trim(), parse_actions(actions)?),
+                    None => (rest, vec![]),
+                };
+                let position = match position.split_once(' ') {
+                    _ if position == "startpos" => None,
+                    Some(("pos", position)) => Some(Box::new(position.parse()?)),
+                    _ => {
+                        return Err(InvalidMessage::InvalidParameter(
+                            "Positions are either `startpos` or `pos <position>`.",
+                        ))
+                    }
+                };
+                Self::Position { position, actions }
+            }
+            "go" => {
+                let mut limits = Limits::default();
+                let mut words = rest.split_whitespace();
+                while let Some(word) = words.next() {
+                    match word {
+                        "infinite" => {}
+                        "depth" => limits.depth = Some(parse_number(words.next(), "depth")?),
+                        "movetime" => {
+                            let ms = parse_number(words.next(), "movetime")?;
+                            limits.time = Some(Duration::from_millis(ms));
+                        }
+                        _ => {
+                            return Err(InvalidMessage::InvalidParameter(
+                                "Searches are limited by `depth`, `movetime` or `infinite`.",
+                            ))
+                        }
+                    }
+                }
+                Self::Go(limits)
+            }
+            _ => return Err(InvalidMessage::Unknown),
+        })
+    }
+}
+
+impl Display for Response {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Response::IdName(name) => write!(f, "id name {name}"),
+            Response::IdAuthor(author) => write!(f, "id author {author}"),
+            Response::Option(EngineOption { name, kind }) => match kind {
+                OptionKind::Spin { default, min, max } => write!(
+                    f,
+                    "option name {name} type spin default {default} min {min} max {max}"
+                ),
+                OptionKind::String { default } => {
+                    write!(f, "option name {name} type string default {default}")
+                }
+            },
+            Response::KepOk => write!(f, "kepok"),
+            Response::ReadyOk => write!(f, "readyok"),
+            Response::Info(info) => {
+                write!(
+                    f,
+                    "info depth {} score {} nodes {} time {} pv",
+                    info.depth,
+                    info.score,
+                    info.nodes,
+                    info.time.as_millis()
+                )?;
+                write_actions(f, &info.pv)
+            }
+            Response::Message(text) => write!(f, "info string {text}"),
+            Response::BestMove(None) => write!(f, "bestmove none"),
+            Response::BestMove(Some(action)) => write!(f, "bestmove {action}"),
+        }
+    }
+}
+
+fn parse_info(s: &str) -> Result<Info, InvalidMessage> {
+    let (fields, pv) = s.split_once("pv").unwrap_or((s, ""));
+    let mut info = Info {
+        depth: 0,
+        score: Score::Value(0),
+        nodes: 0,
+        time: Duration::ZERO,
+        pv: parse_actions(pv)?,
+    };
+    let mut words = fields.split_whitespace();
+    while let Some(word) = words.next() {
+        match word {
+            "depth" => info.depth = parse_number(words.next(), "depth")?,
+            "nodes" => info.nodes = parse_number(words.next(), "nodes")?,
+            "time" => info.time = Duration::from_millis(parse_number(words.next(), "time")?),
+            "score" => {
+                info.score = match words.next() {
+                    Some("win") => Score::Win(parse_number(words.next(), "score")?),
+                    Some("loss") => Score::Loss(parse_number(words.next(), "score")?),
+                    value => Score::Value(parse_number(value, "score")?),
+                }
+            }
+            _ => {}
+        }
+    }
+    Ok(info)
+}
+
+impl FromStr for Response {
+    type Err = InvalidMessage;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        let (keyword, rest) = s.split_once(' ').unwrap_or((s, ""));
+        let rest = rest.trim();
+        Ok(match keyword {
+            "kepok" => Self::KepOk,
+            "readyok" => Self::ReadyOk,
+            "id" => match rest.split_once(' ') {
+                Some(("name", name)) => Self::IdName(name.trim().to_owned()),
+                Some(("author", author)) => Self::IdAuthor(author.trim().to_owned()),
+                _ => return Err(InvalidMessage::MissingParameter("name or author")),
+            },
+            "option" => {
+                let mut words = rest.split_whitespace();
+                let mut field = |name| {
+                    (words.next() == Some(name))
+                        .then(|| words.next())
+                        .flatten()
+                        .ok_or(InvalidMessage::MissingParameter(name))
+                };
+                let name = field("name")?.to_owned();
+                let kind = match field("type")? {
+                    "spin" => OptionKind::Spin {
+                        default: parse_number(Some(field("default")?), "default")?,
+                        min: parse_number(Some(field("min")?), "min")?,
+                        max: parse_number(Some(field("max")?), "max")?,
+                    },
+                    "string" => OptionKind::String {
+                        default: rest
+                            .split_once(" default")
+                            .map_or("", |(_, default)| default.trim())
+                            .to_owned(),
+                    },
+                    _ => {
+                        return Err(InvalidMessage::InvalidParameter(
+                            "Options are either `spin` or `string`.",
+                        ))
+                    }
+                };
+                Self::Option(EngineOption { name, kind })
+            }
+            "info" => match rest.strip_prefix("string") {
+                Some(text) => Self::Message(text.trim().to_owned()),
+                None => Self::Info(parse_info(rest)?),
+            },
+            "bestmove" if rest == "none" => Self::BestMove(None),
+            "bestmove" => Self::BestMove(Some(rest.parse()?)),
+            _ => return Err(InvalidMessage::Unknown),
+        })
+    }
+}
@@ -0,0 +1,186 @@
+use super::{Command, EngineOption, InvalidMessage, Response};
+use crate::{
+    board::{Move, Position},
+    search::{Info, Limits},
+};
+use std::{
+    ffi::OsStr,
+    io::{self, BufRead, BufReader, Write},
+    process::{Child, Command as Process, Stdio},
+    sync::{Arc, Mutex},
+};
+
+use thiserror::Error;
+
+#[derive(Error, Debug)]
+pub enum EngineError {
+    #[error("Could not talk to the engine: {0}")]
+    Io(#[from] io::Error),
+    #[error("The engine stopped responding.")]
+    Closed,
+    #[error("The engine sent an invalid message: {0}")]
+    InvalidMessage(#[from] InvalidMessage),
+}
+
+/// what is written to the engine, shared with its [`Stopper`]s.
+type Writer = Arc<Mutex<Box<dyn Write + Send>>>;
+
+fn send(writer: &Writer, command: &Command) -> Result<(), EngineError> {
+    // a thread that panicked mid-line cannot leave anything worse than a bad line.
+    let mut writer = writer.lock().unwrap_or_else(|e| e.into_inner());
+    writeln!(writer, "{command}")?;
+    writer.flush()?;
+    Ok(())
+}
+
+/// drives an engine that speaks the [protocol](super).
+pub struct Engine {
+    reader: Box<dyn BufRead + Send>,
+    writer: Writer,
+    /// the process is kept so it can be waited for once the engine quits.
+    process: Option<Child>,
+    pub name: String,
+    pub author: String,
+    pub options: Vec<EngineOption>,
+}
+
+impl Engine {
+    /// starts an engine process and introduces ourselves.
+    pub fn spawn<I, S>(program: impl AsRef<OsStr>, args: I) -> Result<Self, EngineError>
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<OsStr>,
+    {
+        let mut process = Process::new(program)
+            .args(args)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .spawn()?;
+        let reader = BufReader::new(process.stdout.take().unwrap());
+        let writer = process.stdin.take().unwrap();
+        let mut engine = Self::connect(reader, writer)?;
+        engine.process = Some(process);
+        Ok(engine)
+    }
+
+    /// introduces ourselves to an engine that is already running.
+    pub fn connect(
+        reader: impl BufRead + Send + 'static,
+        writer: impl Write + Send + 'static,
+    ) -> Result<Self, EngineError> {
+        let mut engine = Self {
+            reader: Box::new(reader),
+            writer: Arc::new(Mutex::new(Box::new(writer))),
+            process: None,
+            name: String::new(),
+            author: String::new(),
+            options: vec![],
+        };
+        engine.send(&Command::Kep)?;
+        loop {
+            match engine.receive()? {
+                Response::IdName(name) => engine.name = name,
+                Response::IdAuthor(author) => engine.author = author,
+                Response::Option(option) => engine.options.push(option),
+                Response::KepOk => return Ok(engine),
+                _ => {}
+            }
+        }
+    }
+
+    fn send(&mut self, command: &Command) -> Result<(), EngineError> {
+        send(&self.writer, command)
+    }
+
+    /// a handle that stops searches from another thread, while [`go`](Self::go) waits.
+    pub fn stopper(&self) -> Stopper {
+        Stopper {
+            writer: self.writer.clone(),
+        }
+    }
+
+    /// the next message that is part of the protocol. anything else is skipped.
+    fn receive(&mut self) -> Result<Response, EngineError> {
+        let mut line = String::new();
+        loop {
+            line.clear();
+            if self.reader.read_line(&mut line)? == 0 {
+                return Err(EngineError::Closed);
+            }
+            match line.parse() {
+                Err(InvalidMessage::Unknown) => continue,
+                response => return Ok(response?),
+            }
+        }
+    }
+
+    pub fn set_option(&mut self, name: &str, value: &str) -> Result<(), EngineError> {
+        self.send(&Command::SetOption {
+            name: name.to_owned(),
+            value: value.to_owned(),
+        })
+    }
+
+    /// waits until the engine has handled everything it was sent.
+    pub fn is_ready(&mut self) -> Result<(), EngineError> {
+        self.send(&Command::IsReady)?;
+        while self.receive()? != Response::ReadyOk {}
+        Ok(())
+    }
+
+    pub fn new_game(&mut self) -> Result<(), EngineError> {
+        self.send(&Command::NewGame)
+    }
+
+    /// `None` is the starting position. `actions` were played since the position.
+    pub fn set_position(
+        &mut self,
+        position: Option<&Position>,
+        actions: &[Move],
+    ) -> Result<(), EngineError> {
+        self.send(&Command::Position {
+            position: position.cloned().map(Box::new),
+            actions: actions.to_vec(),
+        })
+    }
+
+    /// searches the current position, passing on every info line, and returns the best action.
+    ///
+    /// without any limits, this only returns once the engine is stopped with a [`Stopper`].
+    pub fn go(
+        &mut self,
+        limits: &Limits,
+        mut on_info: impl FnMut(&Info),
+    ) -> Result<Option<Move>, EngineError> {
+        self.send(&Command::Go(limits.clone()))?;
+        loop {
+            match self.receive()? {
+                Response::Info(info) => on_info(&info),
+                Response::BestMove(action) => return Ok(action),
+                _ => {}
+            }
+        }
+    }
+}
+
+/// stops the searches of an [`Engine`], from any thread.
+#[derive(Clone)]
+pub struct Stopper {
+    writer: Writer,
+}
+
+impl Stopper {
+    /// asks the engine to stop searching. [`Engine::go`] then returns the best action so far.
+    pub fn stop(&self) -> Result<(), EngineError> {
+        send(&self.writer, &Command::Stop)
+    }
+}
+
+impl Drop for Engine {
+    fn drop(&mut self) {
+        let _ = self.send(&Command::Quit);
+        if let Some(process) = &mut self.process {
+            let _ = process.wait();
+        }
+    }
+}
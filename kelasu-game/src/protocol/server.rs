@@ -0,0 +1,206 @@
+use super::{Command, EngineOption, OptionKind, Response};
+use crate::{
+    book::Book,
+    eval::{Term, Weights},
+    search::search,
+    util::Rng,
+    Game,
+};
+use std::{
+    io::{BufRead, Write},
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc, Mutex,
+    },
+    thread::{self, JoinHandle},
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+const WEIGHTS_FILE: &str = "weights_file";
+const BOOK_FILE: &str = "book_file";
+
+fn options() -> Vec<EngineOption> {
+    let defaults = Weights::default();
+    let mut options = [WEIGHTS_FILE, BOOK_FILE]
+        .map(|name| EngineOption {
+            name: name.to_owned(),
+            kind: OptionKind::String {
+                default: String::new(),
+            },
+        })
+        .to_vec();
+    options.extend(Term::ALL.map(|term| EngineOption {
+        name: term.to_string(),
+        kind: OptionKind::Spin {
+            default: defaults.get(term),
+            min: -100_000,
+            max: 100_000,
+        },
+    }));
+    options
+}
+
+type Output<W> = Arc<Mutex<W>>;
+
+fn respond(output: &Output<impl Write>, response: Response) {
+    let mut output = output.lock().unwrap();
+    // if nobody is listening anymore, there is no one to tell either.
+    let _ = writeln!(output, "{response}").and_then(|_| output.flush());
+}
+
+struct Search {
+    stop: Arc<AtomicBool>,
+    /// set before the best action is sent, so the next command never finds the search busy.
+    done: Arc<AtomicBool>,
+    thread: JoinHandle<()>,
+}
+
+impl Search {
+    fn finish(self) {
+        self.stop.store(true, Ordering::Relaxed);
+        self.thread.join().unwrap();
+    }
+}
+
+/// runs the kelasu-game engine over the [protocol](super) until `quit` or the end of the input.
+///
+/// the weights of every evaluation [`Term`] are options, along with a file to load them from.
+/// with a `book_file`, positions in the opening [`Book`] are answered from it without searching.
+pub fn serve(input: impl BufRead, output: impl Write + Send + 'static) {
+    let output = Arc::new(Mutex::new(output));
+    let mut game = Game::new();
+    let mut weights = Weights::default();
+    let mut book = Book::default();
+    let seed = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |d| d.as_nanos() as u64);
+    let mut rng = Rng::new(seed);
+    let mut searching: Option<Search> = None;
+
+    for line in input.lines() {
+        let Ok(line) = line else { break };
+        if line.trim().is_empty() {
+            continue;
+        }
+        let command = match line.parse::<Command>() {
+            Ok(command) => command,
+            Err(e) => {
+                respond(&output, Response::Message(format!("{e}")));
+                continue;
+            }
+        };
+
+        // a search that has ended on its own has nothing left to do.
+        if searching
+            .as_ref()
+            .is_some_and(|s| s.done.load(Ordering::Relaxed))
+        {
+            searching.take().unwrap().finish();
+        }
+        match command {
+            Command::IsReady => respond(&output, Response::ReadyOk),
+            Command::Stop => {
+                if let Some(search) = searching.take() {
+                    search.finish();
+                }
+            }
+            Command::Quit => break,
+            _ if searching.is_some() => {
+                respond(
+                    &output,
+                    Response::Message("Stop the search first.".to_owned()),
+                );
+            }
+            Command::Kep => {
+                respond(&output, Response::IdName("kelasu-engine".to_owned()));
+                respond(
+                    &output,
+                    Response::IdAuthor("the Kelasu developers".to_owned()),
+                );
+                for option in options() {
+                    respond(&output, Response::Option(option));
+                }
+                respond(&output, Response::KepOk);
+            }
+            Command::SetOption { name, value } => {
+                let error = if name == WEIGHTS_FILE {
+                    match Weights::load(&value) {
+                        Ok(loaded) => {
+                            weights = loaded;
+                            None
+                        }
+                        Err(e) => Some(e.to_string()),
+                    }
+                } else if name == BOOK_FILE {
+                    // an empty file name turns the book off again.
+                    let loaded = if value.is_empty() {
+                        Ok(Book::default())
+                    } else {
+                        Book::load(&value)
+                    };
+                    match loaded {
+                        Ok(loaded) => {
+                            book = loaded;
+                            None
+                        }
+                        Err(e) => Some(e.to_string()),
+                    }
+                } else {
+                    match (name.parse::<Term>(), value.parse()) {
+                        (Ok(term), Ok(weight)) => {
+                            weights.set(term, weight);
+                            None
+                        }
+                        (Err(e), _) => Some(e.to_string()),
+                        (_, Err(_)) => Some("Weights must be whole numbers.".to_owned()),
+                    }
+                };
+                if let Some(e) = error {
+                    respond(&output, Response::Message(e));
+                }
+            }
+            Command::NewGame => game = Game::new(),
+            Command::Position { position, actions } => {
+                let mut next = position.map_or_else(Game::new, |p| Game::from(*p));
+                let result = actions.into_iter().try_for_each(|action| {
+                    let action = next.verify_action(action)?;
+                    next.make_move(action);
+                    Ok::<_, crate::board::InvalidMove>(())
+                });
+                match result {
+                    Ok(()) => game = next,
+                    Err(e) => respond(&output, Response::Message(e.to_string())),
+                }
+            }
+            Command::Go(limits) => {
+                let from_book = book
+                    .pick(&game, &mut rng)
+                    .and_then(|choice| choice.actions.first())
+                    .filter(|action| game.verify_action((*action).clone()).is_ok());
+                if let Some(action) = from_book {
+                    respond(&output, Response::Message("book".to_owned()));
+                    respond(&output, Response::BestMove(Some(action.clone())));
+                    continue;
+                }
+                let stop = Arc::new(AtomicBool::new(false));
+                let done = Arc::new(AtomicBool::new(false));
+                let thread = thread::spawn({
+                    let (game, weights, output) = (game.clone(), weights.clone(), output.clone());
+                    let (stop, done) = (stop.clone(), done.clone());
+                    move || {
+                        let best = search(&game, &weights, &limits, &stop, |info| {
+                            respond(&output, Response::Info(info.clone()))
+                        });
+                        done.store(true, Ordering::Relaxed);
+                        respond(&output, Response::BestMove(best));
+                    }
+                });
+                searching = Some(Search { stop, done, thread });
+            }
+        }
+    }
+
+    if let Some(search) = searching {
+        search.finish();
+    }
+}
@@ -0,0 +1,206 @@
+use super::*;
+use crate::{
+    board::Game,
+    book::{BookBuilder, Granularity},
+    search::Limits,
+};
+use std::{
+    io::{pipe, BufReader, Cursor, Write},
+    sync::{Arc, Mutex},
+    thread,
+};
+
+#[test]
+pub(crate) fn test_command_round_trip() {
+    let position = Game::new().position();
+    let commands = [
+        Command::Kep,
+        Command::SetOption {
+            name: "weights_file".to_owned(),
+            value: "weights/my weights.txt".to_owned(),
+        },
+        Command::Position {
+            position: None,
+            actions: vec![],
+        },
+        Command::Position {
+            position: Some(Box::new(position)),
+            actions: vec![
+                "move 11 to 21".parse().unwrap(),
+                "move 13 to 23".parse().unwrap(),
+            ],
+        },
+        Command::Go(Limits::default()),
+        Command::Go(Limits {
+            depth: Some(4),
+            time: Some(Duration::from_millis(1500)),
+        }),
+        Command::Quit,
+    ];
+    for command in commands {
+        assert_eq!(command.to_string().parse::<Command>().unwrap(), command);
+    }
+    assert!(matches!(
+        "position startpos moves move 11 to 55".parse::<Command>(),
+        Ok(Command::Position { .. })
+    ));
+    assert!(matches!(
+        "go depth".parse::<Command>(),
+        Err(InvalidMessage::MissingParameter("depth"))
+    ));
+}
+
+#[test]
+pub(crate) fn test_response_round_trip() {
+    let responses = [
+        Response::IdName("kelasu-engine".to_owned()),
+        Response::Option(EngineOption {
+            name: "mobility".to_owned(),
+            kind: OptionKind::Spin {
+                default: 5,
+                min: -100,
+                max: 100,
+            },
+        }),
+        Response::Option(EngineOption {
+            name: "weights_file".to_owned(),
+            kind: OptionKind::String {
+                default: String::new(),
+            },
+        }),
+        Response::Info(Info {
+            depth: 2,
+            score: Score::Win(2),
+            nodes: 300,
+            time: Duration::from_millis(12),
+            pv: vec![
+                "move 11 to 21".parse().unwrap(),
+                "merge warrior at 20 with 21".parse().unwrap(),
+            ],
+        }),
+        Response::Message("Stop the search first.".to_owned()),
+        Response::BestMove(None),
+        Response::BestMove(Some("move 11 to 21".parse().unwrap())),
+    ];
+    for response in responses {
+        assert_eq!(response.to_string().parse::<Response>().unwrap(), response);
+    }
+}
+
+/// collects everything the engine writes.
+#[derive(Clone, Default)]
+struct Output(Arc<Mutex<Vec<u8>>>);
+
+impl Write for Output {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.0.lock().unwrap().write(buf)
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+#[test]
+pub(crate) fn test_serve() {
+    let input = "\
+        kep\n\
+        setoption name mobility value ten\n\
+        position startpos moves move 11 to 55\n\
+        position startpos moves move 11 to 21; move 13 to 23\n\
+        go depth 1\n\
+        isready\n\
+        quit\n";
+    let output = Output::default();
+    serve(Cursor::new(input), output.clone());
+
+    let output = String::from_utf8(output.0.lock().unwrap().clone()).unwrap();
+    let lines = output.lines().collect::<Vec<_>>();
+    assert_eq!(lines[0], "id name kelasu-engine");
+    assert!(lines.contains(&"kepok"));
+    assert!(lines.contains(&"option name mobility type spin default 5 min -100000 max 100000"));
+    assert!(lines.contains(&"info string Weights must be whole numbers."));
+    assert!(lines
+        .iter()
+        .any(|l| l.starts_with("info string That piece cannot move")));
+    assert!(lines.contains(&"readyok"));
+    let best = lines
+        .iter()
+        .find_map(|l| l.strip_prefix("bestmove "))
+        .unwrap();
+    let mut game = Game::new();
+    for action in ["move 11 to 21", "move 13 to 23", best] {
+        let action = game.verify_move_str(action).unwrap();
+        game.make_move(action);
+    }
+}
+
+#[test]
+pub(crate) fn test_serve_book() {
+    let mut builder = BookBuilder::new(Granularity::Action, 1);
+    builder.add(&"[Result \"blue\"]\nmove 18 to 28".parse().unwrap());
+    let path = std::env::temp_dir().join(format!("kelasu-book-{}.kbook", std::process::id()));
+    builder.build().save(&path).unwrap();
+
+    let input = format!(
+        "setoption name book_file value {}\n\
+        go depth 1\n\
+        position startpos moves move 18 to 28\n\
+        go depth 1\n\
+        quit\n",
+        path.display()
+    );
+    let output = Output::default();
+    serve(Cursor::new(input), output.clone());
+    std::fs::remove_file(path).unwrap();
+
+    let output = String::from_utf8(output.0.lock().unwrap().clone()).unwrap();
+    let lines = output.lines().collect::<Vec<_>>();
+    assert_eq!(lines[..2], ["info string book", "bestmove move 18 to 28"]);
+    // out of the book, the engine searches again.
+    assert_eq!(
+        lines.iter().filter(|l| **l == "info string book").count(),
+        1
+    );
+}
+
+#[test]
+pub(crate) fn test_client() {
+    let (engine_input, to_engine) = pipe().unwrap();
+    let (from_engine, engine_output) = pipe().unwrap();
+    let server = thread::spawn(move || serve(BufReader::new(engine_input), engine_output));
+
+    let mut engine = Engine::connect(BufReader::new(from_engine), to_engine).unwrap();
+    assert_eq!(engine.name, "kelasu-engine");
+    assert!(engine.options.iter().any(|o| o.name == "weights_file"));
+
+    engine.set_option("stones", "500").unwrap();
+    engine
+        .set_position(None, &["move 11 to 21".parse().unwrap()])
+        .unwrap();
+    engine.is_ready().unwrap();
+    let mut depths = vec![];
+    let limits = Limits {
+        depth: Some(2),
+        time: None,
+    };
+    let best = engine.go(&limits, |info| depths.push(info.depth)).unwrap();
+    assert_eq!(depths, [1, 2]);
+
+    let mut game = Game::new();
+    game.make_move(game.verify_move_str("move 11 to 21").unwrap());
+    assert!(game.verify_action(best.unwrap()).is_ok());
+
+    // an infinite search only ends when another thread stops it.
+    let stopper = engine.stopper();
+    let stopping = thread::spawn(move || {
+        thread::sleep(Duration::from_millis(200));
+        stopper.stop().unwrap();
+    });
+    let best = engine.go(&Limits::default(), |_| {}).unwrap();
+    stopping.join().unwrap();
+    assert!(game.verify_action(best.unwrap()).is_ok());
+
+    drop(engine);
+    server.join().unwrap();
+}
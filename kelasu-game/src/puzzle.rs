@@ -0,0 +1,443 @@
+//! Puzzles: a position, a goal, and the lines of play that reach it.
+//!
+//! A collection is a text file with one puzzle after another, each starting with `puzzle <title>`.
+//! Blank lines and lines starting with `#` are ignored.
+//!
+//! ```text
+//! puzzle The diplomat's bargain
+//! goal convert general
+//! turn blue
+//! board
+//!     .........S
+//!     ..........
+//!     ..........
+//!     ...D......
+//!     ....g.....
+//!     ..........
+//!     ..........
+//!     ..........
+//!     ....w.....
+//!     .........s
+//! solution move 33 to 44
+//! ```
+//!
+//! Directives:
+//! - `goal <goal>`, see [`Goal`].
+//! - `turn <team>` and `board` (followed by the 100 tiles) set up the position,
+//!   or `position <position>` in the [`Position`] format.
+//! - `solution <action>; <action>; ...` is an accepted line, including the replies of the
+//!   other team. A puzzle may have several, and needs at least one.
+//! - `difficulty <n>` is optional. Higher is harder, see [`Generator`] for how it is rated.
+
+use crate::{
+    board::{Board, GameState, InvalidMove, Move, Position, Problem, Winner},
+    piece::{PieceKind, Team},
+    scenario::read_board,
+    Game,
+};
+use std::{fmt::Display, fs, io, path::Path, str::FromStr};
+
+use thiserror::Error;
+
+mod generate;
+#[cfg(test)]
+mod tests;
+
+pub use generate::Generator;
+
+const BUILTIN: &str = include_str!("../puzzles/basics.txt");
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Goal {
+    /// `win <turns>`: win within this many turns.
+    Win(u32),
+    /// `capture <kind>`: capture an enemy piece of this kind.
+    Capture(PieceKind),
+    /// `convert <kind>`: convert an enemy piece of this kind with a Diplomat.
+    Convert(PieceKind),
+    /// `survive <turns>`: still be playing after this many turns of the other team.
+    Survive(u32),
+}
+
+#[derive(Error, Debug)]
+#[error("That is not a goal. Goals are `win <turns>`, `capture <piece>`, `convert <piece>` and `survive <turns>`.")]
+pub struct UnknownGoal;
+
+impl FromStr for Goal {
+    type Err = UnknownGoal;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (kind, value) = s.split_once(' ').ok_or(UnknownGoal)?;
+        let value = value.trim();
+        Ok(match kind {
+            "win" => Self::Win(value.parse().map_err(|_| UnknownGoal)?),
+            "capture" => Self::Capture(value.parse().map_err(|_| UnknownGoal)?),
+            "convert" => Self::Convert(value.parse().map_err(|_| UnknownGoal)?),
+            "survive" => Self::Survive(value.parse().map_err(|_| UnknownGoal)?),
+            _ => return Err(UnknownGoal),
+        })
+    }
+}
+
+/// in the puzzle syntax, e.g. `win 2`.
+impl Display for Goal {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let piece = |kind: &PieceKind| format!("{kind:?}").to_ascii_lowercase();
+        match self {
+            Goal::Win(turns) => write!(f, "win {turns}"),
+            Goal::Capture(kind) => write!(f, "capture {}", piece(kind)),
+            Goal::Convert(kind) => write!(f, "convert {}", piece(kind)),
+            Goal::Survive(turns) => write!(f, "survive {turns}"),
+        }
+    }
+}
+
+impl Goal {
+    /// what the player is asked to do, for showing with the board.
+    pub fn describe(&self, team: Team) -> String {
+        let plural = |n: &u32| if *n == 1 { "" } else { "s" };
+        match self {
+            Goal::Win(turns) => format!(
+                "{team} to play and win within {turns} turn{}.",
+                plural(turns)
+            ),
+            Goal::Capture(kind) => format!("{team} to play and capture a {kind:?}."),
+            Goal::Convert(kind) => format!("{team} to play and convert a {kind:?}."),
+            Goal::Survive(turns) => format!(
+                "{team} to play and survive {turns} enemy turn{}.",
+                plural(turns)
+            ),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Puzzle {
+    pub title: String,
+    pub goal: Goal,
+    /// the player is the team to move.
+    pub start: Position,
+    pub solutions: Vec<Vec<Move>>,
+    pub difficulty: Option<u32>,
+}
+
+#[derive(Error, Debug)]
+pub enum InvalidPuzzle {
+    #[error("The puzzles could not be read: {0}")]
+    Io(#[from] io::Error),
+    #[error("line {line}: {message}")]
+    Syntax { line: usize, message: String },
+}
+
+impl InvalidPuzzle {
+    fn new(line: usize, message: impl ToString) -> Self {
+        Self::Syntax {
+            line,
+            message: message.to_string(),
+        }
+    }
+}
+
+impl Puzzle {
+    pub fn start_game(&self) -> Game {
+        self.start.clone().into()
+    }
+
+    /// the puzzles that come with the game, easiest first.
+    pub fn builtin() -> Vec<Self> {
+        Self::parse_all(BUILTIN).expect("the built-in puzzles are valid.")
+    }
+
+    pub fn load(path: impl AsRef<Path>) -> Result<Vec<Self>, InvalidPuzzle> {
+        Self::parse_all(&fs::read_to_string(path)?)
+    }
+
+    /// every puzzle in `s`, in order. every solution must be legal.
+    pub fn parse_all(s: &str) -> Result<Vec<Self>, InvalidPuzzle> {
+        let mut lines = s
+            .lines()
+            .enumerate()
+            .map(|(i, l)| (i + 1, l.trim()))
+            .filter(|(_, l)| !l.is_empty() && !l.starts_with('#'));
+
+        // each puzzle is collected with the line of its title, and is checked once it is complete.
+        let mut puzzles: Vec<(usize, Self)> = vec![];
+        let mut turn = Team::default();
+        while let Some((line, text)) = lines.next() {
+            let (keyword, rest) = text.split_once(' ').unwrap_or((text, ""));
+            let rest = rest.trim();
+            let err = |message: &str| InvalidPuzzle::new(line, message);
+
+            if keyword == "puzzle" {
+                puzzles.push((
+                    line,
+                    Self {
+                        title: rest.to_owned(),
+                        goal: Goal::Win(1),
+                        start: Game::new().position(),
+                        solutions: vec![],
+                        difficulty: None,
+                    },
+                ));
+                turn = Team::default();
+                continue;
+            }
+            let Some((_, puzzle)) = puzzles.last_mut() else {
+                return Err(err("a collection starts with `puzzle <title>`."));
+            };
+            match keyword {
+                "goal" => puzzle.goal = rest.parse().map_err(|e| InvalidPuzzle::new(line, e))?,
+                "turn" => {
+                    turn = rest.parse().map_err(|e| InvalidPuzzle::new(line, e))?;
+                    puzzle.start = Game::from_position(turn, puzzle.start.board.clone()).position();
+                }
+                "board" => {
+                    let board: Board = read_board(&mut lines).map_err(err)?;
+                    puzzle.start = Game::from_position(turn, board).position();
+                }
+                "position" => {
+                    puzzle.start = rest.parse().map_err(|e| InvalidPuzzle::new(line, e))?;
+                }
+                "solution" => puzzle.solutions.push(
+                    rest.split(';')
+                        .map(|action| action.parse::<Move>())
+                        .collect::<Result<_, _>>()
+                        .map_err(|e| InvalidPuzzle::new(line, e))?,
+                ),
+                "difficulty" => {
+                    puzzle.difficulty = Some(
+                        rest.parse()
+                            .map_err(|_| err("difficulty must be a number."))?,
+                    );
+                }
+                _ => return Err(err("unknown directive.")),
+            }
+        }
+
+        for (line, puzzle) in &puzzles {
+            if puzzle.solutions.is_empty() {
+                return Err(InvalidPuzzle::new(*line, "the puzzle has no solution."));
+            }
+            if let Some(problem) = puzzle
+                .start
+                .validate()
+                .into_iter()
+                .find(Problem::is_impossible)
+            {
+                return Err(InvalidPuzzle::new(*line, problem));
+            }
+            for solution in &puzzle.solutions {
+                // a run with only this solution plays its own replies.
+                let mut run = PuzzleRun::new(Self {
+                    solutions: vec![solution.clone()],
+                    ..puzzle.clone()
+                });
+                while let Some(action) = solution.get(run.played.len()).filter(|_| !run.is_solved())
+                {
+                    match run.attempt(action.clone()) {
+                        Verdict::Correct { .. } | Verdict::Solved => {}
+                        verdict => {
+                            return Err(InvalidPuzzle::new(*line, format!("`{action}`: {verdict}")))
+                        }
+                    }
+                }
+                if !run.is_solved() {
+                    return Err(InvalidPuzzle::new(
+                        *line,
+                        "a solution does not solve the puzzle.",
+                    ));
+                }
+                if run.played.len() < solution.len() {
+                    return Err(InvalidPuzzle::new(
+                        *line,
+                        "a solution goes on after it is solved.",
+                    ));
+                }
+            }
+        }
+        Ok(puzzles.into_iter().map(|(_, puzzle)| puzzle).collect())
+    }
+}
+
+/// in the collection format, with the position on one line.
+impl Display for Puzzle {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "puzzle {}", self.title)?;
+        writeln!(f, "goal {}", self.goal)?;
+        if let Some(difficulty) = self.difficulty {
+            writeln!(f, "difficulty {difficulty}")?;
+        }
+        writeln!(f, "position {}", self.start)?;
+        for solution in &self.solutions {
+            let actions = solution.iter().map(ToString::to_string).collect::<Vec<_>>();
+            writeln!(f, "solution {}", actions.join("; "))?;
+        }
+        Ok(())
+    }
+}
+
+/// the verdict on an attempted action.
+#[derive(Debug)]
+pub enum Verdict {
+    /// the action is against the rules. nothing was played.
+    Illegal(InvalidMove),
+    /// the action is legal, but not a solution. nothing was played.
+    Wrong,
+    /// the other team is to move, but the solutions have no reply for it. nothing was played.
+    NotYourTurn,
+    /// the action was played and the puzzle goes on, after the replies of the other team.
+    Correct { replies: Vec<Move> },
+    /// the action was played and reached the goal.
+    Solved,
+    /// the solution itself cannot be played. only happens with invalid puzzles.
+    Failed(InvalidMove),
+}
+
+impl Display for Verdict {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Verdict::Illegal(e) => write!(f, "That action is illegal: {e}"),
+            Verdict::Wrong => write!(f, "That is not it. Try again!"),
+            Verdict::NotYourTurn => write!(f, "It is not your turn, and the puzzle has no reply."),
+            Verdict::Correct { replies } if replies.is_empty() => write!(f, "Correct! Keep going."),
+            Verdict::Correct { replies } => {
+                write!(f, "Correct! The reply is")?;
+                for (i, reply) in replies.iter().enumerate() {
+                    write!(f, "{} `{reply}`", if i == 0 { "" } else { "," })?;
+                }
+                write!(f, ".")
+            }
+            Verdict::Solved => write!(f, "Solved!"),
+            Verdict::Failed(e) => write!(f, "The puzzle is broken: {e}"),
+        }
+    }
+}
+
+/// a puzzle being solved, one action at a time.
+#[derive(Debug, Clone)]
+pub struct PuzzleRun {
+    pub puzzle: Puzzle,
+    pub game: Game,
+    /// every action so far, by both teams.
+    pub played: Vec<Move>,
+    /// how many turns the other team has finished.
+    enemy_turns: u32,
+    solved: bool,
+}
+
+impl PuzzleRun {
+    pub fn new(puzzle: Puzzle) -> Self {
+        Self {
+            game: puzzle.start_game(),
+            puzzle,
+            played: vec![],
+            enemy_turns: 0,
+            solved: false,
+        }
+    }
+
+    /// the team that is solving the puzzle.
+    pub fn player(&self) -> Team {
+        self.puzzle.start.turn
+    }
+
+    pub fn is_solved(&self) -> bool {
+        self.solved
+    }
+
+    /// the solutions that agree with everything played so far.
+    fn candidates(&self) -> impl Iterator<Item = &[Move]> {
+        self.puzzle
+            .solutions
+            .iter()
+            .filter(|s| s.starts_with(&self.played))
+            .map(|s| &s[self.played.len()..])
+    }
+
+    /// the next action of a solution.
+    pub fn hint(&self) -> Option<&Move> {
+        if self.solved {
+            return None;
+        }
+        self.candidates().find_map(|rest| rest.first())
+    }
+
+    /// whether the action just played from `before` reached the goal.
+    fn reached_goal(&self, before: &Game, action: &Move) -> bool {
+        let player = self.player();
+        let taken = |kind: PieceKind, converted: bool| match action {
+            Move::Move { from, to } => {
+                let mover = before.board[*from].0.unwrap();
+                let diplomat = mover.kind == PieceKind::Diplomat
+                    && from
+                        .dir_to(*to)
+                        .is_some_and(|([dx, dy], _)| dx != 0 && dy != 0);
+                before.board[*to]
+                    .0
+                    .is_some_and(|p| p.team != player && p.kind == kind)
+                    && diplomat == converted
+            }
+            _ => false,
+        };
+        match self.puzzle.goal {
+            Goal::Win(_) => self.game.state == GameState::Finished(Winner(Some(player))),
+            Goal::Capture(kind) => taken(kind, false),
+            Goal::Convert(kind) => taken(kind, true),
+            // surviving is checked after the replies.
+            Goal::Survive(_) => false,
+        }
+    }
+
+    /// tries an action of the player, and plays the replies of the other team if it was right.
+    pub fn attempt(&mut self, action: Move) -> Verdict {
+        if self.solved {
+            return Verdict::Illegal(InvalidMove::GameOver);
+        }
+        if self.game.turn != self.player() {
+            return Verdict::NotYourTurn;
+        }
+        let verified = match self.game.verify_action(action.clone()) {
+            Ok(verified) => verified,
+            Err(e) => return Verdict::Illegal(e),
+        };
+        let on_line = self.candidates().any(|rest| rest.first() == Some(&action));
+
+        let before = self.game.clone();
+        self.game.make_move(verified);
+        if self.reached_goal(&before, &action) {
+            self.played.push(action);
+            self.solved = true;
+            return Verdict::Solved;
+        }
+        if !on_line {
+            self.game = before;
+            return Verdict::Wrong;
+        }
+        self.played.push(action);
+
+        let mut replies = vec![];
+        while self.game.turn != self.player() && self.game.is_ongoing() {
+            let Some(reply) = self.candidates().find_map(|rest| rest.first().cloned()) else {
+                break;
+            };
+            match self.game.verify_action(reply.clone()) {
+                Ok(verified) => self.game.make_move(verified),
+                Err(e) => return Verdict::Failed(e),
+            }
+            self.played.push(reply.clone());
+            replies.push(reply);
+            if self.game.turn == self.player() {
+                self.enemy_turns += 1;
+            }
+        }
+        let lost =
+            matches!(self.game.state, GameState::Finished(Winner(Some(t))) if t != self.player());
+        if let Goal::Survive(turns) = self.puzzle.goal {
+            if !lost && self.enemy_turns >= turns {
+                self.solved = true;
+                return Verdict::Solved;
+            }
+        }
+        Verdict::Correct { replies }
+    }
+}
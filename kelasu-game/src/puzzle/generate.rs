@@ -0,0 +1,339 @@
+use super::{Goal, Puzzle};
+use crate::{
+    board::{GameState, Move, Winner},
+    piece::{PieceKind, Team},
+    record::Record,
+    solver::{actions_to_win, solve, Solution, VICTORY_TILES},
+    Game,
+};
+use std::{collections::HashSet, sync::atomic::AtomicBool};
+
+/// finds puzzles in played games: turns where exactly one way of playing wins,
+/// or exactly one way avoids losing on the enemy's next turn.
+///
+/// ways of playing that only differ in the order of their actions count as one.
+/// a win counts as unique if no action off its lines, at any point of the turn,
+/// still wins the turn.
+///
+/// the difficulty is ten for every action of the solution, five more for every merge and
+/// conversion, plus the base 2 logarithm of how many actions the player could start with.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Generator {
+    /// the most actions a winning turn may take.
+    pub max_win_actions: u8,
+    /// defences are only looked for when the player has at most this much power,
+    /// since every possible turn has to be tried.
+    pub max_defence_power: u8,
+}
+
+impl Default for Generator {
+    fn default() -> Self {
+        Self {
+            max_win_actions: 3,
+            max_defence_power: 2,
+        }
+    }
+}
+
+/// a way of playing a turn, and where it leads.
+struct Turn {
+    line: Vec<Move>,
+    game: Game,
+}
+
+/// every way the team to move can play its turn in at most `actions` actions,
+/// with one line for every position it leads to. a turn also ends when the game does.
+///
+/// `prune` is told how many actions are left, and skips the position if it returns `true`.
+fn turns(game: &Game, actions: u8, prune: &impl Fn(&Game, u8) -> bool) -> Vec<Turn> {
+    fn visit(
+        game: &Game,
+        left: u8,
+        line: &mut Vec<Move>,
+        seen: &mut HashSet<u64>,
+        ends: &mut Vec<Turn>,
+        prune: &impl Fn(&Game, u8) -> bool,
+    ) {
+        if prune(game, left) {
+            return;
+        }
+        for action in game.legal_actions() {
+            let mut next = game.clone();
+            line.push(action.action().clone());
+            next.make_move(action);
+            let ended = next.turn != game.turn || !next.is_ongoing();
+            if !seen.insert(next.position_key()) {
+                // another order of the same actions got here first.
+            } else if ended {
+                ends.push(Turn {
+                    line: line.clone(),
+                    game: next,
+                });
+            } else if left > 1 {
+                visit(&next, left - 1, line, seen, ends, prune);
+            }
+            line.pop();
+        }
+    }
+
+    let mut ends = vec![];
+    visit(
+        game,
+        actions,
+        &mut vec![],
+        &mut HashSet::new(),
+        &mut ends,
+        prune,
+    );
+    ends
+}
+
+/// every order of the actions of `line` that is legal and leads to the same position.
+fn orderings(game: &Game, line: &[Move], end: u64) -> Vec<Vec<Move>> {
+    fn visit(
+        game: &Game,
+        left: &mut Vec<Move>,
+        line: &mut Vec<Move>,
+        end: u64,
+        out: &mut Vec<Vec<Move>>,
+    ) {
+        if left.is_empty() {
+            if game.position_key() == end {
+                out.push(line.clone());
+            }
+            return;
+        }
+        for i in 0..left.len() {
+            let action = left.remove(i);
+            if let Ok(verified) = game.verify_action(action.clone()) {
+                let mut next = game.clone();
+                next.make_move(verified);
+                line.push(action.clone());
+                visit(&next, left, line, end, out);
+                line.pop();
+            }
+            left.insert(i, action);
+        }
+    }
+
+    let mut out = vec![];
+    visit(game, &mut line.to_vec(), &mut vec![], end, &mut out);
+    out
+}
+
+/// whether an action off the `lines`, which start `played` actions into the turn,
+/// still wins the turn, however long it takes.
+fn wins_off_line(game: &Game, lines: &[Vec<Move>], played: usize, stop: &AtomicBool) -> bool {
+    // the orderings of a line are all as long.
+    if lines.first().is_none_or(|line| played == line.len()) {
+        return false;
+    }
+    for action in game.legal_actions() {
+        let on_line = lines
+            .iter()
+            .filter(|line| line[played] == *action.action())
+            .cloned()
+            .collect::<Vec<_>>();
+        let mut next = game.clone();
+        next.make_move(action);
+        let wins = if !on_line.is_empty() {
+            wins_off_line(&next, &on_line, played + 1, stop)
+        } else if next.turn == game.turn && next.is_ongoing() {
+            matches!(solve(&next, 1, stop), Some(Solution::Win { .. }))
+        } else {
+            next.state == GameState::Finished(Winner(Some(game.turn)))
+        };
+        if wins {
+            return true;
+        }
+    }
+    false
+}
+
+/// whether the action, played from `game`, is a merge or a conversion.
+fn is_special(game: &Game, action: &Move) -> bool {
+    match action {
+        Move::Merge { .. } => true,
+        Move::Move { from, to } => {
+            game.board[*from]
+                .0
+                .is_some_and(|p| p.kind == PieceKind::Diplomat)
+                && game.board[*to].0.is_some()
+                && from
+                    .dir_to(*to)
+                    .is_some_and(|([dx, dy], _)| dx != 0 && dy != 0)
+        }
+        _ => false,
+    }
+}
+
+fn difficulty(game: &Game, line: &[Move]) -> u32 {
+    let choices = game.legal_actions().len() as u32;
+    let mut game = game.clone();
+    let mut special = 0;
+    for action in line {
+        special += is_special(&game, action) as u32;
+        game.make_move(game.verify_action(action.clone()).unwrap());
+    }
+    10 * line.len() as u32 + 5 * special + (choices + 1).ilog2()
+}
+
+/// how a won game was won, for the title of its puzzle.
+fn how_won(game: &Game, winner: Team) -> &'static str {
+    if VICTORY_TILES
+        .iter()
+        .all(|&pos| game.board[pos].0.is_some_and(|p| p.team == winner))
+    {
+        "Take the centre"
+    } else if game.board.stone_count(!winner) == 0 {
+        "The last stone"
+    } else {
+        "The last piece"
+    }
+}
+
+impl Generator {
+    /// the shortest winning turn of the team to move, if it is the only way to win.
+    fn win(&self, game: &Game) -> Option<Turn> {
+        let team = game.turn;
+        // every action brings a win at most one step closer.
+        let prune = |game: &Game, left: u8| actions_to_win(game) > left as usize;
+        let max = self.max_win_actions.min(game.power);
+        for actions in 1..=max {
+            let win = turns(game, actions, &prune)
+                .into_iter()
+                .find(|t| t.game.state == GameState::Finished(Winner(Some(team))));
+            if let Some(win) = win {
+                let lines = orderings(game, &win.line, win.game.position_key());
+                let stop = AtomicBool::new(false);
+                return (!wins_off_line(game, &lines, 0, &stop)).then_some(win);
+            }
+        }
+        None
+    }
+
+    /// the only turn of the team to move that keeps the enemy from winning on its next turn,
+    /// if there is exactly one and the enemy would otherwise win.
+    fn defence(&self, game: &Game) -> Option<Turn> {
+        if game.power > self.max_defence_power {
+            return None;
+        }
+        let stop = AtomicBool::new(false);
+        let wins = |game: &Game| matches!(solve(game, 1, &stop), Some(Solution::Win { .. }));
+        // without a threat, every turn is a defence.
+        if !wins(&Game::from_position(!game.turn, game.board.clone())) {
+            return None;
+        }
+
+        let mut saving = vec![];
+        let mut losing = 0;
+        for turn in turns(game, game.power, &|_, _| false) {
+            match turn.game.state {
+                GameState::Finished(Winner(Some(winner))) if winner == game.turn => return None,
+                GameState::Finished(Winner(Some(_))) => losing += 1,
+                _ if wins(&turn.game) => losing += 1,
+                _ => saving.push(turn),
+            }
+        }
+        (saving.len() == 1 && losing > 0).then(|| saving.pop().unwrap())
+    }
+
+    /// a puzzle from the start of the turn in `game`, if there is one.
+    ///
+    /// `played` is what happened next in the game, which is used as the enemy's reply
+    /// to a defence if it fits.
+    pub fn puzzle_at(&self, game: &Game, played: &[Move]) -> Option<Puzzle> {
+        if !game.is_ongoing() {
+            return None;
+        }
+        let (goal, title, turn, reply) = if let Some(win) = self.win(game) {
+            let title = how_won(&win.game, game.turn);
+            (Goal::Win(1), title, win, vec![])
+        } else {
+            let defence = self.defence(game)?;
+            let reply = enemy_reply(game, &defence, played);
+            (Goal::Survive(1), "Hold on", defence, reply)
+        };
+
+        let solutions = orderings(game, &turn.line, turn.game.position_key())
+            .into_iter()
+            .map(|line| [line, reply.clone()].concat())
+            .collect();
+        Some(Puzzle {
+            title: title.to_owned(),
+            goal,
+            start: game.position(),
+            solutions,
+            difficulty: Some(difficulty(game, &turn.line)),
+        })
+    }
+
+    /// every puzzle in the records, easiest first. positions that come up again are only used once.
+    pub fn generate(&self, records: &[Record]) -> Vec<Puzzle> {
+        let mut seen = HashSet::new();
+        let mut puzzles = vec![];
+        for (i, record) in records.iter().enumerate() {
+            let mut game = record.start_game();
+            let mut turn_number = 1;
+            let mut turn_started = true;
+            for (j, action) in record.actions.iter().enumerate() {
+                if turn_started && seen.insert(game.position_key()) {
+                    if let Some(mut puzzle) = self.puzzle_at(&game, &record.actions[j..]) {
+                        puzzle.title =
+                            format!("{} (game {}, turn {turn_number})", puzzle.title, i + 1);
+                        puzzles.push(puzzle);
+                    }
+                }
+                let team = game.turn;
+                let Ok(verified) = game.verify_action(action.clone()) else {
+                    break;
+                };
+                game.make_move(verified);
+                turn_started = game.turn != team;
+                turn_number += turn_started as u32;
+                if !game.is_ongoing() {
+                    break;
+                }
+            }
+        }
+        puzzles.sort_by_key(|p| p.difficulty);
+        puzzles
+    }
+}
+
+/// the enemy turn after a defence: what was played in the game if the game played the defence,
+/// or else the first legal actions.
+fn enemy_reply(game: &Game, defence: &Turn, played: &[Move]) -> Vec<Move> {
+    // the game may have played the defence in another order.
+    let mut from_game = &[][..];
+    if played.len() > defence.line.len() {
+        let (own, rest) = played.split_at(defence.line.len());
+        let mut replayed = game.clone();
+        let legal = own
+            .iter()
+            .all(|action| match replayed.verify_action(action.clone()) {
+                Ok(verified) => {
+                    replayed.make_move(verified);
+                    true
+                }
+                Err(_) => false,
+            });
+        if legal && replayed.position_key() == defence.game.position_key() {
+            from_game = rest;
+        }
+    }
+
+    let mut game = defence.game.clone();
+    let enemy = game.turn;
+    let mut reply = vec![];
+    while game.turn == enemy && game.is_ongoing() {
+        let action = from_game
+            .get(reply.len())
+            .and_then(|action| game.verify_action(action.clone()).ok())
+            .or_else(|| game.legal_actions().into_iter().next());
+        let Some(action) = action else { break };
+        reply.push(action.action().clone());
+        game.make_move(action);
+    }
+    reply
+}
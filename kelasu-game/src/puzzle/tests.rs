@@ -0,0 +1,205 @@
+use super::*;
+use crate::{
+    record::Record,
+    scenario::blue_to_move,
+    solver::{solve, Solution},
+};
+use std::sync::atomic::AtomicBool;
+
+#[test]
+pub(crate) fn test_builtin() {
+    let puzzles = Puzzle::builtin();
+    assert!(!puzzles.is_empty());
+    for puzzle in puzzles {
+        // the solver agrees that every win puzzle can be won.
+        if let Goal::Win(turns) = puzzle.goal {
+            let solution = solve(&puzzle.start_game(), turns, &AtomicBool::new(false));
+            assert!(
+                matches!(solution, Some(Solution::Win { .. })),
+                "{}",
+                puzzle.title
+            );
+        }
+    }
+}
+
+#[test]
+pub(crate) fn test_goal_round_trip() {
+    for goal in ["win 2", "capture stone", "convert general", "survive 1"] {
+        assert_eq!(goal.parse::<Goal>().unwrap().to_string(), goal);
+    }
+    assert!("win".parse::<Goal>().is_err());
+    assert!("capture pawn".parse::<Goal>().is_err());
+}
+
+#[test]
+pub(crate) fn test_run() {
+    let puzzle = Puzzle::builtin()
+        .into_iter()
+        .find(|p| p.title == "The long way round")
+        .unwrap();
+    let mut run = PuzzleRun::new(puzzle);
+    assert_eq!(run.hint().unwrap().to_string(), "move 10 to 19");
+
+    assert!(matches!(
+        run.attempt("move 10 to 00".parse().unwrap()),
+        Verdict::Illegal(_)
+    ));
+    assert!(matches!(
+        run.attempt("move 10 to 20".parse().unwrap()),
+        Verdict::Wrong
+    ));
+    assert!(run.played.is_empty());
+
+    let Verdict::Correct { replies } = run.attempt("move 10 to 50".parse().unwrap()) else {
+        panic!("that is a solution.");
+    };
+    assert_eq!(replies.len(), 1);
+    assert_eq!(run.played.len(), 2);
+    assert_eq!(run.hint().unwrap().to_string(), "move 50 to 59");
+    assert!(matches!(
+        run.attempt("move 50 to 59".parse().unwrap()),
+        Verdict::Solved
+    ));
+    assert!(run.is_solved());
+    assert_eq!(run.hint(), None);
+    assert!(matches!(
+        run.attempt("move 59 to 58".parse().unwrap()),
+        Verdict::Illegal(InvalidMove::GameOver)
+    ));
+}
+
+#[test]
+pub(crate) fn test_missing_reply() {
+    let puzzle = Puzzle::builtin()
+        .into_iter()
+        .find(|p| p.title == "The long way round")
+        .unwrap();
+    // the solution stops before the reply of the other team.
+    let mut run = PuzzleRun::new(Puzzle {
+        solutions: vec![vec!["move 10 to 50".parse().unwrap()]],
+        ..puzzle
+    });
+    let Verdict::Correct { replies } = run.attempt("move 10 to 50".parse().unwrap()) else {
+        panic!("that is a solution.");
+    };
+    assert!(replies.is_empty());
+    assert!(matches!(
+        run.attempt("move 50 to 59".parse().unwrap()),
+        Verdict::NotYourTurn
+    ));
+}
+
+#[test]
+pub(crate) fn test_survive() {
+    let puzzle = Puzzle::builtin()
+        .into_iter()
+        .find(|p| p.goal == Goal::Survive(1))
+        .unwrap();
+    let mut run = PuzzleRun::new(puzzle);
+    assert!(matches!(
+        run.attempt("move 17 to 27".parse().unwrap()),
+        Verdict::Wrong
+    ));
+    assert!(matches!(
+        run.attempt("move 11 to 10".parse().unwrap()),
+        Verdict::Solved
+    ));
+    assert_eq!(run.game.turn, Team::Blue);
+}
+
+#[test]
+pub(crate) fn test_bad_puzzles() {
+    let error_line = |s: &str| match Puzzle::parse_all(s).unwrap_err() {
+        InvalidPuzzle::Syntax { line, .. } => line,
+        e => panic!("{e}"),
+    };
+    assert_eq!(error_line("goal win 1\n"), 1);
+    assert_eq!(error_line("puzzle A\ngoal lose 1\n"), 2);
+    // no solution.
+    assert_eq!(
+        error_line("puzzle A\n\npuzzle B\nsolution move 11 to 21\n"),
+        1
+    );
+    // one action from the starting position does not win.
+    assert_eq!(error_line("puzzle A\nsolution move 11 to 21\n"), 1);
+    assert_eq!(error_line("puzzle A\nsolution move 11 to 31\n"), 1);
+    // the game is lost before red's turn is survived.
+    assert_eq!(
+        error_line("puzzle A\ngoal survive 1\nsolution move 11 to 21; resign\n"),
+        1
+    );
+    // blue cannot have more power than stones.
+    assert_eq!(
+        error_line(
+            "puzzle A\nposition BBBBBBBBBB/BBBBBBBBBB/S.S....S.S/........../........../........../........../s.s....s.s/bbbbbbbbbb/bbbbbbbbbb blue 9 - ongoing 0\nsolution resign\n"
+        ),
+        1
+    );
+}
+
+#[test]
+pub(crate) fn test_generate_win() {
+    let board = "
+        S.........
+        .........G
+        ..........
+        ..........
+        ..........
+        .........s
+        ..........
+        ..........
+        ..........
+        b.........
+    ";
+    let generator = Generator::default();
+    let puzzle = generator.puzzle_at(&blue_to_move(board), &[]).unwrap();
+    assert_eq!(puzzle.goal, Goal::Win(1));
+    assert_eq!(puzzle.title, "The last stone");
+    assert_eq!(puzzle.solutions, [["move 19 to 59".parse().unwrap()]]);
+    // written out and read back, the puzzle is still valid.
+    assert_eq!(Puzzle::parse_all(&puzzle.to_string()).unwrap(), [puzzle]);
+
+    // with two generals, there are two ways to win.
+    let two_ways = board.replacen(".........G", "G........G", 1);
+    assert!(generator.puzzle_at(&blue_to_move(&two_ways), &[]).is_none());
+
+    // with two stones, blue can move a blank first and still win, so the puzzle would
+    // turn that away.
+    let spare_action = board.replacen("S.........", "SSB.......", 1);
+    assert!(generator
+        .puzzle_at(&blue_to_move(&spare_action), &[])
+        .is_none());
+}
+
+#[test]
+pub(crate) fn test_generate_defence() {
+    let hold_the_line = Puzzle::builtin()
+        .into_iter()
+        .find(|p| p.goal == Goal::Survive(1))
+        .unwrap();
+    let game = hold_the_line.start_game();
+    let played = hold_the_line.solutions[0].clone();
+    let puzzle = Generator::default().puzzle_at(&game, &played).unwrap();
+    assert_eq!(puzzle.goal, Goal::Survive(1));
+    // the reply is taken from the game.
+    assert_eq!(puzzle.solutions, [played]);
+    Puzzle::parse_all(&puzzle.to_string()).unwrap();
+}
+
+#[test]
+pub(crate) fn test_generate_from_records() {
+    let record = Record {
+        start: Some(
+            "S........./.........G/........../........../........../.........s/........../........../........../b......... red 1 - ongoing 0"
+                .parse()
+                .unwrap(),
+        ),
+        actions: vec!["move 90 to 80".parse().unwrap(), "move 19 to 59".parse().unwrap()],
+        ..Default::default()
+    };
+    let puzzles = Generator::default().generate(&[record]);
+    assert_eq!(puzzles.len(), 1);
+    assert_eq!(puzzles[0].title, "The last stone (game 1, turn 2)");
+    assert_eq!(puzzles[0].start.turn, Team::Blue);
+}
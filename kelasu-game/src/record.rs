@@ -0,0 +1,200 @@
+//! Game records, for saving and replaying whole games.
+//!
+//! A record is a list of `[Key "value"]` tags followed by one action per line,
+//! in the [`Move`] syntax. A file may hold several records one after another.
+//! Blank lines and lines starting with `#` are ignored.
+//!
+//! ```text
+//! [Blue "alpha"]
+//! [Red "beta"]
+//! [Result "blue"]
+//! move 11 to 21
+//! move 13 to 23
+//! ```
+//!
+//! Two tags have a meaning of their own:
+//! - `Start` is the [`Position`] the game started from. Without it, the game started from [`Game::new`].
+//! - `Result` is `blue`, `red`, `draw`, or `*` if the game is unfinished.
+
+use crate::{
+    board::{InvalidMove, Move, Position, Winner},
+    piece::Team,
+    Game,
+};
+use std::{fmt::Display, str::FromStr};
+
+use thiserror::Error;
+
+#[cfg(test)]
+mod tests;
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Record {
+    /// every tag other than `Start` and `Result`, in order.
+    pub tags: Vec<(String, String)>,
+    /// `None` is the starting position.
+    pub start: Option<Position>,
+    /// `None` if the game is unfinished.
+    pub result: Option<Winner>,
+    pub actions: Vec<Move>,
+}
+
+#[derive(Error, Debug)]
+#[error("line {line}: {message}")]
+pub struct InvalidRecord {
+    pub line: usize,
+    pub message: String,
+}
+
+impl InvalidRecord {
+    fn new(line: usize, message: impl ToString) -> Self {
+        Self {
+            line,
+            message: message.to_string(),
+        }
+    }
+}
+
+#[derive(Error, Debug)]
+#[error("action {index} (`{action}`) is illegal: {error}")]
+pub struct IllegalAction {
+    /// counted from 1.
+    pub index: usize,
+    pub action: Move,
+    pub error: InvalidMove,
+}
+
+impl Record {
+    pub fn tag(&self, key: &str) -> Option<&str> {
+        self.tags
+            .iter()
+            .find(|(k, _)| k == key)
+            .map(|(_, v)| v.as_str())
+    }
+
+    pub fn set_tag(&mut self, key: &str, value: impl ToString) {
+        let value = value.to_string();
+        match self.tags.iter_mut().find(|(k, _)| k == key) {
+            Some((_, v)) => *v = value,
+            None => self.tags.push((key.to_owned(), value)),
+        }
+    }
+
+    /// the game at the start of the record.
+    pub fn start_game(&self) -> Game {
+        self.start.clone().map_or_else(Game::new, Game::from)
+    }
+
+    /// plays every action of the record.
+    pub fn replay(&self) -> Result<Game, IllegalAction> {
+        let mut game = self.start_game();
+        for (i, action) in self.actions.iter().enumerate() {
+            let verified = game
+                .verify_action(action.clone())
+                .map_err(|error| IllegalAction {
+                    index: i + 1,
+                    action: action.clone(),
+                    error,
+                })?;
+            game.make_move(verified);
+        }
+        Ok(game)
+    }
+
+    /// every record in `s`, in order.
+    pub fn parse_all(s: &str) -> Result<Vec<Self>, InvalidRecord> {
+        let mut records = vec![];
+        // a new record starts with the first tag after an action or a `Result` tag.
+        let mut current: Option<(Self, bool)> = None;
+        for (i, line) in s.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let is_tag = line.starts_with('[');
+            if is_tag && current.as_ref().is_some_and(|(_, ended)| *ended) {
+                records.push(current.take().unwrap().0);
+            }
+            let (record, ended) = current.get_or_insert_with(Default::default);
+            if is_tag {
+                let (key, value) = parse_tag(line).ok_or_else(|| {
+                    InvalidRecord::new(i + 1, "tags are written as `[Key \"value\"]`.")
+                })?;
+                *ended = key == "Result";
+                record.apply_tag(key, value, i + 1)?;
+            } else {
+                let action = line.parse().map_err(|e| InvalidRecord::new(i + 1, e))?;
+                record.actions.push(action);
+                *ended = true;
+            }
+        }
+        records.extend(current.map(|(record, _)| record));
+        Ok(records)
+    }
+
+    fn apply_tag(&mut self, key: &str, value: String, line: usize) -> Result<(), InvalidRecord> {
+        match key {
+            "Start" => {
+                self.start = Some(value.parse().map_err(|e| InvalidRecord::new(line, e))?);
+            }
+            "Result" => {
+                self.result = match value.as_str() {
+                    "*" => None,
+                    "draw" => Some(Winner(None)),
+                    team => Some(Winner(Some(team.parse::<Team>().map_err(|_| {
+                        InvalidRecord::new(line, "results are `blue`, `red`, `draw` or `*`.")
+                    })?))),
+                }
+            }
+            _ => self.set_tag(key, value),
+        }
+        Ok(())
+    }
+}
+
+/// `[Key "value"]`, where the value may escape `"` and `\` with a `\`.
+fn parse_tag(line: &str) -> Option<(&str, String)> {
+    let inner = line.strip_prefix('[')?.strip_suffix(']')?;
+    let (key, value) = inner.split_once(' ')?;
+    let value = value.trim().strip_prefix('"')?.strip_suffix('"')?;
+    let mut unescaped = String::with_capacity(value.len());
+    let mut chars = value.chars();
+    while let Some(c) = chars.next() {
+        unescaped.push(if c == '\\' { chars.next()? } else { c });
+    }
+    Some((key, unescaped))
+}
+
+fn write_tag(f: &mut std::fmt::Formatter<'_>, key: &str, value: &str) -> std::fmt::Result {
+    let value = value.replace('\\', "\\\\").replace('"', "\\\"");
+    writeln!(f, "[{key} \"{value}\"]")
+}
+
+impl Display for Record {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for (key, value) in &self.tags {
+            write_tag(f, key, value)?;
+        }
+        if let Some(start) = &self.start {
+            write_tag(f, "Start", &start.to_string())?;
+        }
+        write_tag(f, "Result", self.result.map_or("*", Winner::name))?;
+        for action in &self.actions {
+            writeln!(f, "{action}")?;
+        }
+        Ok(())
+    }
+}
+
+/// exactly one record.
+impl FromStr for Record {
+    type Err = InvalidRecord;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match Self::parse_all(s)?.as_slice() {
+            [record] => Ok(record.clone()),
+            [] => Err(InvalidRecord::new(0, "there is no record.")),
+            _ => Err(InvalidRecord::new(0, "there is more than one record.")),
+        }
+    }
+}
@@ -0,0 +1,52 @@
+use super::*;
+
+const RECORDS: &str = r#"
+[Blue "alpha"]
+[Red "beta \"the second\""]
+[Result "blue"]
+move 11 to 21
+move 13 to 23
+
+# the second game
+[Blue "beta"]
+[Start "........../BBBBBBBBBB/S.S....S.S/........../........../........../........../s.s....s.s/bbbbbbbbbb/.......... blue 4 - ongoing 0"]
+[Result "*"]
+move 11 to 21
+"#;
+
+#[test]
+pub(crate) fn test_parse_records() {
+    let records = Record::parse_all(RECORDS).unwrap();
+    assert_eq!(records.len(), 2);
+    assert_eq!(records[0].tag("Red"), Some("beta \"the second\""));
+    assert_eq!(records[0].result, Some(Winner(Some(Team::Blue))));
+    assert_eq!(records[0].actions.len(), 2);
+    assert!(records[1].start.is_some());
+    assert_eq!(records[1].result, None);
+
+    for record in records {
+        assert_eq!(record.to_string().parse::<Record>().unwrap(), record);
+    }
+}
+
+#[test]
+pub(crate) fn test_replay() {
+    let records = Record::parse_all(RECORDS).unwrap();
+    let game = records[0].replay().unwrap();
+    assert_eq!(game.power, 2);
+
+    let mut record = records[1].clone();
+    record.actions.push("move 11 to 31".parse().unwrap());
+    let e = record.replay().unwrap_err();
+    assert_eq!(e.index, 2);
+    assert_eq!(e.action.to_string(), "move 11 to 31");
+}
+
+#[test]
+pub(crate) fn test_invalid_records() {
+    let err = |s: &str| Record::parse_all(s).unwrap_err().line;
+    assert_eq!(err("[Blue alpha]"), 1);
+    assert_eq!(err("[Result \"purple\"]"), 1);
+    assert_eq!(err("\n[Blue \"alpha\"]\nmove 11"), 3);
+    assert!("".parse::<Record>().is_err());
+}
@@ -0,0 +1,311 @@
+//! Draws boards as text, for terminals, chat and documentation.
+//!
+//! A [`Renderer`] has a [`Style`] and a set of overlays. Overlays that mark a tile
+//! put a fence on both sides of it:
+//!
+//! - `(X)` the cursor
+//! - `[X]` selected tiles
+//! - `<X>` legal destinations
+//! - `{X}` the tiles of the last action
+//!
+//! Two fenced tiles side by side share a `|` between them.
+//!
+//! Boards and games are displayed in colour through [`Coloured`], and told in words
+//! for screen readers by [`describe`].
+//!
+//! A [`Diagram`] draws boards as SVG or PNG images instead.
+
+use crate::{
+    board::{Board, Move, Pos},
+    piece::{Icon, Team},
+    Game,
+};
+use std::{fmt::Display, io::IsTerminal};
+
+mod describe;
+mod diagram;
+#[cfg(test)]
+mod tests;
+
+pub use describe::{describe, describe_tile, parse_tile_name, tile_name};
+pub use diagram::Diagram;
+
+const VICTORY_TILES: [Pos; 4] = [Pos(44), Pos(45), Pos(54), Pos(55)];
+
+// ANSI select graphic rendition codes.
+const RESET: &str = "\x1b[0m";
+const BOLD: &str = "1";
+const DIM: &str = "2";
+const UNDERLINE: &str = "4";
+const YELLOW: &str = "33";
+
+fn team_colour(team: Team) -> &'static str {
+    match team {
+        Team::Blue => "34",
+        Team::Red => "31",
+    }
+}
+
+/// whether standard output is a terminal that wants colour.
+///
+/// see <https://no-color.org> for `NO_COLOR`.
+pub fn colour_enabled() -> bool {
+    std::io::stdout().is_terminal() && std::env::var_os("NO_COLOR").is_none()
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Style {
+    /// numbered rows with a `|` between tiles, as [`Board`] is displayed.
+    #[default]
+    Plain,
+    /// the lettered frame of the README and the bot.
+    Box,
+    /// one character per tile, as [`Board::from_str`](std::str::FromStr::from_str) reads it.
+    ///
+    /// there is no room for fences, so only the pieces and the victory tiles are shown.
+    Compact,
+}
+
+/// the fences of a tile, strongest first.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+enum Fence {
+    Cursor,
+    Selected,
+    Destination,
+    LastMove,
+}
+
+impl Fence {
+    fn brackets(self) -> [char; 2] {
+        match self {
+            Fence::Cursor => ['(', ')'],
+            Fence::Selected => ['[', ']'],
+            Fence::Destination => ['<', '>'],
+            Fence::LastMove => ['{', '}'],
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Renderer {
+    pub style: Style,
+    pub selected: Vec<Pos>,
+    pub cursor: Option<Pos>,
+    /// the row being picked, drawn as a line across the board.
+    pub cursor_row: Option<i8>,
+    pub last_move: Option<Move>,
+    /// see [`Game::destinations`].
+    pub destinations: Vec<Pos>,
+    /// draws empty victory tiles as `:`.
+    pub victory_tiles: bool,
+    /// draws the power of the team to move on its side of the board, when rendering a [`Game`].
+    pub energy: bool,
+    /// colours the teams with ANSI escapes, dims the locked pieces of a [`Game`]
+    /// and underlines the victory tiles. [`Style::Compact`] is never coloured.
+    pub colour: bool,
+}
+
+impl Default for Renderer {
+    fn default() -> Self {
+        Self {
+            style: Style::default(),
+            selected: vec![],
+            cursor: None,
+            cursor_row: None,
+            last_move: None,
+            destinations: vec![],
+            victory_tiles: true,
+            energy: false,
+            colour: false,
+        }
+    }
+}
+
+impl Renderer {
+    pub fn new(style: Style) -> Self {
+        Self {
+            style,
+            ..Default::default()
+        }
+    }
+
+    fn fence(&self, pos: Pos) -> Option<Fence> {
+        let last_move = match &self.last_move {
+            Some(Move::Move { from, to }) => [*from, *to].contains(&pos),
+            Some(Move::Merge { pieces, .. }) => pieces.contains(&pos),
+            _ => false,
+        };
+        [
+            (self.cursor == Some(pos), Fence::Cursor),
+            (self.selected.contains(&pos), Fence::Selected),
+            (self.destinations.contains(&pos), Fence::Destination),
+            (last_move, Fence::LastMove),
+        ]
+        .into_iter()
+        .find_map(|(marked, fence)| marked.then_some(fence))
+    }
+
+    /// the character between tile `x - 1` and tile `x` of the row.
+    fn separator(&self, y: i8, x: i8, default: char) -> char {
+        let fence = |x: i8| {
+            (0..10)
+                .contains(&x)
+                .then(|| self.fence(Pos(y * 10 + x)))
+                .flatten()
+        };
+        match (fence(x - 1), fence(x)) {
+            (Some(Fence::Cursor), _) => ')',
+            (_, Some(Fence::Cursor)) => '(',
+            (Some(_), Some(_)) => '|',
+            (Some(left), None) => left.brackets()[1],
+            (None, Some(right)) => right.brackets()[0],
+            (None, None) => default,
+        }
+    }
+
+    fn tile(&self, board: &Board, pos: Pos, empty: char) -> char {
+        match board[pos].0 {
+            Some(piece) => piece.icon(),
+            None if self.victory_tiles && VICTORY_TILES.contains(&pos) => ':',
+            None => empty,
+        }
+    }
+
+    /// the tile, in colour if enabled.
+    fn paint(&self, board: &Board, pos: Pos, locked: &[Pos], empty: char) -> String {
+        let icon = self.tile(board, pos, empty);
+        if !self.colour {
+            return icon.to_string();
+        }
+        let mut codes = vec![];
+        if let Some(piece) = board[pos].0 {
+            codes.push(if locked.contains(&pos) { DIM } else { BOLD });
+            codes.push(team_colour(piece.team));
+        } else if icon == ':' {
+            codes.push(YELLOW);
+        }
+        if VICTORY_TILES.contains(&pos) {
+            codes.push(UNDERLINE);
+        }
+        if codes.is_empty() {
+            return icon.to_string();
+        }
+        format!("\x1b[{}m{icon}{RESET}", codes.join(";"))
+    }
+
+    /// the board alone, one line per row.
+    pub fn render_board(&self, board: &Board) -> String {
+        self.render_tiles(board, &[])
+    }
+
+    fn render_tiles(&self, board: &Board, locked: &[Pos]) -> String {
+        let mut out = String::new();
+        match self.style {
+            Style::Plain => {
+                out.push_str("   0 1 2 3 4 5 6 7 8 9\n");
+                for y in 0..10 {
+                    let held = self.cursor_row == Some(y);
+                    let [label, line, empty] = if held {
+                        ['=', '-', '.']
+                    } else {
+                        [' ', '|', ' ']
+                    };
+                    out.push_str(&format!("{y}{label}"));
+                    for x in 0..10 {
+                        out.push(self.separator(y, x, line));
+                        out.push_str(&self.paint(board, Pos(y * 10 + x), locked, empty));
+                    }
+                    out.push(self.separator(y, 10, line));
+                    out.push('\n');
+                }
+            }
+            Style::Box => {
+                out.push_str("   ╔[0-1-2-3-4-5-6-7-8-9]╗\n");
+                for (y, rank) in (0..10).zip('A'..='J') {
+                    let held = self.cursor_row == Some(y);
+                    let line = if held { *b"\"=.=\"" } else { *b"     " }.map(|b| b as char);
+                    out.extend([line[0], rank, line[1], '║']);
+                    for x in 0..10 {
+                        out.push(self.separator(y, x, if held { '-' } else { ' ' }));
+                        out.push_str(&self.paint(board, Pos(y * 10 + x), locked, line[2]));
+                    }
+                    out.push(self.separator(y, 10, if held { '-' } else { ' ' }));
+                    out.extend(['║', line[3], rank, line[4]]);
+                    out.truncate(out.trim_end().len());
+                    out.push('\n');
+                }
+                out.push_str("   ╚[0-1-2-3-4-5-6-7-8-9]╝\n");
+            }
+            Style::Compact => {
+                for y in 0..10 {
+                    out.extend((0..10).map(|x| self.tile(board, Pos(y * 10 + x), '.')));
+                    out.push('\n');
+                }
+            }
+        }
+        out
+    }
+
+    /// the board of the game, with the energy of the team to move on its side if enabled.
+    /// Blue is at the top.
+    pub fn render(&self, game: &Game) -> String {
+        let board = self.render_tiles(&game.board, &game.locked_tiles);
+        if !self.energy || self.style == Style::Compact {
+            return board;
+        }
+        let mut pips = " #".repeat(game.power as usize);
+        if self.colour {
+            pips = format!("\x1b[{BOLD};{}m{pips}{RESET}", team_colour(game.turn));
+        }
+        let energy = format!("Energy:{pips}\n");
+        match game.turn {
+            Team::Blue => format!("{energy}\n{board}"),
+            Team::Red => format!("{board}\n{energy}"),
+        }
+    }
+}
+
+/// displays a board or a game in colour, but only when [`colour_enabled`].
+///
+/// games are displayed as with [`Game`]'s `Display`, with the energy meter next to the board.
+#[derive(Debug, Clone, Copy)]
+pub struct Coloured<'a, T>(pub &'a T);
+
+impl Display for Coloured<'_, Board> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let renderer = Renderer {
+            colour: colour_enabled(),
+            ..Default::default()
+        };
+        f.write_str(&renderer.render_board(self.0))
+    }
+}
+
+impl Display for Coloured<'_, Game> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let game = self.0;
+        let renderer = Renderer {
+            energy: game.is_ongoing(),
+            colour: colour_enabled(),
+            ..Default::default()
+        };
+        writeln!(f, "{}", game.state)?;
+        writeln!(f, "{}'s turn.", game.turn)?;
+        writeln!(f, "Turns until a draw: {}.", game.turns_until_draw())?;
+        writeln!(f, "\n{}", renderer.render(game))
+    }
+}
+
+impl Board {
+    /// see [`Coloured`].
+    pub fn coloured(&self) -> Coloured<'_, Board> {
+        Coloured(self)
+    }
+}
+
+impl Game {
+    /// see [`Coloured`].
+    pub fn coloured(&self) -> Coloured<'_, Game> {
+        Coloured(self)
+    }
+}
@@ -0,0 +1,144 @@
+//! Positions in words, for screen readers.
+//!
+//! Tiles are named as on the box board: the row letter, then the column digit.
+//! `E4` is position 44.
+
+use super::VICTORY_TILES;
+use crate::{
+    board::{GameState, Pos, Winner},
+    piece::Piece,
+    Game,
+};
+use std::collections::BTreeMap;
+
+/// `D2` for position 32.
+pub fn tile_name(pos: Pos) -> String {
+    let [x, y] = pos.xy();
+    format!("{}{x}", (b'A' + y as u8) as char)
+}
+
+/// reads a tile name like `D2` or `d2`, or a position like `32`.
+pub fn parse_tile_name(s: &str) -> Option<Pos> {
+    let s = s.trim();
+    if let Ok(pos) = s.parse::<Pos>() {
+        return Some(pos);
+    }
+    match s.as_bytes() {
+        &[row, column] => {
+            let y = row.to_ascii_uppercase().checked_sub(b'A')?;
+            let x = (column as char).to_digit(10)?;
+            (y < 10).then_some(Pos(y as i8 * 10 + x as i8))
+        }
+        _ => None,
+    }
+}
+
+/// `Blue Warrior`.
+fn piece_name(piece: Piece) -> String {
+    format!("{} {:?}", piece.team, piece.kind)
+}
+
+/// `a, b and c`.
+fn list(items: &[String]) -> String {
+    match items {
+        [] => String::new(),
+        [item] => item.clone(),
+        [rest @ .., last] => format!("{} and {last}", rest.join(", ")),
+    }
+}
+
+/// what is on the tile, as `D2: Blue Warrior, locked.` or `E4: an empty victory tile.`
+pub fn describe_tile(game: &Game, pos: Pos) -> String {
+    let victory = VICTORY_TILES.contains(&pos);
+    let mut out = format!("{}: ", tile_name(pos));
+    match game.board[pos].0 {
+        Some(piece) => {
+            out += &piece_name(piece);
+            if game.locked_tiles.contains(&pos) {
+                out += ", locked";
+            }
+            if victory {
+                out += ", on a victory tile";
+            }
+        }
+        None if victory => out += "an empty victory tile",
+        None => out += "empty",
+    }
+    out + "."
+}
+
+/// the whole position, one line per fact:
+///
+/// - whose turn it is and their energy, or the result.
+/// - the pieces, grouped by team and kind, as `Blue Generals: E4, F7.`
+/// - the locked pieces.
+/// - who stands on the victory tiles.
+/// - how many turns are left until the game is drawn for lack of progress.
+pub fn describe(game: &Game) -> String {
+    let mut lines = vec![];
+    match game.state {
+        GameState::Ongoing { draw_offer } => {
+            let mut line = format!("{} to move, with {} energy.", game.turn, game.power);
+            if let Some(team) = draw_offer {
+                line += &format!(" {team} offers a draw.");
+            }
+            lines.push(line);
+        }
+        GameState::Finished(Winner(Some(team))) => lines.push(format!("{team} wins.")),
+        GameState::Finished(Winner(None)) => lines.push("The game is drawn.".to_owned()),
+    }
+
+    // blue before red, then in the order pieces grow into.
+    let mut groups = BTreeMap::<Piece, Vec<String>>::new();
+    for i in 0..100 {
+        if let Some(piece) = game.board[Pos(i)].0 {
+            groups.entry(piece).or_default().push(tile_name(Pos(i)));
+        }
+    }
+    for (piece, tiles) in groups {
+        let plural = if tiles.len() > 1 { "s" } else { "" };
+        lines.push(format!(
+            "{}{plural}: {}.",
+            piece_name(piece),
+            tiles.join(", ")
+        ));
+    }
+
+    let mut locked = game.locked_tiles.clone();
+    locked.sort();
+    let locked = locked
+        .into_iter()
+        .filter_map(|pos| {
+            let piece = game.board[pos].0?;
+            Some(format!("{} on {}", piece_name(piece), tile_name(pos)))
+        })
+        .collect::<Vec<_>>();
+    if locked.is_empty() {
+        lines.push("No pieces are locked.".to_owned());
+    } else {
+        lines.push(format!("Locked: {}.", list(&locked)));
+    }
+
+    let mut occupied = vec![];
+    let mut empty = vec![];
+    for pos in VICTORY_TILES {
+        match game.board[pos].0 {
+            Some(piece) => occupied.push(format!("{} on {}", piece_name(piece), tile_name(pos))),
+            None => empty.push(tile_name(pos)),
+        }
+    }
+    lines.push(match (&occupied[..], &empty[..]) {
+        ([], _) => "Victory tiles: all empty.".to_owned(),
+        (_, []) => format!("Victory tiles: {}.", list(&occupied)),
+        _ => format!(
+            "Victory tiles: {}; {} empty.",
+            list(&occupied),
+            list(&empty)
+        ),
+    });
+
+    if game.is_ongoing() {
+        lines.push(format!("Turns until a draw: {}.", game.turns_until_draw()));
+    }
+    lines.join("\n") + "\n"
+}
@@ -0,0 +1,566 @@
+use super::VICTORY_TILES;
+use crate::{
+    board::{Board, Move, Pos},
+    piece::{Icon, Team},
+};
+use std::fmt::Write;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Color(u8, u8, u8, u8);
+
+impl Color {
+    const LIGHT: Self = Self(240, 217, 181, 255);
+    const DARK: Self = Self(181, 136, 99, 255);
+    const VICTORY: Self = Self(226, 186, 64, 255);
+    const FRAME: Self = Self(62, 46, 36, 255);
+    const LABEL: Self = Self(232, 222, 204, 255);
+    const LAST_MOVE: Self = Self(196, 214, 90, 170);
+    const ARROW: Self = Self(38, 150, 64, 200);
+    const OUTLINE: Self = Self(28, 28, 28, 255);
+    const BLUE: Self = Self(52, 101, 164, 255);
+    const RED: Self = Self(190, 52, 52, 255);
+    const GLYPH: Self = Self(255, 255, 255, 255);
+
+    fn hex(self) -> String {
+        format!("#{:02x}{:02x}{:02x}", self.0, self.1, self.2)
+    }
+}
+
+/// what a diagram is drawn with, in pixels.
+#[derive(Debug, Clone, PartialEq)]
+enum Shape {
+    Rect {
+        x: f32,
+        y: f32,
+        w: f32,
+        h: f32,
+        color: Color,
+    },
+    Circle {
+        x: f32,
+        y: f32,
+        r: f32,
+        color: Color,
+    },
+    /// convex, in either winding.
+    Polygon { points: Vec<[f32; 2]>, color: Color },
+    /// `size` pixels tall, centred on `y`, and on `x` too if `centred`, or starting at it.
+    Text {
+        x: f32,
+        y: f32,
+        size: f32,
+        text: String,
+        centred: bool,
+        color: Color,
+    },
+}
+
+/// 5 by 7 pixel glyphs for images without fonts to draw with. lowercase letters are drawn as uppercase.
+#[cfg(any(feature = "gif", feature = "png"))]
+#[rustfmt::skip]
+fn font(glyph: char) -> [u8; 7] {
+    match glyph.to_ascii_uppercase() {
+        '0' => [0b01110, 0b10001, 0b10011, 0b10101, 0b11001, 0b10001, 0b01110],
+        '1' => [0b00100, 0b01100, 0b00100, 0b00100, 0b00100, 0b00100, 0b01110],
+        '2' => [0b01110, 0b10001, 0b00001, 0b00010, 0b00100, 0b01000, 0b11111],
+        '3' => [0b11110, 0b00001, 0b00001, 0b01110, 0b00001, 0b00001, 0b11110],
+        '4' => [0b00010, 0b00110, 0b01010, 0b10010, 0b11111, 0b00010, 0b00010],
+        '5' => [0b11111, 0b10000, 0b11110, 0b00001, 0b00001, 0b10001, 0b01110],
+        '6' => [0b00110, 0b01000, 0b10000, 0b11110, 0b10001, 0b10001, 0b01110],
+        '7' => [0b11111, 0b00001, 0b00010, 0b00100, 0b01000, 0b01000, 0b01000],
+        '8' => [0b01110, 0b10001, 0b10001, 0b01110, 0b10001, 0b10001, 0b01110],
+        '9' => [0b01110, 0b10001, 0b10001, 0b01111, 0b00001, 0b00010, 0b01100],
+        'A' => [0b01110, 0b10001, 0b10001, 0b11111, 0b10001, 0b10001, 0b10001],
+        'B' => [0b11110, 0b10001, 0b10001, 0b11110, 0b10001, 0b10001, 0b11110],
+        'C' => [0b01110, 0b10001, 0b10000, 0b10000, 0b10000, 0b10001, 0b01110],
+        'D' => [0b11100, 0b10010, 0b10001, 0b10001, 0b10001, 0b10010, 0b11100],
+        'E' => [0b11111, 0b10000, 0b10000, 0b11110, 0b10000, 0b10000, 0b11111],
+        'F' => [0b11111, 0b10000, 0b10000, 0b11110, 0b10000, 0b10000, 0b10000],
+        'G' => [0b01110, 0b10001, 0b10000, 0b10111, 0b10001, 0b10001, 0b01111],
+        'H' => [0b10001, 0b10001, 0b10001, 0b11111, 0b10001, 0b10001, 0b10001],
+        'I' => [0b01110, 0b00100, 0b00100, 0b00100, 0b00100, 0b00100, 0b01110],
+        'J' => [0b00111, 0b00010, 0b00010, 0b00010, 0b00010, 0b10010, 0b01100],
+        'K' => [0b10001, 0b10010, 0b10100, 0b11000, 0b10100, 0b10010, 0b10001],
+        'L' => [0b10000, 0b10000, 0b10000, 0b10000, 0b10000, 0b10000, 0b11111],
+        'M' => [0b10001, 0b11011, 0b10101, 0b10101, 0b10001, 0b10001, 0b10001],
+        'N' => [0b10001, 0b10001, 0b11001, 0b10101, 0b10011, 0b10001, 0b10001],
+        'O' => [0b01110, 0b10001, 0b10001, 0b10001, 0b10001, 0b10001, 0b01110],
+        'P' => [0b11110, 0b10001, 0b10001, 0b11110, 0b10000, 0b10000, 0b10000],
+        'Q' => [0b01110, 0b10001, 0b10001, 0b10001, 0b10101, 0b10010, 0b01101],
+        'R' => [0b11110, 0b10001, 0b10001, 0b11110, 0b10100, 0b10010, 0b10001],
+        'S' => [0b01111, 0b10000, 0b10000, 0b01110, 0b00001, 0b00001, 0b11110],
+        'T' => [0b11111, 0b00100, 0b00100, 0b00100, 0b00100, 0b00100, 0b00100],
+        'U' => [0b10001, 0b10001, 0b10001, 0b10001, 0b10001, 0b10001, 0b01110],
+        'V' => [0b10001, 0b10001, 0b10001, 0b10001, 0b10001, 0b01010, 0b00100],
+        'W' => [0b10001, 0b10001, 0b10001, 0b10101, 0b10101, 0b10101, 0b01010],
+        'X' => [0b10001, 0b10001, 0b01010, 0b00100, 0b01010, 0b10001, 0b10001],
+        'Y' => [0b10001, 0b10001, 0b01010, 0b00100, 0b00100, 0b00100, 0b00100],
+        'Z' => [0b11111, 0b00001, 0b00010, 0b00100, 0b01000, 0b10000, 0b11111],
+        '.' => [0b00000, 0b00000, 0b00000, 0b00000, 0b00000, 0b01100, 0b01100],
+        ',' => [0b00000, 0b00000, 0b00000, 0b00000, 0b01100, 0b00100, 0b01000],
+        ':' => [0b00000, 0b01100, 0b01100, 0b00000, 0b01100, 0b01100, 0b00000],
+        '-' => [0b00000, 0b00000, 0b00000, 0b11111, 0b00000, 0b00000, 0b00000],
+        '\'' => [0b00100, 0b00100, 0b01000, 0b00000, 0b00000, 0b00000, 0b00000],
+        '!' => [0b00100, 0b00100, 0b00100, 0b00100, 0b00100, 0b00000, 0b00100],
+        '(' => [0b00010, 0b00100, 0b01000, 0b01000, 0b01000, 0b00100, 0b00010],
+        ')' => [0b01000, 0b00100, 0b00010, 0b00010, 0b00010, 0b00100, 0b01000],
+        '/' => [0b00001, 0b00010, 0b00010, 0b00100, 0b01000, 0b01000, 0b10000],
+        _ => [0; 7],
+    }
+}
+
+/// rounded for writing, since SVG viewers are not that precise anyway.
+fn tenths(v: f32) -> f32 {
+    (v * 10.0).round() / 10.0
+}
+
+fn team_color(team: Team) -> Color {
+    match team {
+        Team::Blue => Color::BLUE,
+        Team::Red => Color::RED,
+    }
+}
+
+/// the text in lines of at most `width` characters, broken between words.
+fn wrap(text: &str, width: usize) -> Vec<String> {
+    let mut lines: Vec<String> = vec![];
+    for word in text.split_whitespace() {
+        match lines.last_mut() {
+            Some(line) if line.len() + 1 + word.len() <= width => {
+                line.push(' ');
+                line.push_str(word);
+            }
+            _ => lines.push(word.to_owned()),
+        }
+    }
+    lines
+}
+
+/// draws a board as an image, with Blue at the top.
+///
+/// pieces are discs in their team's colour, marked with the uppercase letter of their kind.
+/// the caption and the energy go in a strip below the board.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Diagram {
+    /// in pixels.
+    pub tile_size: u32,
+    /// numbers the columns and letters the rows around the board.
+    pub coordinates: bool,
+    /// tints the tiles the action touched.
+    pub last_move: Option<Move>,
+    pub arrows: Vec<(Pos, Pos)>,
+    pub victory_tiles: bool,
+    /// wrapped onto at most two lines.
+    pub caption: Option<String>,
+    /// the team to move and its power, drawn as a row of pips in its colour.
+    pub energy: Option<(Team, u8)>,
+}
+
+impl Default for Diagram {
+    fn default() -> Self {
+        Self {
+            tile_size: 48,
+            coordinates: true,
+            last_move: None,
+            arrows: vec![],
+            victory_tiles: true,
+            caption: None,
+            energy: None,
+        }
+    }
+}
+
+impl Diagram {
+    /// how many pixels tall caption text is, in multiples of the font.
+    const CAPTION_SCALE: f32 = 2.0;
+
+    fn margin(&self) -> f32 {
+        if self.coordinates {
+            self.tile_size as f32 / 2.0
+        } else {
+            0.0
+        }
+    }
+
+    fn strip(&self) -> u32 {
+        if self.caption.is_some() || self.energy.is_some() {
+            self.tile_size * 3 / 2
+        } else {
+            0
+        }
+    }
+
+    /// the width and height of the image, in pixels.
+    pub fn size(&self) -> [u32; 2] {
+        let width = self.tile_size * 10 + 2 * self.margin() as u32;
+        [width, width + self.strip()]
+    }
+
+    /// the top left corner of the tile.
+    fn corner(&self, Pos(p): Pos) -> [f32; 2] {
+        let tile = self.tile_size as f32;
+        [p % 10, p / 10].map(|i| self.margin() + i as f32 * tile)
+    }
+
+    fn centre(&self, pos: Pos) -> [f32; 2] {
+        self.corner(pos).map(|i| i + self.tile_size as f32 / 2.0)
+    }
+
+    fn shapes(&self, board: &Board) -> Vec<Shape> {
+        let tile = self.tile_size as f32;
+        let [width, height] = self.size().map(|i| i as f32);
+        let board_size = width;
+        let mut shapes = vec![Shape::Rect {
+            x: 0.0,
+            y: 0.0,
+            w: width,
+            h: height,
+            color: Color::FRAME,
+        }];
+
+        if self.coordinates {
+            let labels = ('0'..='9').zip('A'..='J').enumerate();
+            for (i, (file, rank)) in labels {
+                let along = self.margin() + (i as f32 + 0.5) * tile;
+                for across in [self.margin() / 2.0, board_size - self.margin() / 2.0] {
+                    for (x, y, glyph) in [(along, across, file), (across, along, rank)] {
+                        shapes.push(Shape::Text {
+                            x,
+                            y,
+                            size: tile * 0.3,
+                            text: glyph.to_string(),
+                            centred: true,
+                            color: Color::LABEL,
+                        });
+                    }
+                }
+            }
+        }
+
+        let touched = match &self.last_move {
+            Some(Move::Move { from, to }) => vec![*from, *to],
+            Some(Move::Merge { pieces, .. }) => pieces.clone(),
+            _ => vec![],
+        };
+        for pos in (0..100).map(Pos) {
+            let [x, y] = self.corner(pos);
+            let [i, j] = pos.xy();
+            let color = if self.victory_tiles && VICTORY_TILES.contains(&pos) {
+                Color::VICTORY
+            } else if (i + j) % 2 == 0 {
+                Color::LIGHT
+            } else {
+                Color::DARK
+            };
+            shapes.push(Shape::Rect {
+                x,
+                y,
+                w: tile,
+                h: tile,
+                color,
+            });
+            if touched.contains(&pos) {
+                shapes.push(Shape::Rect {
+                    x,
+                    y,
+                    w: tile,
+                    h: tile,
+                    color: Color::LAST_MOVE,
+                });
+            }
+        }
+
+        for (pos, piece) in (0..100).filter_map(|p| board[Pos(p)].0.map(|piece| (Pos(p), piece))) {
+            let [x, y] = self.centre(pos);
+            shapes.push(Shape::Circle {
+                x,
+                y,
+                r: tile * 0.42,
+                color: Color::OUTLINE,
+            });
+            shapes.push(Shape::Circle {
+                x,
+                y,
+                r: tile * 0.37,
+                color: team_color(piece.team),
+            });
+            shapes.push(Shape::Text {
+                x,
+                y,
+                size: tile * 0.44,
+                text: piece.kind.icon().to_ascii_uppercase().to_string(),
+                centred: true,
+                color: Color::GLYPH,
+            });
+        }
+
+        for &(from, to) in &self.arrows {
+            let [a, b] = [self.centre(from), self.centre(to)];
+            let length = ((b[0] - a[0]).powi(2) + (b[1] - a[1]).powi(2)).sqrt();
+            if length == 0.0 {
+                continue;
+            }
+            let d = [(b[0] - a[0]) / length, (b[1] - a[1]) / length];
+            let n = [-d[1], d[0]];
+            let at = |p: [f32; 2], along: f32, side: f32| {
+                [
+                    p[0] + d[0] * along + n[0] * side,
+                    p[1] + d[1] * along + n[1] * side,
+                ]
+            };
+            let [shaft, head] = [tile * 0.07, tile * 0.2];
+            let base = -(tile * 0.35).min(length);
+            shapes.push(Shape::Polygon {
+                points: vec![
+                    at(a, 0.0, shaft),
+                    at(b, base, shaft),
+                    at(b, base, -shaft),
+                    at(a, 0.0, -shaft),
+                ],
+                color: Color::ARROW,
+            });
+            shapes.push(Shape::Polygon {
+                points: vec![at(b, base, head), at(b, 0.0, 0.0), at(b, base, -head)],
+                color: Color::ARROW,
+            });
+        }
+
+        // the strip: a row of pips, then the caption.
+        let left = self.margin().max(tile / 4.0);
+        if let Some((team, power)) = self.energy {
+            for i in 0..power {
+                shapes.push(Shape::Circle {
+                    x: left + tile * (0.15 + 0.4 * i as f32),
+                    y: board_size + tile * 0.25,
+                    r: tile * 0.14,
+                    color: team_color(team),
+                });
+            }
+        }
+        if let Some(caption) = &self.caption {
+            let size = Self::CAPTION_SCALE * 7.0;
+            let advance = Self::CAPTION_SCALE * 6.0;
+            let lines = wrap(caption, ((width - 2.0 * left) / advance) as usize);
+            for (i, line) in lines.into_iter().take(2).enumerate() {
+                shapes.push(Shape::Text {
+                    x: left,
+                    y: board_size + tile * (0.75 + 0.45 * i as f32),
+                    size,
+                    text: line,
+                    centred: false,
+                    color: Color::LABEL,
+                });
+            }
+        }
+
+        shapes
+    }
+
+    /// the diagram as an SVG document.
+    pub fn svg(&self, board: &Board) -> String {
+        let [width, height] = self.size();
+        let mut out = format!(
+            "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{width}\" height=\"{height}\" viewBox=\"0 0 {width} {height}\">\n"
+        );
+        for shape in self.shapes(board) {
+            let fill = |color: Color| {
+                let mut fill = format!("fill=\"{}\"", color.hex());
+                if color.3 != 255 {
+                    fill += &format!(" fill-opacity=\"{:.2}\"", color.3 as f32 / 255.0);
+                }
+                fill
+            };
+            // writing to a string cannot fail.
+            let _ = match shape {
+                Shape::Rect { x, y, w, h, color } => {
+                    let [x, y, w, h] = [x, y, w, h].map(tenths);
+                    writeln!(
+                        out,
+                        "<rect x=\"{x}\" y=\"{y}\" width=\"{w}\" height=\"{h}\" {}/>",
+                        fill(color)
+                    )
+                }
+                Shape::Circle { x, y, r, color } => {
+                    let [x, y, r] = [x, y, r].map(tenths);
+                    writeln!(
+                        out,
+                        "<circle cx=\"{x}\" cy=\"{y}\" r=\"{r}\" {}/>",
+                        fill(color)
+                    )
+                }
+                Shape::Polygon { points, color } => {
+                    let points = points
+                        .iter()
+                        .map(|[x, y]| format!("{},{}", tenths(*x), tenths(*y)))
+                        .collect::<Vec<_>>();
+                    writeln!(
+                        out,
+                        "<polygon points=\"{}\" {}/>",
+                        points.join(" "),
+                        fill(color)
+                    )
+                }
+                Shape::Text {
+                    x,
+                    y,
+                    size,
+                    text,
+                    centred,
+                    color,
+                } => {
+                    let [x, y, size] = [x, y, size].map(tenths);
+                    let anchor = if centred { "middle" } else { "start" };
+                    let text = text
+                        .replace('&', "&amp;")
+                        .replace('<', "&lt;")
+                        .replace('>', "&gt;");
+                    writeln!(
+                        out,
+                        "<text x=\"{x}\" y=\"{y}\" font-size=\"{size}\" font-family=\"sans-serif\" font-weight=\"bold\" \
+                        text-anchor=\"{anchor}\" dominant-baseline=\"central\" {}>{text}</text>",
+                        fill(color)
+                    )
+                }
+            };
+        }
+        out.push_str("</svg>\n");
+        out
+    }
+
+    /// the diagram as RGB pixels, row by row.
+    #[cfg(any(feature = "gif", feature = "png"))]
+    pub(crate) fn pixels(&self, board: &Board) -> Vec<u8> {
+        let [width, height] = self.size();
+        let mut canvas = Canvas::new(width, height);
+        for shape in self.shapes(board) {
+            canvas.draw(&shape);
+        }
+        canvas.pixels
+    }
+
+    /// the diagram as a PNG file.
+    #[cfg(feature = "png")]
+    pub fn png(&self, board: &Board) -> Vec<u8> {
+        let [width, height] = self.size();
+        let mut out = vec![];
+        let mut encoder = png::Encoder::new(&mut out, width, height);
+        encoder.set_color(png::ColorType::Rgb);
+        encoder.set_depth(png::BitDepth::Eight);
+        // encoding into memory cannot fail.
+        encoder
+            .write_header()
+            .and_then(|mut writer| writer.write_image_data(&self.pixels(board)))
+            .expect("PNG encoding into memory failed");
+        out
+    }
+}
+
+/// RGB pixels, drawn on with four by four samples per pixel.
+#[cfg(any(feature = "gif", feature = "png"))]
+struct Canvas {
+    width: u32,
+    height: u32,
+    pixels: Vec<u8>,
+}
+
+#[cfg(any(feature = "gif", feature = "png"))]
+impl Canvas {
+    const SAMPLES: u32 = 4;
+
+    fn new(width: u32, height: u32) -> Self {
+        Self {
+            width,
+            height,
+            pixels: vec![255; (width * height * 3) as usize],
+        }
+    }
+
+    /// blends the colour into every pixel of the bounds, as much as `inside` covers it.
+    fn fill(
+        &mut self,
+        [x0, y0, x1, y1]: [f32; 4],
+        color: Color,
+        inside: impl Fn(f32, f32) -> bool,
+    ) {
+        let clamp = |v: f32, max: u32| (v.max(0.0) as u32).min(max);
+        let step = 1.0 / Self::SAMPLES as f32;
+        for py in clamp(y0.floor(), self.height)..clamp(y1.ceil(), self.height) {
+            for px in clamp(x0.floor(), self.width)..clamp(x1.ceil(), self.width) {
+                let mut covered = 0;
+                for sy in 0..Self::SAMPLES {
+                    for sx in 0..Self::SAMPLES {
+                        let x = px as f32 + (sx as f32 + 0.5) * step;
+                        let y = py as f32 + (sy as f32 + 0.5) * step;
+                        covered += inside(x, y) as u32;
+                    }
+                }
+                if covered == 0 {
+                    continue;
+                }
+                let alpha = covered * color.3 as u32 / (Self::SAMPLES * Self::SAMPLES);
+                let i = ((py * self.width + px) * 3) as usize;
+                for (pixel, channel) in self.pixels[i..i + 3]
+                    .iter_mut()
+                    .zip([color.0, color.1, color.2])
+                {
+                    *pixel = ((*pixel as u32 * (255 - alpha) + channel as u32 * alpha) / 255) as u8;
+                }
+            }
+        }
+    }
+
+    fn draw(&mut self, shape: &Shape) {
+        match *shape {
+            Shape::Rect { x, y, w, h, color } => {
+                self.fill([x, y, x + w, y + h], color, |px, py| {
+                    (x..x + w).contains(&px) && (y..y + h).contains(&py)
+                })
+            }
+            Shape::Circle { x, y, r, color } => {
+                self.fill([x - r, y - r, x + r, y + r], color, |px, py| {
+                    (px - x).powi(2) + (py - y).powi(2) <= r * r
+                })
+            }
+            Shape::Polygon { ref points, color } => {
+                let bounds = points.iter().fold(
+                    [f32::MAX, f32::MAX, f32::MIN, f32::MIN],
+                    |[x0, y0, x1, y1], &[x, y]| [x0.min(x), y0.min(y), x1.max(x), y1.max(y)],
+                );
+                self.fill(bounds, color, |px, py| {
+                    let sides = points
+                        .iter()
+                        .zip(points.iter().cycle().skip(1))
+                        .map(|(a, b)| (b[0] - a[0]) * (py - a[1]) - (b[1] - a[1]) * (px - a[0]));
+                    let sides = sides.collect::<Vec<_>>();
+                    sides.iter().all(|&s| s >= 0.0) || sides.iter().all(|&s| s <= 0.0)
+                })
+            }
+            Shape::Text {
+                x,
+                y,
+                size,
+                ref text,
+                centred,
+                color,
+            } => {
+                let scale = (size / 7.0).round().max(1.0);
+                let width = (text.chars().count() * 6).saturating_sub(1) as f32 * scale;
+                let left = if centred { x - width / 2.0 } else { x }.round();
+                let top = (y - 3.5 * scale).round();
+                for (i, glyph) in text.chars().enumerate() {
+                    for (row, bits) in font(glyph).into_iter().enumerate() {
+                        for column in (0..5).filter(|c| bits & (0b10000 >> c) != 0) {
+                            self.draw(&Shape::Rect {
+                                x: left + (i * 6 + column) as f32 * scale,
+                                y: top + row as f32 * scale,
+                                w: scale,
+                                h: scale,
+                                color,
+                            });
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
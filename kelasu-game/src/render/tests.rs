@@ -0,0 +1,215 @@
+use super::*;
+use crate::piece::Tile;
+
+#[test]
+pub(crate) fn test_plain() {
+    let game = Game::new();
+    let expected = "   0 1 2 3 4 5 6 7 8 9\n\
+                    0 |B|B|B|B|B|B|B|B|B|B|\n\
+                    1 |B|B|B|B|B|B|B|B|B|B|\n\
+                    2 |S| |S| | | | |S| |S|\n\
+                    3 | | | | | | | | | | |\n\
+                    4 | | | | |:|:| | | | |\n\
+                    5 | | | | |:|:| | | | |\n\
+                    6 | | | | | | | | | | |\n\
+                    7 |s| |s| | | | |s| |s|\n\
+                    8 |b|b|b|b|b|b|b|b|b|b|\n\
+                    9 |b|b|b|b|b|b|b|b|b|b|\n";
+    assert_eq!(game.board.to_string(), expected);
+    assert_eq!(Renderer::default().render(&game), expected);
+
+    let mut board = Board::new();
+    board[Pos(22)] = Tile::try_from('W').unwrap();
+    let game = Game::from_position(Team::Blue, board);
+    let renderer = Renderer {
+        destinations: game.destinations(Pos(22)),
+        cursor: Some(Pos(22)),
+        cursor_row: Some(3),
+        ..Default::default()
+    };
+    let rendered = renderer.render_board(&game.board);
+    assert_eq!(rendered.lines().nth(3), Some("2 |S< (W) > | | |S| |S|"));
+    assert_eq!(rendered.lines().nth(4), Some("3=-.-.<.>.-.-.-.-.-.-.-"));
+}
+
+#[test]
+pub(crate) fn test_box() {
+    let mut game = Game::new();
+    let renderer = Renderer {
+        style: Style::Box,
+        selected: vec![Pos(0), Pos(1), Pos(9)],
+        cursor: Some(Pos(2)),
+        cursor_row: Some(3),
+        energy: true,
+        ..Default::default()
+    };
+    assert_eq!(
+        renderer.render(&game),
+        "Energy: # # # #\n\
+         \n   ╔[0-1-2-3-4-5-6-7-8-9]╗\n\
+         \x20A ║[B|B(B)B B B B B B[B]║ A\n\
+         \x20B ║ B B B B B B B B B B ║ B\n\
+         \x20C ║ S   S         S   S ║ C\n\
+         \"D=║-.-.-.-.-.-.-.-.-.-.-║=D\"\n\
+         \x20E ║         : :         ║ E\n\
+         \x20F ║         : :         ║ F\n\
+         \x20G ║                     ║ G\n\
+         \x20H ║ s   s         s   s ║ H\n\
+         \x20I ║ b b b b b b b b b b ║ I\n\
+         \x20J ║ b b b b b b b b b b ║ J\n\
+         \x20  ╚[0-1-2-3-4-5-6-7-8-9]╝\n"
+    );
+
+    // red's energy goes below the board.
+    game.turn = Team::Red;
+    game.power = 2;
+    let renderer = Renderer {
+        style: Style::Box,
+        last_move: Some("move 17 to 37".parse().unwrap()),
+        energy: true,
+        ..Default::default()
+    };
+    let rendered = renderer.render(&game);
+    assert!(rendered.ends_with("╝\n\nEnergy: # #\n"));
+    assert_eq!(
+        rendered.lines().nth(2),
+        Some(" B ║ B B B B B B B{B}B B ║ B")
+    );
+    assert_eq!(
+        rendered.lines().nth(4),
+        Some(" D ║              { }    ║ D")
+    );
+}
+
+#[test]
+pub(crate) fn test_compact() {
+    let game = Game::new();
+    let renderer = Renderer {
+        selected: vec![Pos(0)],
+        energy: true,
+        ..Renderer::new(Style::Compact)
+    };
+    let rendered = renderer.render(&game);
+    assert_eq!(rendered.lines().next(), Some("BBBBBBBBBB"));
+    assert_eq!(rendered.lines().nth(4), Some("....::...."));
+    assert_eq!(rendered.parse::<Board>().unwrap(), game.board);
+
+    let renderer = Renderer {
+        victory_tiles: false,
+        ..Renderer::new(Style::Compact)
+    };
+    assert_eq!(
+        renderer.render_board(&game.board).lines().nth(4),
+        Some("..........")
+    );
+}
+
+#[test]
+pub(crate) fn test_svg() {
+    let board = Board::new();
+    let diagram = Diagram {
+        last_move: Some("move 17 to 37".parse().unwrap()),
+        arrows: vec![(Pos(17), Pos(37))],
+        ..Default::default()
+    };
+    let svg = diagram.svg(&board);
+    assert!(svg.starts_with("<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"528\""));
+    assert!(svg.ends_with("</svg>\n"));
+    let pieces = board.tiles.iter().filter(|t| t.0.is_some()).count();
+    // an outline and a disc for every piece.
+    assert_eq!(svg.matches("<circle").count(), 2 * pieces);
+    // every piece and 10 labels on each side.
+    assert_eq!(svg.matches("<text").count(), pieces + 40);
+    // the frame, the tiles and the two tinted tiles.
+    assert_eq!(svg.matches("<rect").count(), 1 + 100 + 2);
+    assert_eq!(svg.matches("fill-opacity").count(), 2 + 2);
+    assert_eq!(svg.matches("<polygon").count(), 2);
+
+    let plain = Diagram {
+        coordinates: false,
+        ..Default::default()
+    };
+    assert_eq!(plain.size(), [480, 480]);
+    assert_eq!(plain.svg(&board).matches("<text").count(), pieces);
+}
+
+#[cfg(feature = "png")]
+#[test]
+pub(crate) fn test_png() {
+    let diagram = Diagram {
+        coordinates: false,
+        last_move: Some("move 17 to 33".parse().unwrap()),
+        ..Default::default()
+    };
+    let png = diagram.png(&Board::new());
+    let mut reader = png::Decoder::new(png.as_slice()).read_info().unwrap();
+    let mut pixels = vec![0; reader.output_buffer_size()];
+    let info = reader.next_frame(&mut pixels).unwrap();
+    assert_eq!([info.width, info.height], [480, 480]);
+
+    let pixel = |x: usize, y: usize| &pixels[(y * 480 + x) * 3..][..3];
+    // the middle of tiles 44 and 34, and the corner of tile 33.
+    assert_eq!(pixel(4 * 48 + 24, 4 * 48 + 24), [226, 186, 64]);
+    assert_eq!(pixel(4 * 48 + 24, 3 * 48 + 24), [181, 136, 99]);
+    assert_ne!(pixel(3 * 48 + 2, 3 * 48 + 2), [240, 217, 181]);
+    // a blue piece on tile 00, drawn over a light tile.
+    assert_eq!(pixel(24, 24 - 14), [52, 101, 164]);
+    assert_eq!(pixel(1, 1), [240, 217, 181]);
+}
+
+#[test]
+pub(crate) fn test_colour() {
+    let mut game = Game::new();
+    game.locked_tiles.push(Pos(20));
+    let renderer = Renderer {
+        colour: true,
+        energy: true,
+        ..Default::default()
+    };
+    let rendered = renderer.render(&game);
+    let lines = rendered.lines().collect::<Vec<_>>();
+    assert_eq!(lines[0], "Energy:\x1b[1;34m # # # #\x1b[0m");
+    assert!(lines[5].starts_with("2 |\x1b[2;34mS\x1b[0m| |\x1b[1;34mS\x1b[0m|"));
+    assert!(lines[7].contains("|\x1b[33;4m:\x1b[0m|"));
+    assert!(lines[10].starts_with("7 |\x1b[1;31ms\x1b[0m|"));
+
+    // compact boards and renderers without colour have no escapes.
+    let compact = Renderer {
+        style: Style::Compact,
+        ..renderer
+    };
+    assert!(!compact.render(&game).contains('\x1b'));
+    assert!(!Renderer::default().render(&game).contains('\x1b'));
+}
+
+#[test]
+pub(crate) fn test_describe() {
+    assert_eq!(tile_name(Pos(32)), "D2");
+    assert_eq!(parse_tile_name("d2"), Some(Pos(32)));
+    assert_eq!(parse_tile_name("32"), Some(Pos(32)));
+    assert_eq!(parse_tile_name("K2"), None);
+
+    let mut board = Board::new();
+    board[Pos(44)] = Tile::try_from('G').unwrap();
+    board[Pos(57)] = Tile::try_from('G').unwrap();
+    board[Pos(32)] = Tile::try_from('w').unwrap();
+    let mut game = Game::from_position(Team::Red, board);
+    game.locked_tiles.push(Pos(32));
+    let described = describe(&game);
+    let lines = described.lines().collect::<Vec<_>>();
+    assert_eq!(lines[0], "Red to move, with 4 energy.");
+    assert!(lines.contains(&"Blue Generals: E4, F7."));
+    assert!(lines.contains(&"Red Warrior: D2."));
+    assert!(lines.contains(&"Locked: Red Warrior on D2."));
+    assert!(lines.contains(&"Victory tiles: Blue General on E4; E5, F4 and F5 empty."));
+    assert!(lines.last().unwrap().starts_with("Turns until a draw: "));
+
+    assert_eq!(describe_tile(&game, Pos(32)), "D2: Red Warrior, locked.");
+    assert_eq!(
+        describe_tile(&game, Pos(44)),
+        "E4: Blue General, on a victory tile."
+    );
+    assert_eq!(describe_tile(&game, Pos(55)), "F5: an empty victory tile.");
+    assert_eq!(describe_tile(&game, Pos(0)), "A0: Blue Blank.");
+    assert_eq!(describe_tile(&game, Pos(35)), "D5: empty.");
+}
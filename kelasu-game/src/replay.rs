@@ -0,0 +1,179 @@
+//! Plays a game record back as an animation, one frame per action.
+//!
+//! Every frame is a [`Diagram`] of the board after the action, with the action highlighted,
+//! the energy left for the team to move, and a caption from [`narrate`].
+
+use crate::{
+    board::{GameState, Move, Pos, Winner},
+    piece::PieceKind,
+    record::{IllegalAction, Record},
+    render::Diagram,
+    Game,
+};
+
+#[cfg(test)]
+mod tests;
+
+/// `a Red Runner`.
+fn piece_name(game: &Game, pos: Pos) -> String {
+    match game.board[pos].0 {
+        Some(piece) => format!("a {} {:?}", piece.team, piece.kind),
+        None => "nothing".to_owned(),
+    }
+}
+
+/// what the action did, and what happened because of it, as a sentence or two.
+///
+/// `before` is the game the action was played in, and `after` the game after it.
+pub fn narrate(before: &Game, action: &Move, after: &Game) -> String {
+    let team = before.turn;
+    let mut out = match action {
+        Move::Resign => format!("{team} resigns."),
+        Move::Draw if after.state == GameState::Finished(Winner(None)) => {
+            format!("{team} accepts the draw.")
+        }
+        Move::Draw => format!("{team} offers a draw."),
+        Move::DeclineDraw => format!("{team} declines the draw."),
+        &Move::Move { from, to } => {
+            let kind = before.board[from].0.map_or(PieceKind::Blank, |p| p.kind);
+            // diplomats convert diagonally, and are used up doing it.
+            let diagonal = from.dir_to(to).is_some_and(|(dir, _)| !dir.contains(&0));
+            match before.board[to].0 {
+                Some(_) if kind == PieceKind::Diplomat && diagonal => format!(
+                    "{team} {kind:?} {:02} converts {} on {:02}.",
+                    from.0,
+                    piece_name(before, to),
+                    to.0
+                ),
+                Some(_) => format!(
+                    "{team} {kind:?} {:02} takes {} on {:02}.",
+                    from.0,
+                    piece_name(before, to),
+                    to.0
+                ),
+                None => format!("{team} {kind:?} {:02} to {:02}.", from.0, to.0),
+            }
+        }
+        Move::Merge { kind, pieces } => format!(
+            "{team} merges {} pieces into a {kind:?} on {:02}.",
+            pieces.len(),
+            pieces.last().map_or(0, |p| p.0)
+        ),
+    };
+
+    let on_board = matches!(action, Move::Move { .. } | Move::Merge { .. });
+    match after.state {
+        GameState::Finished(Winner(Some(winner))) if on_board => {
+            out += &format!(" {winner} wins.");
+        }
+        GameState::Finished(Winner(None)) if on_board => out += " The game is drawn.",
+        GameState::Ongoing { .. } if after.turn != team => {
+            out += &format!(" {} to move.", after.turn);
+        }
+        _ => {}
+    }
+    out
+}
+
+/// how a record is drawn and timed.
+#[derive(Debug, Clone)]
+pub struct Replay {
+    /// the look of every frame. its overlays are replaced for each action.
+    pub diagram: Diagram,
+    /// how long each frame shows, in hundredths of a second.
+    pub delay: u16,
+    /// how long the last frame shows before the animation starts again.
+    pub end_delay: u16,
+}
+
+impl Default for Replay {
+    fn default() -> Self {
+        Self {
+            diagram: Diagram::default(),
+            delay: 150,
+            end_delay: 500,
+        }
+    }
+}
+
+impl Replay {
+    /// a diagram for every frame, with the board it shows.
+    /// the first frame is the starting position.
+    pub fn frames(&self, record: &Record) -> Result<Vec<(Diagram, Game)>, IllegalAction> {
+        let mut game = record.start_game();
+        let title = match (record.tag("Blue"), record.tag("Red")) {
+            (Some(blue), Some(red)) => format!("{blue} (Blue) against {red} (Red)."),
+            _ => "The start of the game.".to_owned(),
+        };
+        let energy = |game: &Game| game.is_ongoing().then_some((game.turn, game.power));
+
+        let mut frames = vec![(
+            Diagram {
+                caption: Some(title),
+                energy: energy(&game),
+                ..self.diagram.clone()
+            },
+            game.clone(),
+        )];
+        let mut turn = 1;
+        for (i, action) in record.actions.iter().enumerate() {
+            let verified = game
+                .verify_action(action.clone())
+                .map_err(|error| IllegalAction {
+                    index: i + 1,
+                    action: action.clone(),
+                    error,
+                })?;
+            let before = game.clone();
+            game.make_move(verified);
+
+            let arrows = match *action {
+                Move::Move { from, to } => vec![(from, to)],
+                _ => vec![],
+            };
+            let diagram = Diagram {
+                last_move: Some(action.clone()),
+                arrows,
+                caption: Some(format!("{turn}. {}", narrate(&before, action, &game))),
+                energy: energy(&game),
+                ..self.diagram.clone()
+            };
+            frames.push((diagram, game.clone()));
+            if game.turn != before.turn {
+                turn += 1;
+            }
+        }
+        Ok(frames)
+    }
+
+    /// the record as an animated GIF that loops forever.
+    #[cfg(feature = "gif")]
+    pub fn gif(&self, record: &Record) -> Result<Vec<u8>, IllegalAction> {
+        let frames = self.frames(record)?;
+        // every frame has a caption, so they all have the same size.
+        let [width, height] = frames[0].0.size().map(|i| i as u16);
+        let mut out = vec![];
+        {
+            // encoding into memory cannot fail.
+            let mut encoder = gif::Encoder::new(&mut out, width, height, &[])
+                .expect("GIF encoding into memory failed");
+            encoder
+                .set_repeat(gif::Repeat::Infinite)
+                .expect("GIF encoding into memory failed");
+            let last = frames.len() - 1;
+            for (i, (diagram, game)) in frames.into_iter().enumerate() {
+                let pixels = diagram.pixels(&game.board);
+                let mut frame = gif::Frame::from_rgb_speed(width, height, &pixels, 30);
+                frame.delay = if i == last {
+                    self.end_delay
+                } else {
+                    self.delay
+                };
+                encoder
+                    .write_frame(&frame)
+                    .expect("GIF encoding into memory failed");
+            }
+        }
+        Ok(out)
+    }
+}
@@ -0,0 +1,91 @@
+use super::*;
+use crate::piece::Team;
+
+const RECORD: &str = r#"
+[Blue "alpha"]
+[Red "beta"]
+move 11 to 21
+move 13 to 23
+move 14 to 24
+move 15 to 25
+draw
+move 81 to 71
+"#;
+
+#[test]
+pub(crate) fn test_narrate() {
+    let record = RECORD.parse::<Record>().unwrap();
+    let frames = Replay::default().frames(&record).unwrap();
+    let captions = frames
+        .iter()
+        .map(|(diagram, _)| diagram.caption.clone().unwrap())
+        .collect::<Vec<_>>();
+    assert_eq!(
+        captions,
+        [
+            "alpha (Blue) against beta (Red).",
+            "1. Blue Blank 11 to 21.",
+            "1. Blue Blank 13 to 23.",
+            "1. Blue Blank 14 to 24.",
+            "1. Blue Blank 15 to 25. Red to move.",
+            "2. Red offers a draw.",
+            "2. Red Blank 81 to 71.",
+        ]
+    );
+    assert_eq!(frames[0].0.energy, Some((Team::Blue, 4)));
+    assert_eq!(frames[3].0.energy, Some((Team::Blue, 1)));
+    assert_eq!(frames[4].0.energy, Some((Team::Red, 4)));
+    assert_eq!(frames[4].0.arrows, [(Pos(15), Pos(25))]);
+
+    // captures, conversions and merges.
+    let board = "\
+        SS........\
+        ..........\
+        BB........\
+        ...D......\
+        ....w.....\
+        ..........\
+        ..........\
+        ..........\
+        ..........\
+        ........bs"
+        .parse()
+        .unwrap();
+    let game = Game::from_position(Team::Blue, board);
+    let narrate_action = |action: &str| {
+        let verified = game.verify_move_str(action).unwrap();
+        let mut after = game.clone();
+        after.make_move(verified);
+        narrate(&game, &action.parse().unwrap(), &after)
+    };
+    assert_eq!(
+        narrate_action("move 33 to 44"),
+        "Blue Diplomat 33 converts a Red Warrior on 44."
+    );
+    assert_eq!(
+        narrate_action("merge warrior at 21 with 20"),
+        "Blue merges 2 pieces into a Warrior on 21. Red to move."
+    );
+    assert_eq!(narrate_action("resign"), "Blue resigns.");
+}
+
+#[cfg(feature = "gif")]
+#[test]
+pub(crate) fn test_gif() {
+    let record = RECORD.parse::<Record>().unwrap();
+    let replay = Replay {
+        diagram: Diagram {
+            tile_size: 16,
+            ..Default::default()
+        },
+        ..Default::default()
+    };
+    let gif = replay.gif(&record).unwrap();
+    let mut decoder = gif::DecodeOptions::new().read_info(gif.as_slice()).unwrap();
+    assert_eq!([decoder.width(), decoder.height()], [176, 200]);
+    let mut delays = vec![];
+    while let Some(frame) = decoder.read_next_frame().unwrap() {
+        delays.push(frame.delay);
+    }
+    assert_eq!(delays, [150, 150, 150, 150, 150, 150, 500]);
+}
@@ -0,0 +1,299 @@
+//! Rules conformance scenarios written as plain text.
+//!
+//! A scenario sets up a starting position, then plays actions and checks what happens.
+//! Blank lines and lines starting with `#` are ignored.
+//!
+//! ```text
+//! # Runners cannot capture on their first step.
+//! turn blue
+//! board
+//!     S.........
+//!     ..........
+//!     ..........
+//!     ...R......
+//!     ..w.......
+//!     ..........
+//!     ..........
+//!     ..........
+//!     ..........
+//!     .........s
+//! reject move 33 to 42: InvalidPieceMove(NoMelee)
+//! play move 33 to 22
+//! expect turn red
+//! ```
+//!
+//! Directives:
+//! - `turn <team>` and `board` (followed by the 100 tiles) set up the starting position.
+//! - `play <action>; <action>; ...` plays actions in the [`Move`] syntax. They must all be legal.
+//! - `reject <action>: <error>` checks that an action is illegal.
+//!   The error is the `Debug` form of the [`InvalidMove`](crate::board::InvalidMove), e.g. `HomeMerge`.
+//! - `expect board`, `expect turn <team>`, `expect power <n>`, `expect stagnation <n>`
+//!   and `expect state <state>` check the game.
+//!   States are `ongoing`, `offer <team>`, `draw` and `winner <team>`.
+//! - `repeat <n>` ... `end` plays the enclosed steps `n` times.
+//! - `ignore <reason>` keeps the scenario from running, for rules that are not implemented yet.
+
+use crate::{
+    board::{Board, Game, GameState, InvalidMoveCommand, Move, Winner},
+    piece::Team,
+};
+use std::str::FromStr;
+
+use thiserror::Error;
+
+#[cfg(test)]
+mod tests;
+
+#[derive(Error, Debug)]
+#[error("line {line}: {message}")]
+pub struct ScenarioError {
+    pub line: usize,
+    pub message: String,
+}
+
+impl ScenarioError {
+    fn new(line: usize, message: impl ToString) -> Self {
+        Self {
+            line,
+            message: message.to_string(),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub enum Step {
+    Play(Vec<Move>),
+    /// the action is kept as text, because some illegal actions cannot be parsed into a [`Move`].
+    Reject {
+        action: String,
+        error: String,
+    },
+    ExpectBoard(Board),
+    ExpectTurn(Team),
+    ExpectPower(u8),
+    ExpectStagnation(u8),
+    ExpectState(GameState),
+}
+
+#[derive(Debug, Clone)]
+pub struct Scenario {
+    /// why the scenario should not be run, if it should not.
+    pub ignored: Option<String>,
+    pub turn: Team,
+    pub board: Board,
+    /// each step is paired with the line it was written on.
+    pub steps: Vec<(usize, Step)>,
+}
+
+impl Scenario {
+    /// plays the scenario from the start, stopping at the first step that does not hold.
+    pub fn run(&self) -> Result<Game, ScenarioError> {
+        let mut game = Game::from_position(self.turn, self.board.clone());
+        for (line, step) in &self.steps {
+            let fail = |message: String| Err(ScenarioError::new(*line, message));
+            match step {
+                Step::Play(actions) => {
+                    for action in actions {
+                        match game.verify_action(action.clone()) {
+                            Ok(action) => game.make_move(action),
+                            Err(e) => return fail(format!("{action:?} was rejected: {e}")),
+                        }
+                    }
+                }
+                Step::Reject { action, error } => match game.verify_move_str(action) {
+                    Ok(_) => return fail(format!("`{action}` was accepted.")),
+                    Err(InvalidMoveCommand::InvalidMove(e)) if format!("{e:?}") == *error => {}
+                    Err(e) => return fail(format!("`{action}` was rejected with {e:?}")),
+                },
+                Step::ExpectBoard(board) if game.board != *board => {
+                    return fail(format!("expected board:\n{board}\nfound:\n{}", game.board));
+                }
+                Step::ExpectTurn(turn) if game.turn != *turn => {
+                    return fail(format!("expected {turn}'s turn, found {}'s", game.turn));
+                }
+                Step::ExpectPower(power) if game.power != *power => {
+                    return fail(format!("expected power {power}, found {}", game.power));
+                }
+                Step::ExpectStagnation(stagnation) if game.stagnation() != *stagnation => {
+                    return fail(format!(
+                        "expected stagnation {stagnation}, found {}",
+                        game.stagnation()
+                    ));
+                }
+                Step::ExpectState(state) if game.state != *state => {
+                    return fail(format!("expected {state:?}, found {:?}", game.state));
+                }
+                _ => {}
+            }
+        }
+        Ok(game)
+    }
+}
+
+fn parse_state(s: &str) -> Option<GameState> {
+    let mut words = s.split_whitespace();
+    let state = match (words.next()?, words.next()) {
+        ("ongoing", None) => GameState::Ongoing { draw_offer: None },
+        ("offer", Some(team)) => GameState::Ongoing {
+            draw_offer: Some(team.parse().ok()?),
+        },
+        ("draw", None) => GameState::Finished(Winner(None)),
+        ("winner", Some(team)) => GameState::Finished(Winner(Some(team.parse().ok()?))),
+        _ => return None,
+    };
+    words.next().is_none().then_some(state)
+}
+
+/// a `repeat` block that is still being read.
+struct Block {
+    line: usize,
+    count: usize,
+    steps: Vec<(usize, Step)>,
+}
+
+/// boards span several lines, so they are read from the same lines as the directives.
+pub(crate) fn read_board<'a>(
+    lines: &mut impl Iterator<Item = (usize, &'a str)>,
+) -> Result<Board, &'static str> {
+    let mut tiles = String::with_capacity(100);
+    while tiles.chars().filter(|c| !c.is_whitespace()).count() < 100 {
+        let Some((_, text)) = lines.next() else {
+            return Err("the board is missing some rows.");
+        };
+        tiles.push_str(text);
+    }
+    tiles.parse()
+}
+
+/// a game on `board`, written as rows of tiles, with Blue to move.
+#[cfg(test)]
+pub(crate) fn blue_to_move(board: &str) -> Game {
+    Game::from_position(Team::Blue, board.parse().unwrap())
+}
+
+impl FromStr for Scenario {
+    type Err = ScenarioError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut lines = s
+            .lines()
+            .enumerate()
+            .map(|(i, l)| (i + 1, l.trim()))
+            .filter(|(_, l)| !l.is_empty() && !l.starts_with('#'));
+
+        let mut ignored = None;
+        let mut turn = Team::default();
+        let mut board = None;
+        // the outermost block runs once. each `repeat` pushes another.
+        let mut blocks = vec![Block {
+            line: 0,
+            count: 1,
+            steps: vec![],
+        }];
+
+        while let Some((line, text)) = lines.next() {
+            let (keyword, rest) = text.split_once(' ').unwrap_or((text, ""));
+            let rest = rest.trim();
+            let err = |message: &str| ScenarioError::new(line, message);
+
+            let setting_up = blocks.len() == 1 && blocks[0].steps.is_empty();
+            let step = match keyword {
+                "ignore" => {
+                    ignored = Some(rest.to_owned());
+                    continue;
+                }
+                "turn" | "board" if !setting_up => {
+                    return Err(err("the starting position must come before any steps."));
+                }
+                "turn" => {
+                    turn = rest.parse().map_err(|e| ScenarioError::new(line, e))?;
+                    continue;
+                }
+                "board" => {
+                    board = Some(read_board(&mut lines).map_err(err)?);
+                    continue;
+                }
+                "repeat" => {
+                    let count = rest
+                        .parse()
+                        .map_err(|_| err("repeat needs a number of times."))?;
+                    blocks.push(Block {
+                        line,
+                        count,
+                        steps: vec![],
+                    });
+                    continue;
+                }
+                "end" => {
+                    if blocks.len() == 1 {
+                        return Err(err("there is no repeat to end."));
+                    }
+                    let block = blocks.pop().unwrap();
+                    let outer = &mut blocks.last_mut().unwrap().steps;
+                    for _ in 0..block.count {
+                        outer.extend(block.steps.iter().cloned());
+                    }
+                    continue;
+                }
+                "play" => Step::Play(
+                    rest.split(';')
+                        .map(|action| action.parse::<Move>())
+                        .collect::<Result<_, _>>()
+                        .map_err(|e| ScenarioError::new(line, e))?,
+                ),
+                "reject" => {
+                    let (action, error) = rest
+                        .split_once(':')
+                        .ok_or_else(|| err("expected `reject <action>: <error>`."))?;
+                    Step::Reject {
+                        action: action.trim().to_owned(),
+                        error: error.trim().to_owned(),
+                    }
+                }
+                "expect" => {
+                    let (what, value) = rest.split_once(' ').unwrap_or((rest, ""));
+                    let value = value.trim();
+                    match what {
+                        "board" => Step::ExpectBoard(read_board(&mut lines).map_err(err)?),
+                        "turn" => Step::ExpectTurn(
+                            value.parse().map_err(|e| ScenarioError::new(line, e))?,
+                        ),
+                        "power" => Step::ExpectPower(
+                            value.parse().map_err(|_| err("power must be a number."))?,
+                        ),
+                        "stagnation" => Step::ExpectStagnation(
+                            value
+                                .parse()
+                                .map_err(|_| err("stagnation must be a number."))?,
+                        ),
+                        "state" => Step::ExpectState(parse_state(value).ok_or_else(|| {
+                            err("states are `ongoing`, `offer <team>`, `draw` or `winner <team>`.")
+                        })?),
+                        _ => {
+                            return Err(err(
+                                "you can expect a board, turn, power, stagnation or state.",
+                            ))
+                        }
+                    }
+                }
+                _ => return Err(err("unknown directive.")),
+            };
+            blocks.last_mut().unwrap().steps.push((line, step));
+        }
+
+        if let [.., unended] = &blocks[1..] {
+            return Err(ScenarioError::new(
+                unended.line,
+                "this repeat is never ended.",
+            ));
+        }
+        let board = board.ok_or_else(|| ScenarioError::new(0, "there is no starting board."))?;
+        let steps = blocks.pop().unwrap().steps;
+        Ok(Self {
+            ignored,
+            turn,
+            board,
+            steps,
+        })
+    }
+}
@@ -0,0 +1,85 @@
+use super::*;
+use std::{fs, path::Path};
+
+#[test]
+pub(crate) fn test_scenario_files() {
+    let dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("scenarios");
+    let mut paths = fs::read_dir(dir)
+        .unwrap()
+        .map(|entry| entry.unwrap().path())
+        .collect::<Vec<_>>();
+    paths.sort();
+
+    let mut failures = vec![];
+    for path in paths {
+        let name = path.file_name().unwrap().to_string_lossy().into_owned();
+        let scenario = match fs::read_to_string(&path).unwrap().parse::<Scenario>() {
+            Ok(scenario) => scenario,
+            Err(e) => {
+                failures.push(format!("{name}: {e}"));
+                continue;
+            }
+        };
+        if let Some(reason) = &scenario.ignored {
+            eprintln!("skipping {name}: {reason}");
+            continue;
+        }
+        if let Err(e) = scenario.run() {
+            failures.push(format!("{name}: {e}"));
+        }
+    }
+    assert!(failures.is_empty(), "\n{}", failures.join("\n\n"));
+}
+
+const EMPTY_BOARD: &str = "
+    board
+        S.........
+        ..........
+        ..........
+        ..........
+        ..........
+        ..........
+        ..........
+        ..........
+        ..........
+        .........s
+";
+
+#[test]
+pub(crate) fn test_repeat() {
+    let scenario = format!(
+        "{EMPTY_BOARD}
+        repeat 2
+            expect turn blue
+            repeat 3
+                expect power 1
+            end
+        end
+        "
+    )
+    .parse::<Scenario>()
+    .unwrap();
+    assert_eq!(scenario.steps.len(), 8);
+    scenario.run().unwrap();
+}
+
+#[test]
+pub(crate) fn test_bad_scenarios() {
+    let error_line = |s: &str| s.parse::<Scenario>().unwrap_err().line;
+    assert_eq!(error_line(&format!("{EMPTY_BOARD}\nrepeat 2\n")), 14);
+    assert_eq!(error_line(&format!("{EMPTY_BOARD}\nend\n")), 14);
+    assert_eq!(error_line(&format!("{EMPTY_BOARD}\nplay move 00 to\n")), 14);
+    assert_eq!(
+        error_line(&format!("{EMPTY_BOARD}\nexpect state won\n")),
+        14
+    );
+    assert_eq!(
+        error_line(&format!("{EMPTY_BOARD}\nexpect power 1\nturn red\n")),
+        15
+    );
+
+    let scenario = format!("{EMPTY_BOARD}\nexpect turn red\n")
+        .parse::<Scenario>()
+        .unwrap();
+    assert_eq!(scenario.run().unwrap_err().line, 14);
+}
@@ -0,0 +1,219 @@
+//! Alpha-beta search over single actions.
+//!
+//! A turn is made of several actions by the same team, so the score is only negated
+//! when an action passes the turn to the other team. Depths are counted in actions.
+
+use crate::{
+    board::{GameState, Move, Winner},
+    eval::Weights,
+    json::Json,
+    Game,
+};
+use std::{
+    fmt::Display,
+    sync::atomic::{AtomicBool, Ordering},
+    time::{Duration, Instant},
+};
+
+#[cfg(test)]
+mod tests;
+
+/// the score of a won game, minus the number of actions it takes to win.
+const WIN: i32 = 1_000_000;
+/// the search never goes deeper than this, even without a depth limit.
+pub const MAX_DEPTH: u32 = 64;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Score {
+    /// an evaluation, see [`crate::eval`].
+    Value(i32),
+    /// the team to move can win in this many actions.
+    Win(u32),
+    /// the team to move loses in this many actions.
+    Loss(u32),
+}
+
+impl Score {
+    fn from_raw(raw: i32) -> Self {
+        let plies = WIN - raw.abs();
+        match raw {
+            _ if plies > MAX_DEPTH as i32 => Self::Value(raw),
+            1.. => Self::Win(plies as u32),
+            _ => Self::Loss(plies as u32),
+        }
+    }
+}
+
+/// written as in the [`crate::protocol`]: `<n>`, `win <actions>` or `loss <actions>`.
+impl Display for Score {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Score::Value(value) => write!(f, "{value}"),
+            Score::Win(actions) => write!(f, "win {actions}"),
+            Score::Loss(actions) => write!(f, "loss {actions}"),
+        }
+    }
+}
+
+/// `{"value":120}`, `{"win":3}` or `{"loss":3}`.
+impl From<Score> for Json {
+    fn from(score: Score) -> Self {
+        match score {
+            Score::Value(value) => Json::object([("value", value.into())]),
+            Score::Win(actions) => Json::object([("win", actions.into())]),
+            Score::Loss(actions) => Json::object([("loss", actions.into())]),
+        }
+    }
+}
+
+/// when to stop searching. the search also stops when it is told to from outside.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Limits {
+    pub depth: Option<u32>,
+    pub time: Option<Duration>,
+}
+
+/// what the search found after finishing a depth.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Info {
+    pub depth: u32,
+    pub score: Score,
+    pub nodes: u64,
+    pub time: Duration,
+    /// the best line of play found, starting with the best action.
+    pub pv: Vec<Move>,
+}
+
+struct Searcher<'a> {
+    weights: &'a Weights,
+    stop: &'a AtomicBool,
+    deadline: Option<Instant>,
+    nodes: u64,
+    aborted: bool,
+}
+
+impl Searcher<'_> {
+    /// the stop flag is cheap to look at on every node, but the clock only every 1024 nodes.
+    fn should_stop(&mut self) -> bool {
+        if !self.aborted {
+            self.aborted = self.stop.load(Ordering::Relaxed)
+                || self.nodes % 1024 == 1 && self.deadline.is_some_and(|d| Instant::now() >= d);
+        }
+        self.aborted
+    }
+
+    /// the score for the team to move, and the line that leads to it.
+    fn negamax(
+        &mut self,
+        game: &Game,
+        depth: u32,
+        ply: u32,
+        mut alpha: i32,
+        beta: i32,
+        hint: &[Move],
+    ) -> (i32, Vec<Move>) {
+        self.nodes += 1;
+        if let GameState::Finished(Winner(winner)) = game.state {
+            let score = match winner {
+                None => 0,
+                Some(team) if team == game.turn => WIN - ply as i32,
+                Some(_) => ply as i32 - WIN,
+            };
+            return (score, vec![]);
+        }
+        let mut actions = game.legal_actions();
+        // no legal action is a stalemate, which the rules count as a draw.
+        if actions.is_empty() {
+            return (0, vec![]);
+        }
+        // checked first, so that every node counts towards the next look at the clock.
+        if self.should_stop() || depth == 0 {
+            return (
+                self.weights.evaluate(&game.board, game.turn).total(),
+                vec![],
+            );
+        }
+
+        // the previous best line first, then captures and conversions, then merges.
+        actions.sort_by_key(|action| match action.action() {
+            action if hint.first() == Some(action) => 0,
+            Move::Move { to, .. } if game.board[*to].0.is_some() => 1,
+            Move::Merge { .. } => 2,
+            _ => 3,
+        });
+
+        let mut best = (-WIN - 1, vec![]);
+        for action in actions {
+            let first = action.action().clone();
+            let hint = match hint.split_first() {
+                Some((h, rest)) if *h == first => rest,
+                _ => &[],
+            };
+            let mut next = game.clone();
+            next.make_move(action);
+
+            let (score, line) = if next.turn == game.turn {
+                self.negamax(&next, depth - 1, ply + 1, alpha, beta, hint)
+            } else {
+                let (score, line) = self.negamax(&next, depth - 1, ply + 1, -beta, -alpha, hint);
+                (-score, line)
+            };
+            if self.aborted {
+                break;
+            }
+            if score > best.0 {
+                best = (score, [vec![first], line].concat());
+            }
+            alpha = alpha.max(score);
+            if alpha >= beta {
+                break;
+            }
+        }
+        best
+    }
+}
+
+/// searches for the best action for the team to move, deepening one action at a time.
+///
+/// `on_info` is called after every finished depth. returns `None` if the game is over.
+/// resigning and draws are never chosen.
+pub fn search(
+    game: &Game,
+    weights: &Weights,
+    limits: &Limits,
+    stop: &AtomicBool,
+    mut on_info: impl FnMut(&Info),
+) -> Option<Move> {
+    let start = Instant::now();
+    let mut searcher = Searcher {
+        weights,
+        stop,
+        deadline: limits.time.map(|t| start + t),
+        nodes: 0,
+        aborted: false,
+    };
+    let mut best = game.legal_actions().first()?.action().clone();
+    let mut pv = vec![];
+    let max_depth = limits.depth.unwrap_or(MAX_DEPTH).clamp(1, MAX_DEPTH);
+
+    for depth in 1..=max_depth {
+        let (score, line) = searcher.negamax(game, depth, 0, -WIN - 1, WIN + 1, &pv);
+        if searcher.aborted {
+            break;
+        }
+        pv = line;
+        best = pv[0].clone();
+        let score = Score::from_raw(score);
+        on_info(&Info {
+            depth,
+            score,
+            nodes: searcher.nodes,
+            time: start.elapsed(),
+            pv: pv.clone(),
+        });
+        if matches!(score, Score::Win(_) | Score::Loss(_)) {
+            break;
+        }
+    }
+    Some(best)
+}
@@ -0,0 +1,70 @@
+use super::*;
+use crate::scenario::blue_to_move;
+
+#[test]
+pub(crate) fn test_finds_occupation() {
+    // moving the warrior onto 45 takes every victory tile.
+    let game = blue_to_move(
+        "
+        S.........
+        ..........
+        ..........
+        .....W....
+        ....W.....
+        ....WW....
+        ..........
+        ..........
+        ..........
+        s........s
+    ",
+    );
+    let mut infos = vec![];
+    let best = search(
+        &game,
+        &Weights::default(),
+        &Limits {
+            depth: Some(3),
+            time: None,
+        },
+        &AtomicBool::new(false),
+        |info| infos.push(info.clone()),
+    );
+    assert_eq!(best.unwrap().to_string(), "move 35 to 45");
+    assert_eq!(infos.len(), 1);
+    assert_eq!(infos[0].score, Score::Win(1));
+}
+
+#[test]
+pub(crate) fn test_depth_limit() {
+    let mut depths = vec![];
+    let best = search(
+        &Game::new(),
+        &Weights::default(),
+        &Limits {
+            depth: Some(3),
+            time: None,
+        },
+        &AtomicBool::new(false),
+        |info| {
+            assert_eq!(info.pv.len(), info.depth as usize);
+            depths.push(info.depth);
+        },
+    );
+    assert!(Game::new().verify_action(best.unwrap()).is_ok());
+    assert_eq!(depths, [1, 2, 3]);
+}
+
+#[test]
+pub(crate) fn test_stopped() {
+    let mut called = false;
+    let best = search(
+        &Game::new(),
+        &Weights::default(),
+        &Limits::default(),
+        &AtomicBool::new(true),
+        |_| called = true,
+    );
+    // a stopped search still answers with a legal action.
+    assert!(Game::new().verify_action(best.unwrap()).is_ok());
+    assert!(!called);
+}
@@ -0,0 +1,212 @@
+//! Starting setups other than [`Board::new`].
+//!
+//! A setup is written as `standard`, `kelasu960 <n>` or `handicap <team> <blanks> <stones>`,
+//! or as the name of one of the [`PRESETS`].
+//!
+//! Kelasu960 setups move the Stones of row C to other columns, two on each half of the board,
+//! and give Red the same Stones flipped across the middle, so both teams start with the same energy.
+//! There are [`KELASU960_COUNT`] of them, and the standard setup is `kelasu960 18`.
+
+use crate::{
+    board::{Board, Pos},
+    piece::{Piece, PieceKind, Team, Tile},
+    util::Rng,
+    Game,
+};
+use std::{fmt::Display, str::FromStr};
+
+use thiserror::Error;
+
+#[cfg(test)]
+mod tests;
+
+pub const KELASU960_COUNT: u16 = 100;
+
+/// the ways to put two Stones on five columns, in order.
+const PAIRS: [[i8; 2]; 10] = [
+    [0, 1],
+    [0, 2],
+    [0, 3],
+    [0, 4],
+    [1, 2],
+    [1, 3],
+    [1, 4],
+    [2, 3],
+    [2, 4],
+    [3, 4],
+];
+
+/// the order Blanks are taken away in: the back row from the edges inwards, then the front row.
+const BLANK_ORDER: [i8; 10] = [0, 9, 1, 8, 2, 7, 3, 6, 4, 5];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum Setup {
+    #[default]
+    Standard,
+    /// one of the [`KELASU960_COUNT`] Kelasu960 setups, counted from 0.
+    Kelasu960(u16),
+    /// the standard setup, without some of the team's Blanks and Stones.
+    ///
+    /// the Stones furthest from the middle go first. the team keeps at least one Blank and one Stone.
+    Handicap { team: Team, blanks: u8, stones: u8 },
+}
+
+/// named setups, for choosing from a list.
+pub const PRESETS: [(&str, Setup); 7] = [
+    ("standard", Setup::Standard),
+    ("wings", Setup::Kelasu960(9)),
+    ("centre", Setup::Kelasu960(90)),
+    (
+        "blue-stone-odds",
+        Setup::Handicap {
+            team: Team::Blue,
+            blanks: 0,
+            stones: 1,
+        },
+    ),
+    (
+        "red-stone-odds",
+        Setup::Handicap {
+            team: Team::Red,
+            blanks: 0,
+            stones: 1,
+        },
+    ),
+    (
+        "blue-blank-odds",
+        Setup::Handicap {
+            team: Team::Blue,
+            blanks: 5,
+            stones: 0,
+        },
+    ),
+    (
+        "red-blank-odds",
+        Setup::Handicap {
+            team: Team::Red,
+            blanks: 5,
+            stones: 0,
+        },
+    ),
+];
+
+impl Setup {
+    /// a Kelasu960 setup picked by the seed.
+    pub fn kelasu960(seed: u64) -> Self {
+        Self::Kelasu960(Rng::new(seed).below(KELASU960_COUNT as usize) as u16)
+    }
+
+    /// the Kelasu960 number is taken modulo [`KELASU960_COUNT`].
+    pub fn board(self) -> Board {
+        let mut board = Board::new();
+        match self {
+            Setup::Standard => {}
+            Setup::Kelasu960(n) => {
+                let n = (n % KELASU960_COUNT) as usize;
+                let [a, b] = PAIRS[n / 10];
+                let [c, d] = PAIRS[n % 10].map(|x| x + 5);
+                for x in 0..10 {
+                    let stone = [a, b, c, d].contains(&x).then_some(PieceKind::Stone);
+                    for (team, y) in [(Team::Blue, 2), (Team::Red, 7)] {
+                        board[Pos(y * 10 + x)] = Tile(stone.map(|kind| Piece { team, kind }));
+                    }
+                }
+            }
+            Setup::Handicap {
+                team,
+                blanks,
+                stones,
+            } => {
+                let [back, front, stone_row] = match team {
+                    Team::Blue => [0, 1, 2],
+                    Team::Red => [9, 8, 7],
+                };
+                let rows = [back, front].map(|y| BLANK_ORDER.map(|x| Pos(y * 10 + x)));
+                for pos in rows.concat().into_iter().take(blanks.min(19) as usize) {
+                    board[pos] = Tile(None);
+                }
+                let mut stone_tiles = (0..10)
+                    .map(|x| Pos(stone_row * 10 + x))
+                    .filter(|&pos| board[pos].0.is_some())
+                    .collect::<Vec<_>>();
+                stone_tiles.sort_by_key(|pos| -(2 * pos.xy()[0] - 9).abs());
+                for pos in stone_tiles.into_iter().take(stones.min(3) as usize) {
+                    board[pos] = Tile(None);
+                }
+            }
+        }
+        board
+    }
+
+    /// a new game from the setup, with Blue to move.
+    pub fn game(self) -> Game {
+        Game::from_position(Team::Blue, self.board())
+    }
+}
+
+#[derive(Error, Debug)]
+pub enum InvalidSetup {
+    #[error(
+        "Unknown setup. Setups are `standard`, `kelasu960 <n>`, \
+        `handicap <team> <blanks> <stones>`, or one of: {}.",
+        PRESETS.map(|(name, _)| name).join(", ")
+    )]
+    Unknown,
+    #[error("Kelasu960 setups are numbered from 0 to 99.")]
+    Kelasu960OutOfRange,
+    #[error(
+        "A handicap leaves at least one Blank and one Stone, so at most 19 Blanks and 3 Stones."
+    )]
+    HandicapTooBig,
+}
+
+impl FromStr for Setup {
+    type Err = InvalidSetup;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if let Some((_, setup)) = PRESETS.iter().find(|(name, _)| *name == s.trim()) {
+            return Ok(*setup);
+        }
+        let words = s.split_whitespace().collect::<Vec<_>>();
+        match words[..] {
+            ["kelasu960", n] => match n.parse() {
+                Ok(n) if n < KELASU960_COUNT => Ok(Setup::Kelasu960(n)),
+                Ok(_) => Err(InvalidSetup::Kelasu960OutOfRange),
+                Err(_) => Err(InvalidSetup::Unknown),
+            },
+            ["handicap", team, blanks, stones] => {
+                let (Ok(team), Ok(blanks), Ok(stones)) =
+                    (team.parse(), blanks.parse(), stones.parse())
+                else {
+                    return Err(InvalidSetup::Unknown);
+                };
+                if blanks > 19 || stones > 3 {
+                    return Err(InvalidSetup::HandicapTooBig);
+                }
+                Ok(Setup::Handicap {
+                    team,
+                    blanks,
+                    stones,
+                })
+            }
+            _ => Err(InvalidSetup::Unknown),
+        }
+    }
+}
+
+/// written the same way that [`Setup::from_str`] reads it, without preset names.
+impl Display for Setup {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Setup::Standard => write!(f, "standard"),
+            Setup::Kelasu960(n) => write!(f, "kelasu960 {n}"),
+            Setup::Handicap {
+                team,
+                blanks,
+                stones,
+            } => {
+                write!(f, "handicap {} {blanks} {stones}", team.name())
+            }
+        }
+    }
+}
@@ -0,0 +1,88 @@
+use super::*;
+
+fn stone_columns(board: &Board, y: i8) -> Vec<i8> {
+    (0..10)
+        .filter(|&x| {
+            board[Pos(y * 10 + x)]
+                .0
+                .is_some_and(|p| p.kind == PieceKind::Stone)
+        })
+        .collect()
+}
+
+#[test]
+pub(crate) fn test_kelasu960() {
+    assert_eq!(Setup::Kelasu960(18).board(), Board::new());
+    let mut boards = vec![];
+    for n in 0..KELASU960_COUNT {
+        let board = Setup::Kelasu960(n).board();
+        // both teams have the same stones, two on each half.
+        assert_eq!(board.flipped(), board);
+        let columns = stone_columns(&board, 2);
+        assert_eq!(columns.len(), 4);
+        assert_eq!(columns.iter().filter(|&&x| x < 5).count(), 2);
+        boards.push(board);
+    }
+    boards.sort();
+    boards.dedup();
+    assert_eq!(boards.len(), KELASU960_COUNT as usize);
+
+    assert_eq!(Setup::kelasu960(5), Setup::kelasu960(5));
+    assert_eq!(
+        stone_columns(&Setup::Kelasu960(90).board(), 7),
+        [3, 4, 5, 6]
+    );
+}
+
+#[test]
+pub(crate) fn test_handicap() {
+    let board = Setup::Handicap {
+        team: Team::Red,
+        blanks: 12,
+        stones: 2,
+    }
+    .board();
+    assert_eq!(board.piece_count(Team::Red), 8);
+    assert_eq!(board.stone_count(Team::Red), 2);
+    assert_eq!(stone_columns(&board, 7), [2, 7]);
+    assert_eq!(board.piece_count(Team::Blue), 20);
+
+    // the team keeps a stone and a blank, so the game can start.
+    let game = Setup::Handicap {
+        team: Team::Blue,
+        blanks: 50,
+        stones: 9,
+    }
+    .game();
+    assert_eq!(game.power, 1);
+    assert_eq!(game.board.piece_count(Team::Blue), 1);
+    assert!(game.is_ongoing());
+}
+
+#[test]
+pub(crate) fn test_parse_setups() {
+    for (name, setup) in PRESETS {
+        assert_eq!(name.parse::<Setup>().unwrap(), setup);
+        assert_eq!(setup.to_string().parse::<Setup>().unwrap(), setup);
+    }
+    assert_eq!(
+        "handicap red 5 1".parse::<Setup>().unwrap(),
+        Setup::Handicap {
+            team: Team::Red,
+            blanks: 5,
+            stones: 1,
+        }
+    );
+    assert!(matches!(
+        "kelasu960 100".parse::<Setup>(),
+        Err(InvalidSetup::Kelasu960OutOfRange)
+    ));
+    assert!(matches!(
+        "handicap blue 0 4".parse::<Setup>(),
+        Err(InvalidSetup::HandicapTooBig)
+    ));
+    assert!(matches!(
+        "chess960".parse::<Setup>(),
+        Err(InvalidSetup::Unknown)
+    ));
+}
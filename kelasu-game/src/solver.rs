@@ -0,0 +1,185 @@
+//! Proves or refutes that the team to move can win within a number of its own turns.
+//!
+//! The solver tries every action of the attacking team, and every action of the defending
+//! team in between, including every order of the actions within a turn.
+//! Positions are remembered by [`Game::position_key`], so the solver does not tell positions
+//! apart by their repetitions or their stagnation count.
+
+use crate::{
+    board::{GameState, Move, Pos, VerifiedMove, Winner},
+    piece::Team,
+    Game,
+};
+use std::{
+    collections::HashMap,
+    sync::atomic::{AtomicBool, Ordering},
+};
+
+#[cfg(test)]
+mod tests;
+
+pub(crate) const VICTORY_TILES: [Pos; 4] = [Pos(44), Pos(45), Pos(54), Pos(55)];
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Solution {
+    /// the team to move wins within `turns` of its turns, whatever the other team does.
+    ///
+    /// `line` is every action until the win, against the defence that lasts the longest.
+    Win { turns: u32, line: Vec<Move> },
+    /// the other team can always avoid losing that soon.
+    NoWin,
+}
+
+struct Solver<'a> {
+    attacker: Team,
+    stop: &'a AtomicBool,
+    nodes: u64,
+    aborted: bool,
+    /// the winning line of every position that was solved, by its key and turns left.
+    known: HashMap<(u64, u32), Option<Vec<Move>>>,
+}
+
+impl Solver<'_> {
+    fn should_stop(&mut self) -> bool {
+        if !self.aborted && self.nodes % 1024 == 1 {
+            self.aborted = self.stop.load(Ordering::Relaxed);
+        }
+        self.aborted
+    }
+
+    /// what an action leads to for the attacker, with `turns` of its turns left after this one.
+    fn after(&mut self, game: &Game, action: VerifiedMove, turns: u32) -> Option<Vec<Move>> {
+        let mut next = game.clone();
+        next.make_move(action);
+        match next.state {
+            GameState::Finished(Winner(winner)) => (winner == Some(self.attacker)).then(Vec::new),
+            _ if next.turn == game.turn && game.turn == self.attacker => self.attack(&next, turns),
+            _ if next.turn == game.turn => self.defend(&next, turns),
+            _ if next.turn == self.attacker => self.attack(&next, turns),
+            _ if turns > 1 => self.defend(&next, turns - 1),
+            _ => None,
+        }
+    }
+
+    /// the winning line of the attacker, who is to move, within `turns` of its turns.
+    fn attack(&mut self, game: &Game, turns: u32) -> Option<Vec<Move>> {
+        self.nodes += 1;
+        if self.should_stop() {
+            return None;
+        }
+        let key = (game.position_key(), turns);
+        if let Some(known) = self.known.get(&key) {
+            return known.clone();
+        }
+        if turns == 1 && !can_win_this_turn(game) {
+            return None;
+        }
+
+        let mut actions = game.legal_actions();
+        // the likeliest wins first: taking victory tiles, then captures and conversions.
+        actions.sort_by_key(|action| match action.action() {
+            Move::Move { to, .. } if VICTORY_TILES.contains(to) => 0,
+            Move::Move { to, .. } if game.board[*to].0.is_some() => 1,
+            _ => 2,
+        });
+        let mut found = None;
+        for action in actions {
+            let first = action.action().clone();
+            let line = self.after(game, action, turns);
+            if self.aborted {
+                return None;
+            }
+            if let Some(line) = line {
+                found = Some([vec![first], line].concat());
+                break;
+            }
+        }
+        self.known.insert(key, found.clone());
+        found
+    }
+
+    /// the winning line of the attacker against every defence of the team to move.
+    fn defend(&mut self, game: &Game, turns: u32) -> Option<Vec<Move>> {
+        self.nodes += 1;
+        if self.should_stop() {
+            return None;
+        }
+        let key = (game.position_key(), turns);
+        if let Some(known) = self.known.get(&key) {
+            return known.clone();
+        }
+
+        let mut actions = game.legal_actions();
+        // the likeliest defences first: captures, then blocking victory tiles.
+        actions.sort_by_key(|action| match action.action() {
+            Move::Move { to, .. } if game.board[*to].0.is_some() => 0,
+            Move::Move { to, .. } if VICTORY_TILES.contains(to) => 1,
+            _ => 2,
+        });
+        let mut longest = Some(vec![]);
+        for action in actions {
+            let first = action.action().clone();
+            let line = self.after(game, action, turns);
+            if self.aborted {
+                return None;
+            }
+            match line {
+                None => {
+                    longest = None;
+                    break;
+                }
+                Some(line) if longest.as_ref().is_some_and(|l| line.len() + 1 > l.len()) => {
+                    longest = Some([vec![first], line].concat());
+                }
+                Some(_) => {}
+            }
+        }
+        self.known.insert(key, longest.clone());
+        longest
+    }
+}
+
+/// the fewest actions the team to move needs to win.
+///
+/// every action takes at most one victory tile, or one stone or piece away from the enemy.
+pub(crate) fn actions_to_win(game: &Game) -> usize {
+    let enemy = !game.turn;
+    let tiles_left = VICTORY_TILES
+        .iter()
+        .filter(|&&pos| game.board[pos].0.is_none_or(|p| p.team != game.turn))
+        .count();
+    tiles_left
+        .min(game.board.stone_count(enemy) as usize)
+        .min(game.board.piece_count(enemy) as usize)
+}
+
+/// whether the team to move has enough power left to win before its turn ends.
+fn can_win_this_turn(game: &Game) -> bool {
+    actions_to_win(game) <= game.power as usize
+}
+
+/// whether the team to move can win within `turns` of its own turns, starting with this one.
+///
+/// the shortest win is found first. returns `None` if the solver was stopped before it was sure.
+pub fn solve(game: &Game, turns: u32, stop: &AtomicBool) -> Option<Solution> {
+    if !game.is_ongoing() {
+        return Some(Solution::NoWin);
+    }
+    let mut solver = Solver {
+        attacker: game.turn,
+        stop,
+        nodes: 0,
+        aborted: false,
+        known: HashMap::new(),
+    };
+    for n in 1..=turns {
+        let line = solver.attack(game, n);
+        if solver.aborted {
+            return None;
+        }
+        if let Some(line) = line {
+            return Some(Solution::Win { turns: n, line });
+        }
+    }
+    Some(Solution::NoWin)
+}
@@ -0,0 +1,114 @@
+use super::*;
+use crate::scenario::blue_to_move;
+
+fn solve_for(game: &Game, turns: u32) -> Solution {
+    solve(game, turns, &AtomicBool::new(false)).unwrap()
+}
+
+#[test]
+pub(crate) fn test_win_in_one() {
+    // the general takes the last red stone.
+    let game = blue_to_move(
+        "
+        S.........
+        .........G
+        ..........
+        ..........
+        ..........
+        .........s
+        ..........
+        ..........
+        ..........
+        ........b.
+    ",
+    );
+    let Solution::Win { turns, line } = solve_for(&game, 3) else {
+        panic!("the general can take the stone.");
+    };
+    assert_eq!(turns, 1);
+    assert_eq!(line, ["move 19 to 59".parse().unwrap()]);
+}
+
+#[test]
+pub(crate) fn test_win_in_two() {
+    // the general needs two moves to reach the stone, and red cannot stop it.
+    let game = blue_to_move(
+        "
+        S.........
+        G.........
+        ..........
+        ..........
+        ..........
+        .........s
+        ..........
+        ..........
+        ..........
+        ...b......
+    ",
+    );
+    assert_eq!(solve_for(&game, 1), Solution::NoWin);
+    let Solution::Win { turns, line } = solve_for(&game, 2) else {
+        panic!("red has no defence.");
+    };
+    assert_eq!(turns, 2);
+    // blue, red, blue.
+    assert_eq!(line.len(), 3);
+    let mut replay = game.clone();
+    for action in line {
+        replay.make_move(replay.verify_action(action).unwrap());
+    }
+    assert_eq!(replay.state, GameState::Finished(Winner(Some(Team::Blue))));
+}
+
+#[test]
+pub(crate) fn test_defence() {
+    // the red general takes the blue general if it stays in front of the blue stone,
+    // and takes the stone if it leaves. taking the red general wins too late.
+    let game = blue_to_move(
+        "
+        S.........
+        G.........
+        ..........
+        ..........
+        ..........
+        .........s
+        g.........
+        ..........
+        ..........
+        b.........
+    ",
+    );
+    assert_eq!(solve_for(&game, 2), Solution::NoWin);
+}
+
+#[test]
+pub(crate) fn test_stalemate() {
+    // red has power left after moving its only piece, which is then locked.
+    // blue cannot reach anything, and a stalemate is a draw, not a win.
+    let game = blue_to_move(
+        "
+        B........S
+        ..........
+        ..........
+        ..........
+        ..........
+        ..........
+        ..........
+        ..........
+        s.........
+        s........b
+    ",
+    );
+    let mut stalemate = game.clone();
+    for action in ["move 00 to 10", "move 99 to 89"] {
+        stalemate.make_move(stalemate.verify_move_str(action).unwrap());
+    }
+    assert_eq!(stalemate.turn, Team::Red);
+    assert_eq!(stalemate.state, GameState::Finished(Winner(None)));
+    assert_eq!(solve_for(&game, 2), Solution::NoWin);
+}
+
+#[test]
+pub(crate) fn test_stop() {
+    assert_eq!(solve(&Game::new(), 2, &AtomicBool::new(true)), None);
+}
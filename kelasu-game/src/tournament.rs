@@ -0,0 +1,275 @@
+//! Plays many games between engines, to measure how strong they are.
+//!
+//! Every pair of players plays each opening twice, once with each team.
+//! Games run in parallel, each with fresh engine processes, and every game is kept as a [`Record`].
+//!
+//! Time is only controlled per search: the protocol has no game clock to send an engine,
+//! so there is none to run out of over a whole game.
+
+use crate::{
+    board::{GameState, Winner},
+    piece::Team,
+    protocol::{Engine, EngineError},
+    record::Record,
+    search::Limits,
+    util::Rng,
+    Game,
+};
+use std::{
+    ops::ControlFlow,
+    sync::{
+        atomic::{AtomicBool, AtomicUsize, Ordering},
+        mpsc, Arc,
+    },
+    thread,
+    time::{Duration, Instant},
+};
+use thiserror::Error;
+
+mod elo;
+#[cfg(test)]
+mod tests;
+
+pub use elo::{Sprt, SprtResult, Tally};
+
+pub struct Player {
+    pub name: String,
+    /// starts a fresh engine, ready to play a new game.
+    pub start: Arc<dyn Fn() -> Result<Engine, EngineError> + Send + Sync>,
+}
+
+/// how long each search may take. there is no clock for the whole game.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TimeControl {
+    /// the limits of every search.
+    pub limits: Limits,
+    /// how much longer than `limits.time` an engine may take before it loses on time.
+    pub margin: Duration,
+}
+
+#[derive(Debug, Clone)]
+pub struct Settings {
+    pub time_control: TimeControl,
+    /// how many games are played at once.
+    pub concurrency: usize,
+    /// each game starts from one of these, which are played from their start to their last action.
+    pub openings: Vec<Record>,
+    /// games that are still going after this many actions are drawn.
+    pub max_actions: usize,
+}
+
+/// a finished game, with the indices of its players.
+#[derive(Debug, Clone)]
+pub struct GameResult {
+    pub blue: usize,
+    pub red: usize,
+    pub record: Record,
+}
+
+/// how many random games are tried for each opening before giving up.
+const OPENING_ATTEMPTS: usize = 100;
+
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+#[error("Random play keeps ending the game before {actions} actions, so there are no openings that long.")]
+pub struct OpeningTooLong {
+    pub actions: usize,
+}
+
+/// `count` openings of `actions` random actions each, which never finish the game.
+///
+/// fails if random play ends the game before `actions` actions too often to find them.
+pub fn random_openings(
+    count: usize,
+    actions: usize,
+    seed: u64,
+) -> Result<Vec<Record>, OpeningTooLong> {
+    let mut rng = Rng::new(seed);
+    let mut openings = vec![];
+    let mut attempts = 0;
+    while openings.len() < count {
+        if attempts == OPENING_ATTEMPTS {
+            return Err(OpeningTooLong { actions });
+        }
+        attempts += 1;
+        let mut game = Game::new();
+        let mut record = Record::default();
+        while record.actions.len() < actions && game.is_ongoing() {
+            let mut legal = game.legal_actions();
+            if legal.is_empty() {
+                break;
+            }
+            let action = legal.swap_remove(rng.below(legal.len()));
+            record.actions.push(action.action().clone());
+            game.make_move(action);
+        }
+        if game.is_ongoing() && record.actions.len() == actions {
+            openings.push(record);
+            attempts = 0;
+        }
+    }
+    Ok(openings)
+}
+
+/// how a game ended, when it was not by the rules.
+fn forfeit(record: &mut Record, loser: Team, reason: &str) {
+    record.result = Some(Winner(Some(!loser)));
+    record.set_tag("Termination", reason);
+}
+
+fn play_game(blue: &Player, red: &Player, opening: &Record, settings: &Settings) -> Record {
+    let mut record = opening.clone();
+    record.set_tag("Blue", &blue.name);
+    record.set_tag("Red", &red.name);
+
+    let mut engines = [(blue.start)(), (red.start)()];
+    let mut game = match opening.replay() {
+        Ok(game) => game,
+        Err(e) => {
+            record.set_tag("Termination", format!("invalid opening: {e}"));
+            return record;
+        }
+    };
+    let time_limit = settings
+        .time_control
+        .limits
+        .time
+        .map(|t| t + settings.time_control.margin);
+
+    while game.is_ongoing() {
+        if record.actions.len() >= settings.max_actions {
+            record.result = Some(Winner(None));
+            record.set_tag("Termination", "action limit");
+            return record;
+        }
+        let team = game.turn;
+        let engine = match &mut engines[team as usize] {
+            Ok(engine) => engine,
+            Err(e) => {
+                forfeit(&mut record, team, &format!("engine error: {e}"));
+                return record;
+            }
+        };
+        let start = Instant::now();
+        let action = engine
+            .set_position(record.start.as_ref(), &record.actions)
+            .and_then(|_| engine.go(&settings.time_control.limits, |_| {}));
+        let action = match action {
+            Ok(Some(action)) => action,
+            Ok(None) => {
+                forfeit(&mut record, team, "no action");
+                return record;
+            }
+            Err(e) => {
+                forfeit(&mut record, team, &format!("engine error: {e}"));
+                return record;
+            }
+        };
+        if time_limit.is_some_and(|limit| start.elapsed() > limit) {
+            forfeit(&mut record, team, "time");
+            return record;
+        }
+        match game.verify_action(action.clone()) {
+            Ok(verified) => game.make_move(verified),
+            Err(e) => {
+                forfeit(
+                    &mut record,
+                    team,
+                    &format!("illegal action `{action}`: {e}"),
+                );
+                return record;
+            }
+        }
+        record.actions.push(action);
+    }
+
+    if let GameState::Finished(winner) = game.state {
+        record.result = Some(winner);
+    }
+    record
+}
+
+/// plays every pair of players on every opening, with both teams, `concurrency` games at a time.
+///
+/// `on_game` is told about each game as it finishes, and can stop any more games from starting.
+/// returns every finished game, in the order they finished.
+pub fn run(
+    players: &[Player],
+    settings: &Settings,
+    mut on_game: impl FnMut(&GameResult) -> ControlFlow<()>,
+) -> Vec<GameResult> {
+    let mut schedule = vec![];
+    for (round, _) in settings.openings.iter().enumerate() {
+        for i in 0..players.len() {
+            for j in i + 1..players.len() {
+                schedule.push((round, i, j));
+                schedule.push((round, j, i));
+            }
+        }
+    }
+
+    let next = AtomicUsize::new(0);
+    let stopped = AtomicBool::new(false);
+    let (sender, receiver) = mpsc::channel();
+    let mut results = vec![];
+    thread::scope(|scope| {
+        for _ in 0..settings.concurrency.max(1) {
+            let sender = sender.clone();
+            let (next, stopped, schedule) = (&next, &stopped, &schedule);
+            scope.spawn(move || {
+                while !stopped.load(Ordering::Relaxed) {
+                    let Some(&(round, blue, red)) =
+                        schedule.get(next.fetch_add(1, Ordering::Relaxed))
+                    else {
+                        break;
+                    };
+                    let mut record = play_game(
+                        &players[blue],
+                        &players[red],
+                        &settings.openings[round],
+                        settings,
+                    );
+                    record.set_tag("Round", round + 1);
+                    if sender.send(GameResult { blue, red, record }).is_err() {
+                        break;
+                    }
+                }
+            });
+        }
+        drop(sender);
+
+        for result in receiver {
+            if on_game(&result).is_break() {
+                stopped.store(true, Ordering::Relaxed);
+            }
+            results.push(result);
+        }
+    });
+    results
+}
+
+/// the results of each player against every other, from the games of [`run`].
+///
+/// `tallies[i][j]` are the results of player `i` against player `j`.
+/// games that are unfinished, e.g. because the opening was invalid, are left out.
+pub fn tally(players: usize, results: &[GameResult]) -> Vec<Vec<Tally>> {
+    let mut tallies = vec![vec![Tally::default(); players]; players];
+    for result in results {
+        let (blue, red) = (result.blue, result.red);
+        match result.record.result {
+            None => {}
+            Some(Winner(None)) => {
+                tallies[blue][red].draws += 1;
+                tallies[red][blue].draws += 1;
+            }
+            Some(Winner(Some(Team::Blue))) => {
+                tallies[blue][red].wins += 1;
+                tallies[red][blue].losses += 1;
+            }
+            Some(Winner(Some(Team::Red))) => {
+                tallies[red][blue].wins += 1;
+                tallies[blue][red].losses += 1;
+            }
+        }
+    }
+    tallies
+}
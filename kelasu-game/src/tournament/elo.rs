@@ -0,0 +1,134 @@
+use std::fmt::Display;
+
+/// the expected score of a player that is `elo` points stronger.
+fn expected_score(elo: f64) -> f64 {
+    1.0 / (1.0 + 10f64.powf(-elo / 400.0))
+}
+
+/// the Elo difference that gives an expected score of `score`.
+fn elo_difference(score: f64) -> f64 {
+    -400.0 * (1.0 / score - 1.0).log10()
+}
+
+/// the results of one player against others.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct Tally {
+    pub wins: u32,
+    pub draws: u32,
+    pub losses: u32,
+}
+
+impl Tally {
+    pub fn games(&self) -> u32 {
+        self.wins + self.draws + self.losses
+    }
+
+    /// the average score, counting a win as 1 and a draw as 0.5.
+    pub fn score(&self) -> f64 {
+        (self.wins as f64 + self.draws as f64 / 2.0) / self.games() as f64
+    }
+
+    /// the variance of the score of a single game.
+    fn variance(&self) -> f64 {
+        let s = self.score();
+        (self.wins as f64 * (1.0 - s).powi(2)
+            + self.draws as f64 * (0.5 - s).powi(2)
+            + self.losses as f64 * s.powi(2))
+            / self.games() as f64
+    }
+
+    /// the estimated Elo difference to the opponents. infinite if every game was won or lost.
+    pub fn elo(&self) -> f64 {
+        elo_difference(self.score())
+    }
+
+    /// the bounds of the Elo difference at 95% confidence.
+    pub fn elo_interval(&self) -> (f64, f64) {
+        let margin = 1.96 * (self.variance() / self.games() as f64).sqrt();
+        let s = self.score();
+        (
+            elo_difference((s - margin).max(0.0)),
+            elo_difference((s + margin).min(1.0)),
+        )
+    }
+
+    /// the log-likelihood ratio of the Elo difference being `elo1` rather than `elo0`.
+    pub fn llr(&self, elo0: f64, elo1: f64) -> f64 {
+        let variance = self.variance();
+        if self.games() == 0 || variance == 0.0 {
+            return 0.0;
+        }
+        let (s0, s1) = (expected_score(elo0), expected_score(elo1));
+        self.games() as f64 * (s1 - s0) * (2.0 * self.score() - s0 - s1) / (2.0 * variance)
+    }
+}
+
+/// e.g. `+12-3=5: 72.5%, Elo +169 (+48, +441)`.
+impl Display for Tally {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "+{}-{}={}", self.wins, self.losses, self.draws)?;
+        if self.games() == 0 {
+            return Ok(());
+        }
+        let (low, high) = self.elo_interval();
+        write!(
+            f,
+            ": {:.1}%, Elo {:+.0} ({:+.0}, {:+.0})",
+            100.0 * self.score(),
+            self.elo(),
+            low,
+            high
+        )
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SprtResult {
+    /// the Elo difference is more likely `elo1`.
+    AcceptH1,
+    /// the Elo difference is more likely `elo0`.
+    AcceptH0,
+    Continue,
+}
+
+/// a sequential probability ratio test between two Elo differences.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Sprt {
+    pub elo0: f64,
+    pub elo1: f64,
+    /// the chance of accepting H1 when H0 holds.
+    pub alpha: f64,
+    /// the chance of accepting H0 when H1 holds.
+    pub beta: f64,
+}
+
+impl Sprt {
+    pub fn new(elo0: f64, elo1: f64) -> Self {
+        Self {
+            elo0,
+            elo1,
+            alpha: 0.05,
+            beta: 0.05,
+        }
+    }
+
+    /// the log-likelihood ratios at which H0 and H1 are accepted.
+    pub fn bounds(&self) -> (f64, f64) {
+        (
+            (self.beta / (1.0 - self.alpha)).ln(),
+            ((1.0 - self.beta) / self.alpha).ln(),
+        )
+    }
+
+    pub fn test(&self, tally: &Tally) -> SprtResult {
+        let llr = tally.llr(self.elo0, self.elo1);
+        let (lower, upper) = self.bounds();
+        if llr >= upper {
+            SprtResult::AcceptH1
+        } else if llr <= lower {
+            SprtResult::AcceptH0
+        } else {
+            SprtResult::Continue
+        }
+    }
+}
@@ -0,0 +1,103 @@
+use super::*;
+use crate::protocol::serve;
+use std::io::{pipe, BufReader};
+
+#[test]
+pub(crate) fn test_tally() {
+    let even = Tally {
+        wins: 3,
+        draws: 4,
+        losses: 3,
+    };
+    assert_eq!(even.score(), 0.5);
+    assert_eq!(even.elo(), 0.0);
+    let (low, high) = even.elo_interval();
+    assert!(low < 0.0 && high > 0.0 && (low + high).abs() < 1e-9);
+
+    let strong = Tally {
+        wins: 3,
+        draws: 0,
+        losses: 1,
+    };
+    assert!((strong.elo() - 190.85).abs() < 0.01);
+    assert_eq!(strong.to_string(), "+3-1=0: 75.0%, Elo +191 (-126, +inf)");
+}
+
+#[test]
+pub(crate) fn test_sprt() {
+    let sprt = Sprt::new(0.0, 10.0);
+    let (lower, upper) = sprt.bounds();
+    assert!((lower + 2.944).abs() < 0.001 && (upper - 2.944).abs() < 0.001);
+
+    let tally = |wins, losses| Tally {
+        wins,
+        draws: 100,
+        losses,
+    };
+    assert_eq!(sprt.test(&tally(50, 50)), SprtResult::Continue);
+    assert_eq!(sprt.test(&tally(400, 250)), SprtResult::AcceptH1);
+    assert_eq!(sprt.test(&tally(250, 400)), SprtResult::AcceptH0);
+}
+
+#[test]
+pub(crate) fn test_random_openings() {
+    let openings = random_openings(3, 6, 7).unwrap();
+    assert_eq!(openings, random_openings(3, 6, 7).unwrap());
+    for opening in &openings {
+        assert_eq!(opening.actions.len(), 6);
+        assert!(opening.replay().unwrap().is_ongoing());
+    }
+}
+
+/// an engine running on another thread of this process.
+fn in_process_engine() -> Result<Engine, EngineError> {
+    let (engine_input, to_engine) = pipe()?;
+    let (from_engine, engine_output) = pipe()?;
+    thread::spawn(move || serve(BufReader::new(engine_input), engine_output));
+    Engine::connect(BufReader::new(from_engine), to_engine)
+}
+
+#[test]
+pub(crate) fn test_run() {
+    let player = |name: &str, depth: &'static str| Player {
+        name: name.to_owned(),
+        start: Arc::new(move || {
+            let mut engine = in_process_engine()?;
+            engine.set_option("mobility", depth)?;
+            Ok(engine)
+        }),
+    };
+    let players = [player("alpha", "5"), player("beta", "0")];
+    let settings = Settings {
+        time_control: TimeControl {
+            limits: Limits {
+                depth: Some(1),
+                time: None,
+            },
+            margin: Duration::ZERO,
+        },
+        concurrency: 2,
+        openings: random_openings(2, 4, 1).unwrap(),
+        max_actions: 30,
+    };
+
+    let mut seen = 0;
+    let results = run(&players, &settings, |_| {
+        seen += 1;
+        ControlFlow::Continue(())
+    });
+    assert_eq!(results.len(), 4);
+    assert_eq!(seen, 4);
+    for result in &results {
+        assert_ne!(result.blue, result.red);
+        assert_eq!(
+            result.record.tag("Blue"),
+            Some(players[result.blue].name.as_str())
+        );
+        assert!(result.record.result.is_some());
+        result.record.replay().unwrap();
+    }
+    let tallies = tally(2, &results);
+    assert_eq!(tallies[0][1].games(), 4);
+    assert_eq!(tallies[0][1].wins, tallies[1][0].losses);
+}
@@ -3,6 +3,29 @@ use std::io::{stdin, stdout, Write};
 
 use thiserror::Error;
 
+/// a small random number generator (SplitMix64), so that random games can be repeated from a seed.
+#[derive(Debug, Clone)]
+pub struct Rng(u64);
+
+impl Rng {
+    pub fn new(seed: u64) -> Self {
+        Self(seed)
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    }
+
+    /// a number from `0` to `n - 1`.
+    pub fn below(&mut self, n: usize) -> usize {
+        (self.next_u64() % n as u64) as usize
+    }
+}
+
 pub fn input(msg: &str) -> String {
     println!("{msg}");
     print!("> ");
@@ -27,6 +50,9 @@ pub fn verify_polyomino(pieces: &mut [Pos]) -> Result<(), NonPolyomino> {
     while l < r {
         let p = pieces[l].0;
         let nbs = [p - 1, p + 1, p - 10, p + 10];
+        // the range is fixed when the loop starts, on purpose: the piece swapped out of `r`
+        // was already looked at, and the neighbours found are checked when `l` gets to them.
+        #[allow(clippy::mut_range_bound)]
         for i in r..pieces.len() {
             let np = pieces[i].0;
             if p == np {
@@ -0,0 +1,358 @@
+use kelasu_game::{
+    board::{GameState, InvalidMove, Move, Pos, Position, Winner},
+    lan::GameView,
+    piece::{PieceKind, Team},
+    render::{Renderer, Style},
+    search::Limits,
+    Game,
+};
+use std::time::Instant;
+
+#[cfg(test)]
+mod tests;
+
+/// the keys the app knows, whatever terminal they came from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Key {
+    Up,
+    Down,
+    Left,
+    Right,
+    /// space or enter.
+    Select,
+    Escape,
+    Char(char),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Mode {
+    /// pick a piece, then one of its destinations.
+    Move,
+    /// pick the pieces to merge, then the kind to merge them into, at the cursor.
+    Merge,
+}
+
+/// the merge keys, in the order they are listed in the help.
+const MERGE_KEYS: [(char, PieceKind); 6] = [
+    ('w', PieceKind::Warrior),
+    ('r', PieceKind::Runner),
+    ('d', PieceKind::Diplomat),
+    ('c', PieceKind::Champion),
+    ('g', PieceKind::General),
+    ('s', PieceKind::Stone),
+];
+
+pub const HELP: &str = "\
+arrows/hjkl move the cursor    space/enter select    esc clear
+m merge mode, then space to pick pieces and w r d c g s to merge at the cursor
+u undo    D offer or accept a draw    X decline a draw    R resign    q quit
+t describe the position in words, for screen readers";
+
+/// a computer player, and how long it may think. see [`crate::computer`] for the thinking.
+#[derive(Debug, Clone)]
+pub struct Ai {
+    pub team: Team,
+    pub limits: Limits,
+}
+
+/// a game on a server, which referees it.
+#[derive(Debug, Clone)]
+pub struct Online {
+    /// `None` when spectating.
+    pub team: Option<Team>,
+    /// the last state the server sent, and when it came.
+    pub view: Option<(GameView, Instant)>,
+    /// the actions played here, for the server to check.
+    pub outbox: Vec<Move>,
+    pub connected: bool,
+}
+
+/// everything on the screen, and what the keys do to it.
+#[derive(Debug, Clone)]
+pub struct App {
+    pub game: Game,
+    /// every action so far, with the game it was played in.
+    history: Vec<(Game, Move)>,
+    pub cursor: Pos,
+    pub selected: Vec<Pos>,
+    pub mode: Mode,
+    pub ai: Option<Ai>,
+    pub online: Option<Online>,
+    /// the line under the board, for errors and hints.
+    pub message: String,
+    pub show_help: bool,
+    /// the position in words instead of the board.
+    pub show_text: bool,
+    pub quit: bool,
+}
+
+impl App {
+    pub fn new(game: Game, ai: Option<Ai>) -> Self {
+        Self {
+            game,
+            history: vec![],
+            cursor: Pos(44),
+            selected: vec![],
+            mode: Mode::Move,
+            ai,
+            online: None,
+            message: "Press ? for help.".to_owned(),
+            show_help: false,
+            show_text: false,
+            quit: false,
+        }
+    }
+
+    /// whether the computer should play next.
+    pub fn ai_to_move(&self) -> bool {
+        self.game.is_ongoing() && self.ai.as_ref().is_some_and(|ai| ai.team == self.game.turn)
+    }
+
+    /// an app for a game on a server, which starts once the server sends its state.
+    pub fn online() -> Self {
+        Self {
+            online: Some(Online {
+                team: None,
+                view: None,
+                outbox: vec![],
+                connected: false,
+            }),
+            message: "Connecting...".to_owned(),
+            ..Self::new(Game::new(), None)
+        }
+    }
+
+    /// whether the keys here may play for the team to move.
+    pub fn can_play(&self) -> bool {
+        self.online
+            .as_ref()
+            .is_none_or(|online| online.team == Some(self.game.turn))
+    }
+
+    /// takes the game from the server, with its history.
+    pub fn update(&mut self, view: GameView) {
+        if self.online.is_none() {
+            return;
+        }
+        let Ok(game) = view.game() else {
+            self.message = "The server sent a game that cannot be replayed.".to_owned();
+            return;
+        };
+        let mut replay = Game::from(view.start.clone());
+        self.history.clear();
+        for action in &view.actions {
+            // the whole record was just replayed.
+            let verified = replay.verify_action(action.clone()).unwrap();
+            self.history.push((replay.clone(), action.clone()));
+            replay.make_move(verified);
+        }
+        if game.position() != self.game.position() {
+            self.selected.clear();
+        }
+        self.game = game;
+        let loser = match view.result {
+            Some(Winner(Some(winner))) => Some(!winner),
+            _ => None,
+        };
+        self.message = match (loser, view.termination.as_deref()) {
+            (Some(loser), Some("time")) => format!("{loser} ran out of time. {}", self.status()),
+            (Some(loser), Some("left")) => format!("{loser} left the game. {}", self.status()),
+            _ if view.blue.is_none() || view.red.is_none() => {
+                format!("Waiting for an opponent in {}...", view.lobby)
+            }
+            _ => self.status(),
+        };
+        if let Some(online) = &mut self.online {
+            online.view = Some((view, Instant::now()));
+        }
+    }
+
+    /// the tiles the selected piece can move to.
+    pub fn destinations(&self) -> Vec<Pos> {
+        match (self.mode, &self.selected[..]) {
+            (Mode::Move, &[from]) => self.game.destinations(from),
+            _ => vec![],
+        }
+    }
+
+    /// the board with the cursor, the selection and the last action on it.
+    pub fn renderer(&self) -> Renderer {
+        Renderer {
+            style: Style::Box,
+            selected: self.selected.clone(),
+            cursor: Some(self.cursor),
+            last_move: self.history.last().map(|(_, action)| action.clone()),
+            destinations: self.destinations(),
+            ..Default::default()
+        }
+    }
+
+    /// every action so far, numbered by turn, as `3. Red: move 71 to 61`.
+    pub fn log(&self) -> Vec<String> {
+        let mut turn = 1;
+        let mut log = vec![];
+        for (i, (game, action)) in self.history.iter().enumerate() {
+            log.push(format!("{turn}. {}: {action}", game.turn));
+            let next = self.history.get(i + 1).map_or(&self.game, |(game, _)| game);
+            if next.turn != game.turn {
+                turn += 1;
+            }
+        }
+        log
+    }
+
+    /// plays the action for the team to move, and clears the selection.
+    pub fn play(&mut self, action: Move) -> Result<(), InvalidMove> {
+        let verified = self.game.verify_action(action.clone())?;
+        self.history.push((self.game.clone(), action.clone()));
+        self.game.make_move(verified);
+        self.selected.clear();
+        self.mode = Mode::Move;
+        self.message = self.status();
+        if let Some(online) = &mut self.online {
+            online.outbox.push(action);
+        }
+        Ok(())
+    }
+
+    /// what the state of the game asks of the players.
+    fn status(&self) -> String {
+        match self.game.state {
+            GameState::Finished(winner) if self.online.is_some() => {
+                format!("{winner} Press q to quit.")
+            }
+            GameState::Finished(winner) => format!("{winner} Press u to undo or q to quit."),
+            GameState::Ongoing {
+                draw_offer: Some(team),
+            } if team != self.game.turn => {
+                format!("{team} offers a draw. D accepts, X declines.")
+            }
+            GameState::Ongoing { .. } => String::new(),
+        }
+    }
+
+    /// the position the game started from, and every action since.
+    pub fn line(&self) -> (Position, Vec<Move>) {
+        let start = self.history.first().map_or(&self.game, |(game, _)| game);
+        let actions = self.history.iter().map(|(_, action)| action.clone());
+        (start.position(), actions.collect())
+    }
+
+    /// plays the action the computer found.
+    pub fn play_ai(&mut self, action: Move) {
+        if let Err(e) = self.play(action) {
+            self.message = format!("The computer tried an illegal action: {e}");
+        }
+    }
+
+    /// takes back the last action, and the computer's answers to it.
+    pub fn undo(&mut self) {
+        while let Some((game, _)) = self.history.pop() {
+            self.game = game;
+            if !self.ai_to_move() {
+                break;
+            }
+        }
+        self.selected.clear();
+        self.message = String::new();
+    }
+
+    fn try_play(&mut self, action: Move) {
+        if let Err(e) = self.play(action) {
+            self.message = e.to_string();
+        }
+    }
+
+    fn select(&mut self) {
+        let cursor = self.cursor;
+        match self.mode {
+            Mode::Move if self.selected == [cursor] => self.selected.clear(),
+            Mode::Move if self.destinations().contains(&cursor) => {
+                let from = self.selected[0];
+                self.try_play(Move::Move { from, to: cursor });
+            }
+            Mode::Move => {
+                let own = self.game.board[cursor]
+                    .0
+                    .is_some_and(|p| p.team == self.game.turn);
+                self.selected.clear();
+                if own {
+                    self.selected.push(cursor);
+                    if self.destinations().is_empty() {
+                        self.message = "That piece cannot move.".to_owned();
+                    }
+                }
+            }
+            Mode::Merge => match self.selected.iter().position(|&p| p == cursor) {
+                Some(i) => {
+                    self.selected.remove(i);
+                }
+                None => self.selected.push(cursor),
+            },
+        }
+    }
+
+    fn merge(&mut self, kind: PieceKind) {
+        // the cursor is the destination, which goes last.
+        let mut pieces = self.selected.clone();
+        pieces.retain(|&p| p != self.cursor);
+        pieces.push(self.cursor);
+        self.try_play(Move::Merge { kind, pieces });
+    }
+
+    pub fn handle(&mut self, key: Key) {
+        // the computer's turn only lets the player leave.
+        if self.ai_to_move() {
+            self.quit |= key == Key::Char('q');
+            return;
+        }
+        let [x, y] = self.cursor.xy();
+        match key {
+            Key::Up => self.cursor = Pos((y + 9) % 10 * 10 + x),
+            Key::Down => self.cursor = Pos((y + 1) % 10 * 10 + x),
+            Key::Left => self.cursor = Pos(y * 10 + (x + 9) % 10),
+            Key::Right => self.cursor = Pos(y * 10 + (x + 1) % 10),
+            Key::Char('k') => self.handle(Key::Up),
+            Key::Char('j') => self.handle(Key::Down),
+            Key::Char('h') => self.handle(Key::Left),
+            Key::Char('l') => self.handle(Key::Right),
+            Key::Char('q') => self.quit = true,
+            Key::Char('?') => self.show_help = !self.show_help,
+            Key::Char('t') => self.show_text = !self.show_text,
+            Key::Char('u') if self.online.is_some() => {
+                self.message = "The server keeps every action.".to_owned()
+            }
+            Key::Char('u') => self.undo(),
+            _ if !self.game.is_ongoing() => self.message = "The game is over.".to_owned(),
+            _ if !self.can_play() => {
+                self.message = match self.online.as_ref().and_then(|online| online.team) {
+                    Some(_) => "It is not your turn.",
+                    None => "Spectators cannot play.",
+                }
+                .to_owned()
+            }
+            Key::Select => self.select(),
+            Key::Escape => {
+                self.selected.clear();
+                self.mode = Mode::Move;
+                self.message = String::new();
+            }
+            Key::Char('m') => {
+                self.selected.clear();
+                self.mode = match self.mode {
+                    Mode::Move => Mode::Merge,
+                    Mode::Merge => Mode::Move,
+                };
+            }
+            Key::Char('D') => self.try_play(Move::Draw),
+            Key::Char('X') => self.try_play(Move::DeclineDraw),
+            Key::Char('R') => self.try_play(Move::Resign),
+            Key::Char(c) if self.mode == Mode::Merge => {
+                if let Some(&(_, kind)) = MERGE_KEYS.iter().find(|(key, _)| *key == c) {
+                    self.merge(kind);
+                }
+            }
+            Key::Char(_) => {}
+        }
+    }
+}
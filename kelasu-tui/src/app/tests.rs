@@ -0,0 +1,193 @@
+use super::*;
+use crate::computer::{Brain, Computer};
+use kelasu_game::{board::Board, eval::Weights, piece::Tile};
+
+fn press(app: &mut App, keys: &str) {
+    for c in keys.chars() {
+        app.handle(match c {
+            ' ' => Key::Select,
+            c => Key::Char(c),
+        });
+    }
+}
+
+#[test]
+pub(crate) fn test_move() {
+    let mut app = App::new(Game::new(), None);
+    // from 44 up to 11, then select it and move it down onto 21.
+    press(&mut app, "kkkhhh ");
+    assert_eq!(app.cursor, Pos(11));
+    assert_eq!(app.selected, [Pos(11)]);
+    assert!(app.destinations().contains(&Pos(21)));
+    press(&mut app, "j ");
+    assert_eq!(app.game.power, 3);
+    assert!(app.selected.is_empty());
+    assert_eq!(app.log(), ["1. Blue: move 11 to 21"]);
+
+    // red pieces cannot be picked.
+    app.cursor = Pos(81);
+    press(&mut app, " ");
+    assert!(app.selected.is_empty());
+
+    press(&mut app, "u");
+    assert_eq!(app.game.power, 4);
+    assert!(app.log().is_empty());
+}
+
+#[test]
+pub(crate) fn test_merge() {
+    let mut board = Board::new();
+    for pos in [30, 31] {
+        board[Pos(pos)] = Tile::try_from('B').unwrap();
+    }
+    let mut app = App::new(Game::from_position(Team::Blue, board), None);
+    app.cursor = Pos(30);
+    press(&mut app, "m l ");
+    assert_eq!(app.mode, Mode::Merge);
+    assert_eq!(app.selected, [Pos(30), Pos(31)]);
+    // the cursor is on 31, so the warrior goes there.
+    press(&mut app, "w");
+    assert!(app.message.is_empty(), "{}", app.message);
+    assert_eq!(app.mode, Mode::Move);
+    assert_eq!(app.log(), ["1. Blue: merge warrior at 31 with 30"]);
+}
+
+#[test]
+pub(crate) fn test_ai() {
+    let ai = Ai {
+        team: Team::Red,
+        limits: Limits {
+            depth: Some(1),
+            time: None,
+        },
+    };
+    let mut app = App::new(Game::new(), Some(ai));
+    for action in [
+        "move 11 to 21",
+        "move 13 to 23",
+        "move 14 to 24",
+        "move 15 to 25",
+    ] {
+        app.play(action.parse().unwrap()).unwrap();
+    }
+    assert!(app.ai_to_move());
+    // the computer's turn ignores the keys.
+    press(&mut app, "u");
+    assert_eq!(app.log().len(), 4);
+    let mut computer = Computer::new(Brain::Search(Weights::default()));
+    while app.ai_to_move() {
+        computer.think(&app);
+        let best = loop {
+            if let Some(answer) = computer.answer() {
+                break answer.unwrap();
+            }
+        };
+        app.play_ai(best.unwrap());
+    }
+    assert_eq!(app.game.turn, Team::Blue);
+    assert!(app.log().len() > 4);
+
+    // undoing takes back the computer's turn too.
+    press(&mut app, "u");
+    assert_eq!(app.log().len(), 3);
+    assert_eq!(app.game.turn, Team::Blue);
+
+    // the keys can still quit while the computer thinks.
+    app.play("move 15 to 25".parse().unwrap()).unwrap();
+    assert!(app.ai_to_move());
+    press(&mut app, "q");
+    assert!(app.quit);
+}
+
+#[test]
+pub(crate) fn test_online() {
+    let mut app = App::online();
+    let mut game = Game::new();
+    let start = game.position();
+    let action: Move = "move 11 to 21".parse().unwrap();
+    game.make_move(game.verify_action(action.clone()).unwrap());
+    let view = GameView {
+        lobby: "den".to_owned(),
+        blue: Some("alice".to_owned()),
+        red: Some("bob".to_owned()),
+        connected: [true, true],
+        start,
+        actions: vec![action.clone()],
+        position: game.position(),
+        clock: None,
+        result: None,
+        termination: None,
+        spectators: 0,
+    };
+    app.update(view.clone());
+    assert_eq!(app.game.position(), game.position());
+    assert_eq!(app.log(), ["1. Blue: move 11 to 21"]);
+
+    // spectators only watch.
+    app.cursor = Pos(13);
+    press(&mut app, " ");
+    assert!(app.selected.is_empty());
+    assert_eq!(app.message, "Spectators cannot play.");
+
+    // actions are played here at once, and sent to the server, which keeps them.
+    app.online.as_mut().unwrap().team = Some(Team::Blue);
+    press(&mut app, " j ");
+    assert_eq!(app.log().len(), 2);
+    assert_eq!(
+        app.online.as_ref().unwrap().outbox,
+        ["move 13 to 23".parse().unwrap()]
+    );
+    press(&mut app, "u");
+    assert_eq!(app.log().len(), 2);
+
+    // the server has the last word.
+    let mut position = view.position.clone();
+    position.state = GameState::Finished(Winner(Some(Team::Red)));
+    app.update(GameView {
+        position,
+        result: Some(Winner(Some(Team::Red))),
+        termination: Some("time".to_owned()),
+        ..view
+    });
+    assert_eq!(app.log().len(), 1);
+    assert!(!app.game.is_ongoing());
+    assert!(app.message.starts_with("Blue ran out of time."));
+}
+
+#[test]
+pub(crate) fn test_engine() {
+    use kelasu_game::protocol::{serve, Engine};
+    use std::io::{pipe, BufReader};
+
+    let (engine_input, to_engine) = pipe().unwrap();
+    let (from_engine, engine_output) = pipe().unwrap();
+    std::thread::spawn(move || serve(BufReader::new(engine_input), engine_output));
+    let engine = Engine::connect(BufReader::new(from_engine), to_engine).unwrap();
+
+    let ai = Ai {
+        team: Team::Red,
+        limits: Limits::default(),
+    };
+    let mut app = App::new(Game::new(), Some(ai));
+    for action in [
+        "move 11 to 21",
+        "move 13 to 23",
+        "move 14 to 24",
+        "move 15 to 25",
+    ] {
+        app.play(action.parse().unwrap()).unwrap();
+    }
+    // without limits, the engine thinks until it is stopped.
+    let mut computer = Computer::new(Brain::Engine(engine));
+    computer.think(&app);
+    std::thread::sleep(std::time::Duration::from_millis(200));
+    assert!(computer.answer().is_none());
+    computer.stop();
+    let best = loop {
+        if let Some(answer) = computer.answer() {
+            break answer.unwrap();
+        }
+    };
+    app.play_ai(best.unwrap());
+    assert_eq!(app.log().len(), 5);
+}
@@ -0,0 +1,139 @@
+use crate::app::App;
+use kelasu_game::{
+    board::{Move, Position},
+    eval::Weights,
+    protocol::{Engine, Stopper},
+    search::{search, Limits},
+    Game,
+};
+use std::{
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        mpsc::{self, Receiver, Sender, TryRecvError},
+        Arc,
+    },
+    thread,
+};
+
+/// where the computer's actions come from.
+pub enum Brain {
+    /// the search of this crate's library.
+    Search(Weights),
+    /// an engine process that speaks the protocol.
+    Engine(Engine),
+}
+
+/// what the computer is asked to think about.
+struct Request {
+    start: Position,
+    actions: Vec<Move>,
+    game: Game,
+    limits: Limits,
+}
+
+/// the best action, `None` if the game is over, or why the engine failed.
+type Answer = Result<Option<Move>, String>;
+
+/// stops the search that is running, whichever brain runs it.
+enum Stop {
+    Flag(Arc<AtomicBool>),
+    Engine(Stopper),
+}
+
+/// a computer player that thinks on its own thread, so the keys still work meanwhile.
+pub struct Computer {
+    requests: Sender<Request>,
+    answers: Receiver<Answer>,
+    stop: Stop,
+    thinking: bool,
+}
+
+fn work(
+    mut brain: Brain,
+    flag: Arc<AtomicBool>,
+    requests: Receiver<Request>,
+    answers: Sender<Answer>,
+) {
+    for request in requests {
+        flag.store(false, Ordering::Relaxed);
+        let answer = match &mut brain {
+            Brain::Search(weights) => Ok(search(
+                &request.game,
+                weights,
+                &request.limits,
+                &flag,
+                |_| {},
+            )),
+            Brain::Engine(engine) => engine
+                .set_position(Some(&request.start), &request.actions)
+                .and_then(|()| engine.go(&request.limits, |_| {}))
+                .map_err(|e| e.to_string()),
+        };
+        if answers.send(answer).is_err() {
+            return;
+        }
+    }
+}
+
+impl Computer {
+    pub fn new(brain: Brain) -> Self {
+        let flag = Arc::new(AtomicBool::new(false));
+        let stop = match &brain {
+            Brain::Search(_) => Stop::Flag(flag.clone()),
+            Brain::Engine(engine) => Stop::Engine(engine.stopper()),
+        };
+        let (requests, to_work) = mpsc::channel();
+        let (answered, answers) = mpsc::channel();
+        thread::spawn(move || work(brain, flag, to_work, answered));
+        Self {
+            requests,
+            answers,
+            stop,
+            thinking: false,
+        }
+    }
+
+    pub fn is_thinking(&self) -> bool {
+        self.thinking
+    }
+
+    /// starts thinking about the game of the app, with the limits of its computer player.
+    pub fn think(&mut self, app: &App) {
+        let Some(ai) = &app.ai else {
+            return;
+        };
+        let (start, actions) = app.line();
+        let request = Request {
+            start,
+            actions,
+            game: app.game.clone(),
+            limits: ai.limits.clone(),
+        };
+        self.thinking = self.requests.send(request).is_ok();
+    }
+
+    /// the answer, once the thinking is over.
+    pub fn answer(&mut self) -> Option<Answer> {
+        if !self.thinking {
+            return None;
+        }
+        let answer = match self.answers.try_recv() {
+            Ok(answer) => answer,
+            Err(TryRecvError::Empty) => return None,
+            Err(TryRecvError::Disconnected) => Err("The computer stopped.".to_owned()),
+        };
+        self.thinking = false;
+        Some(answer)
+    }
+
+    /// asks the search to finish as soon as it can.
+    pub fn stop(&self) {
+        match &self.stop {
+            Stop::Flag(flag) => flag.store(true, Ordering::Relaxed),
+            Stop::Engine(stopper) => {
+                // an engine that is gone has stopped already.
+                let _ = stopper.stop();
+            }
+        }
+    }
+}
@@ -0,0 +1,245 @@
+use app::{Ai, App, Key};
+use computer::{Brain, Computer};
+use crossterm::{
+    cursor::{Hide, Show},
+    event::{self, Event, KeyCode, KeyEventKind},
+    execute,
+    terminal::{self, EnterAlternateScreen, LeaveAlternateScreen},
+};
+use kelasu_game::{
+    eval::Weights,
+    lan::{ClientMessage, Clock},
+    piece::Team,
+    protocol::Engine,
+    search::Limits,
+    setup::Setup,
+};
+use online::Session;
+use std::{
+    env,
+    io::{self, stdout, Write},
+    process::exit,
+    time::Duration,
+};
+
+mod app;
+mod computer;
+mod online;
+mod ui;
+
+const USAGE: &str = "\
+    Usage: kelasu-tui [--ai <blue|red>] [--time <ms>] [--engine <command>] [--setup <setup>]\n\
+    \x20      kelasu-tui --connect <address> --name <name> [--token <token>]\n\
+    \x20                 [--host <lobby> [--setup <setup>] [--team <team>] [--clock <m+s>]\n\
+    \x20                 | --join <lobby> | --spectate <lobby>]\n\
+    \n\
+    Plays Kelasu in the terminal, on one keyboard, against the computer,\n\
+    or against someone else through a `kelasu-server`.\n\
+      \t--ai        the team the computer plays (default none, for two players)\n\
+      \t--time      how long the computer thinks about each action (default 2000)\n\
+      \t--engine    an engine for the computer to play with, e.g. `kelasu-engine` (default the built-in search)\n\
+      \t--setup     the starting setup, e.g. `wings` or `kelasu960 42` (default standard)\n\
+      \t--connect   the server, e.g. `192.168.1.20:7878`. without a lobby, lists the lobbies\n\
+      \t--name      the name to play under\n\
+      \t--token     resumes the session that was given this token\n\
+      \t--host      opens a lobby and waits in it for an opponent\n\
+      \t--team      the team the host plays (default blue)\n\
+      \t--clock     the time each team has, in minutes, plus seconds per turn (default untimed)\n\
+      \t--join      plays in a lobby that is waiting for an opponent\n\
+      \t--spectate  watches the game in a lobby";
+
+fn fail(message: impl std::fmt::Display) -> ! {
+    eprintln!("{message}\n\n{USAGE}");
+    exit(1);
+}
+
+pub(crate) fn key(code: KeyCode) -> Option<Key> {
+    Some(match code {
+        KeyCode::Up => Key::Up,
+        KeyCode::Down => Key::Down,
+        KeyCode::Left => Key::Left,
+        KeyCode::Right => Key::Right,
+        KeyCode::Enter | KeyCode::Char(' ') => Key::Select,
+        KeyCode::Esc => Key::Escape,
+        KeyCode::Char(c) => Key::Char(c),
+        _ => return None,
+    })
+}
+
+/// how long to wait for a key before looking at the computer again.
+const POLL: Duration = Duration::from_millis(50);
+
+fn run(out: &mut impl Write, app: &mut App, computer: &mut Computer) -> io::Result<()> {
+    while !app.quit {
+        if app.ai_to_move() && !computer.is_thinking() {
+            app.message = format!("{} is thinking...", app.game.turn);
+            computer.think(app);
+        }
+        match computer.answer() {
+            Some(Ok(Some(best))) => app.play_ai(best),
+            Some(Err(e)) => {
+                app.ai = None;
+                app.message = format!("{e} Both teams are played from the keyboard now.");
+            }
+            Some(Ok(None)) | None => {}
+        }
+        ui::draw(out, app)?;
+        if !event::poll(POLL)? {
+            continue;
+        }
+        if let Event::Key(event) = event::read()? {
+            if let (KeyEventKind::Press, Some(key)) = (event.kind, key(event.code)) {
+                app.handle(key);
+            }
+        }
+    }
+    computer.stop();
+    Ok(())
+}
+
+fn main() -> io::Result<()> {
+    let mut ai = None;
+    let mut time = 2000;
+    let mut setup = Setup::Standard;
+    let mut address = None;
+    let mut name = None;
+    let mut token = None;
+    let mut team = Team::Blue;
+    let mut clock = None;
+    let mut lobby = None;
+    let mut engine = None;
+
+    let mut args = env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--ai" => {
+                ai = Some(
+                    args.next()
+                        .and_then(|team| team.parse().ok())
+                        .unwrap_or_else(|| fail("--ai needs a team.")),
+                )
+            }
+            "--time" => {
+                time = args
+                    .next()
+                    .and_then(|n| n.parse().ok())
+                    .unwrap_or_else(|| fail("--time needs a number."))
+            }
+            "--engine" => {
+                engine = Some(
+                    args.next()
+                        .unwrap_or_else(|| fail("--engine needs a command.")),
+                )
+            }
+            "--setup" => {
+                setup = args
+                    .next()
+                    .ok_or_else(|| "--setup needs a setup.".to_owned())
+                    .and_then(|s| s.parse().map_err(|e| format!("{e}")))
+                    .unwrap_or_else(|e| fail(e))
+            }
+            "--connect" => {
+                address = Some(
+                    args.next()
+                        .unwrap_or_else(|| fail("--connect needs an address.")),
+                )
+            }
+            "--name" => name = Some(args.next().unwrap_or_else(|| fail("--name needs a name."))),
+            "--token" => {
+                token = Some(
+                    args.next()
+                        .unwrap_or_else(|| fail("--token needs a token.")),
+                )
+            }
+            "--team" => {
+                team = args
+                    .next()
+                    .and_then(|team| team.parse().ok())
+                    .unwrap_or_else(|| fail("--team needs a team."))
+            }
+            "--clock" => {
+                clock = Some(
+                    args.next()
+                        .and_then(|clock| clock.parse::<Clock>().ok())
+                        .unwrap_or_else(|| fail("--clock needs a clock, e.g. `5+3`.")),
+                )
+            }
+            "--host" | "--join" | "--spectate" => {
+                let name = args
+                    .next()
+                    .unwrap_or_else(|| fail(format!("{arg} needs a lobby.")));
+                lobby = Some((arg, name));
+            }
+            _ => fail(format!("Unexpected argument: {arg}")),
+        }
+    }
+
+    let mut session = None;
+    if let Some(address) = address {
+        let name = name.unwrap_or_else(|| fail("--name is missing."));
+        let mut online = Session::new(address, name, token);
+        let Some((kind, lobby)) = lobby else {
+            for lobby in online.lobbies()? {
+                println!("{}", online::lobby_line(&lobby));
+            }
+            return Ok(());
+        };
+        online.request = Some(match kind.as_str() {
+            "--host" => ClientMessage::Host {
+                lobby,
+                setup,
+                team,
+                clock,
+            },
+            "--join" => ClientMessage::Join { lobby },
+            _ => ClientMessage::Spectate { lobby },
+        });
+        session = Some(online);
+    }
+    let ai = ai.map(|team| Ai {
+        team,
+        limits: Limits {
+            depth: None,
+            time: Some(Duration::from_millis(time)),
+        },
+    });
+    let brain = match engine {
+        None => Brain::Search(Weights::default()),
+        Some(_) if ai.is_none() => fail("--engine needs --ai."),
+        Some(command) => {
+            let mut words = command.split_whitespace();
+            let program = words
+                .next()
+                .unwrap_or_else(|| fail("--engine needs a command."));
+            Brain::Engine(
+                Engine::spawn(program, words).unwrap_or_else(|e| fail(format!("{command}: {e}"))),
+            )
+        }
+    };
+    let mut app = match session {
+        Some(_) => App::online(),
+        None => App::new(setup.game(), ai),
+    };
+
+    // leave the terminal usable even if something goes wrong.
+    let hook = std::panic::take_hook();
+    std::panic::set_hook(Box::new(move |info| {
+        let _ = execute!(stdout(), Show, LeaveAlternateScreen);
+        let _ = terminal::disable_raw_mode();
+        hook(info);
+    }));
+
+    let mut out = stdout();
+    terminal::enable_raw_mode()?;
+    execute!(out, EnterAlternateScreen, Hide)?;
+    let result = match &mut session {
+        Some(session) => online::run(&mut out, &mut app, session),
+        None => run(&mut out, &mut app, &mut Computer::new(brain)),
+    };
+    execute!(out, Show, LeaveAlternateScreen)?;
+    terminal::disable_raw_mode()?;
+    if let Some(token) = session.and_then(|session| session.token) {
+        println!("To come back to this session, add --token {token}");
+    }
+    result
+}
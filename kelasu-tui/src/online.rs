@@ -0,0 +1,155 @@
+use crate::{app::App, key, ui};
+use crossterm::event::{self, Event, KeyEventKind};
+use kelasu_game::lan::{Client, ClientMessage, LobbyInfo, ServerMessage, Status};
+use std::{
+    io::{self, Write},
+    sync::mpsc::{Receiver, TryRecvError},
+    time::{Duration, Instant},
+};
+
+/// how long to wait for a key before looking at the server again.
+const POLL: Duration = Duration::from_millis(100);
+/// how long to wait between tries to reconnect.
+const RETRY: Duration = Duration::from_secs(2);
+
+/// a session on a server, which outlives its connections.
+pub struct Session {
+    pub address: String,
+    pub name: String,
+    /// from the server's welcome, to resume the session after losing the connection.
+    pub token: Option<String>,
+    /// what to send once the server says welcome, unless a session is being resumed.
+    pub request: Option<ClientMessage>,
+    connection: Option<(Client, Receiver<ServerMessage>)>,
+    last_try: Option<Instant>,
+}
+
+impl Session {
+    pub fn new(address: String, name: String, token: Option<String>) -> Self {
+        Self {
+            address,
+            name,
+            token,
+            request: None,
+            connection: None,
+            last_try: None,
+        }
+    }
+
+    /// connects, unless it did so a moment ago.
+    fn connect(&mut self) {
+        if self.last_try.is_some_and(|at| at.elapsed() < RETRY) {
+            return;
+        }
+        self.last_try = Some(Instant::now());
+        self.connection = Client::connect(&self.address, &self.name, self.token.clone()).ok();
+    }
+
+    fn send(&mut self, message: &ClientMessage) {
+        if let Some((client, _)) = &self.connection {
+            if client.send(message).is_err() {
+                self.connection = None;
+            }
+        }
+    }
+
+    /// the next message from the server, if any has come.
+    fn receive(&mut self) -> Option<ServerMessage> {
+        let (_, messages) = self.connection.as_ref()?;
+        match messages.try_recv() {
+            Ok(message) => Some(message),
+            Err(TryRecvError::Empty) => None,
+            Err(TryRecvError::Disconnected) => {
+                self.connection = None;
+                None
+            }
+        }
+    }
+
+    /// asks for the lobbies, and waits for them.
+    pub fn lobbies(&mut self) -> io::Result<Vec<LobbyInfo>> {
+        self.connection = Some(Client::connect(
+            &self.address,
+            &self.name,
+            self.token.clone(),
+        )?);
+        self.send(&ClientMessage::Lobbies);
+        let (_, messages) = self.connection.as_ref().unwrap();
+        loop {
+            match messages.recv() {
+                Ok(ServerMessage::Lobbies(lobbies)) => return Ok(lobbies),
+                Ok(ServerMessage::Error(message)) => return Err(io::Error::other(message)),
+                Ok(_) => {}
+                Err(_) => return Err(io::ErrorKind::ConnectionAborted.into()),
+            }
+        }
+    }
+}
+
+/// one line for the list of lobbies.
+pub fn lobby_line(lobby: &LobbyInfo) -> String {
+    let status = match lobby.status {
+        Status::Waiting => "waiting",
+        Status::Playing => "playing",
+        Status::Finished => "finished",
+    };
+    let clock = lobby
+        .clock
+        .map_or("untimed".to_owned(), |clock| clock.to_string());
+    format!(
+        "{}: {} vs {}, {status}, {}, {clock}, {} watching",
+        lobby.name,
+        lobby.blue.as_deref().unwrap_or("(free)"),
+        lobby.red.as_deref().unwrap_or("(free)"),
+        lobby.setup,
+        lobby.spectators,
+    )
+}
+
+fn handle(app: &mut App, session: &mut Session, message: ServerMessage) {
+    match message {
+        ServerMessage::Welcome { token, .. } => {
+            session.token = Some(token);
+            if let Some(request) = session.request.take() {
+                session.send(&request);
+            }
+        }
+        ServerMessage::Joined { team, .. } => {
+            if let Some(online) = &mut app.online {
+                online.team = team;
+            }
+        }
+        ServerMessage::State(view) => app.update(*view),
+        ServerMessage::Error(message) => app.message = message,
+        ServerMessage::Left => app.quit = true,
+        ServerMessage::Lobbies(_) => {}
+    }
+}
+
+/// plays on the server until the keys say to quit.
+pub fn run(out: &mut impl Write, app: &mut App, session: &mut Session) -> io::Result<()> {
+    while !app.quit {
+        if session.connection.is_none() {
+            session.connect();
+        }
+        while let Some(message) = session.receive() {
+            handle(app, session, message);
+        }
+        if let Some(online) = &mut app.online {
+            online.connected = session.connection.is_some();
+            for action in std::mem::take(&mut online.outbox) {
+                session.send(&ClientMessage::Action(action));
+            }
+        }
+        ui::draw(out, app)?;
+        if !event::poll(POLL)? {
+            continue;
+        }
+        if let Event::Key(event) = event::read()? {
+            if let (KeyEventKind::Press, Some(key)) = (event.kind, key(event.code)) {
+                app.handle(key);
+            }
+        }
+    }
+    Ok(())
+}
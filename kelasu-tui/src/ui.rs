@@ -0,0 +1,213 @@
+use crate::app::{App, Mode, Online, HELP};
+use crossterm::{
+    cursor::MoveTo,
+    queue,
+    style::{Attribute, Color, Print, ResetColor, SetAttribute, SetForegroundColor},
+    terminal::{Clear, ClearType},
+};
+use kelasu_game::{
+    board::Pos,
+    piece::Team,
+    render::{describe, describe_tile},
+    Game,
+};
+use std::{
+    io::{self, Write},
+    time::Duration,
+};
+
+/// where the panel starts, right of the board.
+const PANEL: u16 = 30;
+/// how many actions of the log fit in the panel.
+const LOG_LINES: usize = 12;
+/// how wide the text view may get before wrapping.
+const TEXT_WIDTH: usize = 78;
+
+fn team_color(team: Team) -> Color {
+    match team {
+        Team::Blue => Color::Blue,
+        Team::Red => Color::Red,
+    }
+}
+
+/// the tile a character of a rendered box row belongs to, if it is a piece or an empty tile.
+fn tile_at(y: usize, column: usize) -> Option<Pos> {
+    let x = column.checked_sub(5)?;
+    (x % 2 == 0 && x / 2 < 10).then(|| Pos((y * 10 + x / 2) as i8))
+}
+
+/// one row of the rendered board, coloured by team, with locked pieces dimmed
+/// and the fences highlighted.
+fn draw_row(out: &mut impl Write, game: &Game, y: Option<usize>, line: &str) -> io::Result<()> {
+    for (column, c) in line.chars().enumerate() {
+        let tile = y.and_then(|y| tile_at(y, column));
+        let piece = tile.and_then(|pos| game.board[pos].0);
+        match (tile, piece) {
+            (Some(pos), Some(piece)) => {
+                queue!(
+                    out,
+                    SetForegroundColor(team_color(piece.team)),
+                    SetAttribute(Attribute::Bold)
+                )?;
+                if game.locked_tiles.contains(&pos) {
+                    queue!(out, SetAttribute(Attribute::Dim))?;
+                }
+            }
+            _ if "()[]<>{}|".contains(c) && y.is_some() => {
+                queue!(
+                    out,
+                    SetForegroundColor(Color::Yellow),
+                    SetAttribute(Attribute::Bold)
+                )?;
+            }
+            _ if c == ':' => queue!(out, SetForegroundColor(Color::DarkYellow))?,
+            _ => {}
+        }
+        queue!(out, Print(c), SetAttribute(Attribute::Reset), ResetColor)?;
+    }
+    Ok(())
+}
+
+/// minutes and seconds, as `4:05`.
+fn clock_text(time: Duration) -> String {
+    let seconds = time.as_secs();
+    format!("{}:{:02}", seconds / 60, seconds % 60)
+}
+
+/// who plays whom on the server, and how long they have left.
+fn players(app: &App, online: &Online) -> Vec<String> {
+    let Some((view, since)) = &online.view else {
+        return vec!["Kelasu online".to_owned()];
+    };
+    let mut lines = vec![format!("Kelasu online: {}", view.lobby)];
+    for team in [Team::Blue, Team::Red] {
+        let name = match team {
+            Team::Blue => &view.blue,
+            Team::Red => &view.red,
+        };
+        let mut line = format!("{team}: {}", name.as_deref().unwrap_or("(free)"));
+        if let Some(clock) = view.clock {
+            // the server's clock has run since it sent the state.
+            let running = app.game.is_ongoing()
+                && app.game.turn == team
+                && view.blue.is_some()
+                && view.red.is_some();
+            let left = if running {
+                clock[team as usize].saturating_sub(since.elapsed())
+            } else {
+                clock[team as usize]
+            };
+            line += &format!(" {}", clock_text(left));
+        }
+        if online.team == Some(team) {
+            line += " (you)";
+        } else if name.is_some() && !view.connected[team as usize] {
+            line += " (away)";
+        }
+        lines.push(line);
+    }
+    if view.spectators > 0 {
+        lines.push(format!("Watching: {}", view.spectators));
+    }
+    lines
+}
+
+fn panel(app: &App) -> Vec<String> {
+    let game = &app.game;
+    let mut lines = match (&app.online, &app.ai) {
+        (Some(online), _) => players(app, online),
+        (None, Some(ai)) => vec![format!("Kelasu against the computer ({})", ai.team)],
+        (None, None) => vec!["Kelasu, hot seat".to_owned()],
+    };
+    if app.online.as_ref().is_some_and(|online| !online.connected) {
+        lines.push("Connection lost. Reconnecting...".to_owned());
+    }
+    lines.extend([
+        String::new(),
+        format!("{} to move", game.turn),
+        format!("Energy: {}", "#".repeat(game.power as usize)),
+    ]);
+    let mut locked = game
+        .locked_tiles
+        .iter()
+        .map(|p| format!("{:02}", p.0))
+        .collect::<Vec<_>>();
+    locked.sort();
+    lines.push(format!(
+        "Locked: {}",
+        if locked.is_empty() {
+            "none".to_owned()
+        } else {
+            locked.join(" ")
+        }
+    ));
+    lines.push(format!("Turns until a draw: {}", game.turns_until_draw()));
+    lines.push(match app.mode {
+        Mode::Move => "Mode: move".to_owned(),
+        Mode::Merge => format!("Mode: merge ({} picked)", app.selected.len()),
+    });
+    lines.push(String::new());
+    lines.push("Log:".to_owned());
+    let log = app.log();
+    lines.extend(
+        log.iter()
+            .skip(log.len().saturating_sub(LOG_LINES))
+            .cloned(),
+    );
+    lines
+}
+
+/// the line in pieces no wider than `TEXT_WIDTH`, broken between words.
+/// the pieces after the first are indented.
+fn wrap(line: &str) -> Vec<String> {
+    let mut lines = vec![String::new()];
+    for word in line.split(' ') {
+        let last = lines.last_mut().unwrap();
+        if !last.trim().is_empty() && last.len() + 1 + word.len() > TEXT_WIDTH {
+            lines.push(format!("  {word}"));
+        } else {
+            if !last.is_empty() {
+                last.push(' ');
+            }
+            last.push_str(word);
+        }
+    }
+    lines
+}
+
+pub fn draw(out: &mut impl Write, app: &App) -> io::Result<()> {
+    queue!(out, Clear(ClearType::All))?;
+    let bottom = if app.show_text {
+        let text = describe(&app.game);
+        let lines = text.lines().flat_map(wrap).collect::<Vec<_>>();
+        for (i, line) in lines.iter().enumerate() {
+            queue!(out, MoveTo(0, i as u16), Print(line))?;
+        }
+        lines.len() as u16 + 1
+    } else {
+        let board = app.renderer().render(&app.game);
+        // the frame lines are not board rows.
+        for (i, line) in board.lines().enumerate() {
+            queue!(out, MoveTo(0, i as u16))?;
+            let y = (1..=10).contains(&i).then(|| i - 1);
+            draw_row(out, &app.game, y, line)?;
+        }
+        for (i, line) in panel(app).into_iter().enumerate() {
+            queue!(out, MoveTo(PANEL, i as u16), Print(line))?;
+        }
+        13
+    };
+    queue!(
+        out,
+        MoveTo(0, bottom),
+        Print(format!("Cursor: {}", describe_tile(&app.game, app.cursor))),
+        MoveTo(0, bottom + 1),
+        Print(&app.message),
+    )?;
+    if app.show_help {
+        for (i, line) in HELP.lines().enumerate() {
+            queue!(out, MoveTo(0, bottom + 3 + i as u16), Print(line))?;
+        }
+    }
+    out.flush()
+}